@@ -0,0 +1,80 @@
+//! Browses for one service type and prints a live table of the instances found.
+//!
+//! Usage: `cargo run --example browse -- _http._tcp.local`
+use mdns::{CancellationToken, MdnsConfig, MdnsService, RunMode};
+use tokio::signal;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{interval, Duration};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let service_type = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "_http._tcp.local".to_string());
+
+    // Browse only: nothing is advertised from this host.
+    let config = MdnsConfig {
+        run_mode: RunMode::QuerierOnly,
+        registry_print_interval: None,
+        ..MdnsConfig::default()
+    };
+    let mdns_service = MdnsService::with_config(config).await?;
+    let mut events = mdns_service.subscribe();
+
+    let shutdown = CancellationToken::new();
+    mdns_service
+        .clone()
+        .run(service_type.clone(), 5, 10, shutdown.clone())
+        .await;
+
+    // Redraw on every discovery event, and once a second so the TTLs count down.
+    let mut ticks = interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            _ = signal::ctrl_c() => break,
+            event = events.recv() => match event {
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+            _ = ticks.tick() => {}
+        }
+        print_table(&mdns_service, &service_type).await;
+    }
+
+    println!("(MAIN) Shutdown signal received. Exiting...");
+    shutdown.cancel();
+    Ok(())
+}
+
+/// Clears the terminal and prints every discovered instance of `service_type`.
+async fn print_table(mdns_service: &MdnsService, service_type: &str) {
+    let wanted = service_type.trim_end_matches('.');
+    let mut instances: Vec<_> = mdns_service
+        .registry
+        .instances()
+        .await
+        .into_values()
+        .filter(|instance| instance.service_type.eq_ignore_ascii_case(wanted))
+        .collect();
+    instances.sort_by(|a, b| a.instance.cmp(&b.instance));
+
+    print!("\x1b[2J\x1b[H");
+    println!("Browsing {} (Ctrl-C to exit)\n", wanted);
+    println!(
+        "{:<20} {:<40} {:<24} {:>6} {:>8}",
+        "TYPE", "INSTANCE", "HOST", "PORT", "TTL"
+    );
+    for instance in &instances {
+        let ttl = mdns_service
+            .registry
+            .discovered_remaining_ttl(&instance.instance)
+            .map_or_else(|| "-".to_string(), |ttl| format!("{}s", ttl));
+        println!(
+            "{:<20} {:<40} {:<24} {:>6} {:>8}",
+            instance.service_type, instance.instance, instance.host, instance.port, ttl
+        );
+    }
+    if instances.is_empty() {
+        println!("(no instances found yet)");
+    }
+}
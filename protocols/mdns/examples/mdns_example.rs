@@ -1,4 +1,4 @@
-use mdns::MdnsService;
+use mdns::{CancellationToken, MdnsService};
 use tokio::signal;
 
 #[tokio::main]
@@ -14,21 +14,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             8080,
             Some(120),
             "MyHost.local.".to_string(),
+            None,
         )
         .await?;
     println!("Local service registered.");
 
     // Clone the mDNS service for periodic tasks
-    let mdns_service_clone = mdns_service.clone();
-    tokio::spawn(async move {
-        mdns_service_clone
-            .run("_http._tcp.local.".to_string(), 5, 10)
-            .await;
-    });
+    let shutdown = CancellationToken::new();
+    mdns_service
+        .clone()
+        .run("_http._tcp.local.".to_string(), 5, 10, shutdown.clone())
+        .await;
 
     // Wait for shutdown signal
     signal::ctrl_c().await?;
     println!("(MAIN) Shutdown signal received. Exiting...");
+    shutdown.cancel();
 
     // Retrieve and print discovered nodes before exiting
     let nodes = mdns_service.registry.list_nodes().await;
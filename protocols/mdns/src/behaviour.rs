@@ -1,10 +1,38 @@
 // protocols\mdns\src\behaviour.rs
 mod mdns_event;
-pub use mdns_event::MdnsEvent;
+pub use mdns_event::{DiscoveryEvent, MdnsEvent};
 mod mdns_error;
 pub use mdns_error::MdnsError;
+mod mdns_config;
+pub use mdns_config::{MdnsConfig, ResponderMode, RunMode};
+mod timing_policy;
+pub use timing_policy::TimingPolicy;
 mod mdns_service;
+mod interfaces;
+mod transport;
+pub use transport::Transport;
+mod channel_transport;
+pub use channel_transport::{ChannelBus, ChannelTransport};
+mod capturing_transport;
+pub use capturing_transport::CapturingTransport;
+mod clock;
+pub use clock::{Clock, TokioClock};
+mod advertise_policy;
+pub use advertise_policy::{AdvertiseAll, AdvertisePolicy};
+mod suppression;
+pub use suppression::SuppressionEntry;
+mod metrics;
+pub use metrics::MdnsMetrics;
+#[cfg(feature = "serde")]
+mod service_file;
+#[cfg(feature = "serde")]
+pub use service_file::ServiceDefinition;
 
 mod records;
-pub use records::MdnsRegistry;
+pub use records::{
+    CacheFlush, MdnsRegistry, MemoryStore, NodeRecord, Protocol, RegistryStore, ResolvedInstance,
+    ResolvedService, ServiceRecord, ServiceRecordBuilder,
+};
+// The table interface implemented by `RegistryStore` backends.
+pub use registry::{Record, Registry, RegistryError};
 pub use mdns_service::MdnsService;
\ No newline at end of file
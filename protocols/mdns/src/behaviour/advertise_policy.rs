@@ -0,0 +1,27 @@
+// protocols\mdns\src\behaviour\advertise_policy.rs
+use crate::behaviour::records::ServiceRecord;
+use std::net::Ipv4Addr;
+
+/// Hook shaping the services a responder advertises on each outgoing interface.
+///
+/// `MdnsService` consults the policy for every local service and interface when it builds
+/// periodic advertisements and announcements, e.g. to hide a service from an untrusted
+/// network or to advertise a different port behind a NAT. Query responses and goodbyes
+/// are not affected.
+pub trait AdvertisePolicy: Send + Sync {
+    /// Returns the record to advertise for `service` on `interface`, or `None` to leave the
+    /// service out there.
+    ///
+    /// `interface` is `Ipv4Addr::UNSPECIFIED` for sends without a configured interface,
+    /// i.e. over the default route or IPv6. The default advertises `service` unchanged.
+    fn adjust(&self, service: &ServiceRecord, interface: Ipv4Addr) -> Option<ServiceRecord> {
+        let _ = interface;
+        Some(service.clone())
+    }
+}
+
+/// The default policy, advertising every service unchanged on every interface.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AdvertiseAll;
+
+impl AdvertisePolicy for AdvertiseAll {}
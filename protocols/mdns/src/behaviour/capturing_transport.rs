@@ -0,0 +1,68 @@
+// protocols\mdns\src\behaviour\capturing_transport.rs
+use crate::behaviour::Transport;
+use crate::DnsPacket;
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex as StdMutex};
+
+type Datagram = (Vec<u8>, SocketAddr);
+
+/// A `Transport` that records every datagram it is asked to send instead of sending it,
+/// so tests can assert exactly what a service puts on the wire.
+///
+/// Clones share one log: keep a clone, hand the other to the service, and inspect the
+/// log after calling e.g. `advertise_services`. Nothing is ever received; `recv_from`
+/// waits forever, as on a silent network.
+#[derive(Clone, Default)]
+pub struct CapturingTransport {
+    sent: Arc<StdMutex<Vec<Datagram>>>,
+}
+
+impl CapturingTransport {
+    /// Creates a transport with an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared log of `(bytes, destination)` pairs, which keeps filling as the
+    /// transport sends.
+    pub fn log(&self) -> Arc<StdMutex<Vec<Datagram>>> {
+        Arc::clone(&self.sent)
+    }
+
+    /// Returns every `(bytes, destination)` pair sent so far.
+    pub fn sent(&self) -> Vec<Datagram> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Parses every datagram sent so far back into a `DnsPacket`.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<DnsPacket>)` - The sent packets, in order.
+    /// * `Err(Box<dyn std::error::Error>)` - If a datagram is not a valid DNS packet.
+    pub fn sent_packets(&self) -> Result<Vec<DnsPacket>, Box<dyn std::error::Error + Send + Sync>> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(bytes, _)| DnsPacket::parse(bytes))
+            .collect()
+    }
+
+    /// Empties the log, e.g. between the steps of a test.
+    pub fn clear(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl Transport for CapturingTransport {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> std::io::Result<usize> {
+        self.sent.lock().unwrap().push((buf.to_vec(), target));
+        Ok(buf.len())
+    }
+
+    async fn recv_from(&self, _buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+        std::future::pending().await
+    }
+}
@@ -0,0 +1,81 @@
+// protocols\mdns\src\behaviour\channel_transport.rs
+use crate::behaviour::Transport;
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::mpsc;
+use tokio::sync::Mutex;
+
+type Datagram = (Vec<u8>, SocketAddr);
+type Member = (SocketAddr, mpsc::UnboundedSender<Datagram>);
+
+/// An in-process network connecting `ChannelTransport`s, so several `MdnsService`s can
+/// talk to each other in tests without touching real sockets.
+///
+/// A datagram sent to a multicast address reaches every other member of the bus; one sent
+/// to a unicast address reaches the member bound to that address, if any.
+#[derive(Clone, Default)]
+pub struct ChannelBus {
+    members: Arc<StdMutex<Vec<Member>>>,
+}
+
+impl ChannelBus {
+    /// Creates an empty bus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a member with the given address, which is the source of everything it sends.
+    pub fn join(&self, address: SocketAddr) -> ChannelTransport {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.members.lock().unwrap().push((address, tx));
+        ChannelTransport {
+            address,
+            bus: self.clone(),
+            inbox: Mutex::new(rx),
+        }
+    }
+}
+
+/// A `Transport` that exchanges datagrams with the other members of a `ChannelBus`.
+pub struct ChannelTransport {
+    address: SocketAddr,
+    bus: ChannelBus,
+    inbox: Mutex<mpsc::UnboundedReceiver<Datagram>>,
+}
+
+impl ChannelTransport {
+    /// Returns the address this member sends from and receives unicast datagrams on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+#[async_trait]
+impl Transport for ChannelTransport {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> std::io::Result<usize> {
+        let members = self.bus.members.lock().unwrap();
+        for (address, tx) in members.iter() {
+            let receives = if target.ip().is_multicast() {
+                *address != self.address
+            } else {
+                *address == target
+            };
+            if receives {
+                // A member that has been dropped just misses the datagram, as on a network.
+                let _ = tx.send((buf.to_vec(), self.address));
+            }
+        }
+        Ok(buf.len())
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+        let (data, src) = self.inbox.lock().await.recv().await.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::ConnectionAborted, "channel bus closed")
+        })?;
+        // Like a UDP socket, silently drop whatever does not fit.
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok((len, src))
+    }
+}
@@ -0,0 +1,36 @@
+// protocols\mdns\src\behaviour\clock.rs
+use async_trait::async_trait;
+use tokio::time::{Duration, Instant};
+
+/// Source of time for the mDNS service's expiry and scheduling logic.
+///
+/// The default implementation follows the tokio clock, but a manually advanced clock can
+/// be plugged in to test TTL expiry and announcement timing deterministically.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+
+    /// Waits until `duration` has passed on this clock.
+    async fn sleep(&self, duration: Duration);
+
+    /// Waits until `deadline` is reached on this clock, returning at once if it has passed.
+    async fn sleep_until(&self, deadline: Instant) {
+        self.sleep(deadline.saturating_duration_since(self.now())).await
+    }
+}
+
+/// The real clock, backed by `tokio::time`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioClock;
+
+#[async_trait]
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+}
@@ -0,0 +1,108 @@
+// protocols\mdns\src\behaviour\interfaces.rs
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Lists the IPv4 addresses of every interface that is up, skipping loopback.
+pub(crate) fn local_ipv4_interfaces() -> Vec<Ipv4Addr> {
+    interface_addresses()
+        .into_iter()
+        .filter_map(|ip| match ip {
+            IpAddr::V4(ip) => Some(ip),
+            IpAddr::V6(_) => None,
+        })
+        .collect()
+}
+
+/// Lists the IPv6 addresses of every interface that is up, skipping loopback.
+pub(crate) fn local_ipv6_interfaces() -> Vec<Ipv6Addr> {
+    interface_addresses()
+        .into_iter()
+        .filter_map(|ip| match ip {
+            IpAddr::V6(ip) => Some(ip),
+            IpAddr::V4(_) => None,
+        })
+        .collect()
+}
+
+/// Lists the IPv4 and IPv6 addresses of every interface that is up, skipping loopback.
+#[cfg(unix)]
+fn interface_addresses() -> Vec<IpAddr> {
+    let mut addresses = Vec::new();
+    let mut ifaddrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: getifaddrs fills `ifaddrs` with a linked list that stays valid until it
+    // is handed back to freeifaddrs below.
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return addresses;
+    }
+
+    let mut current = ifaddrs;
+    while !current.is_null() {
+        // SAFETY: `current` is a non-null node of the list returned by getifaddrs.
+        let entry = unsafe { &*current };
+        current = entry.ifa_next;
+
+        let up = entry.ifa_flags & libc::IFF_UP as libc::c_uint != 0;
+        if !up || entry.ifa_addr.is_null() {
+            continue;
+        }
+        // SAFETY: `ifa_addr` is non-null and its family tells which sockaddr it holds.
+        let ip = match unsafe { (*entry.ifa_addr).sa_family } as libc::c_int {
+            libc::AF_INET => {
+                // SAFETY: AF_INET addresses are stored as sockaddr_in.
+                let addr = unsafe { &*(entry.ifa_addr as *const libc::sockaddr_in) };
+                IpAddr::V4(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)))
+            }
+            libc::AF_INET6 => {
+                // SAFETY: AF_INET6 addresses are stored as sockaddr_in6.
+                let addr = unsafe { &*(entry.ifa_addr as *const libc::sockaddr_in6) };
+                IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr))
+            }
+            _ => continue,
+        };
+        if !ip.is_loopback() && !addresses.contains(&ip) {
+            addresses.push(ip);
+        }
+    }
+
+    // SAFETY: `ifaddrs` came from a successful getifaddrs call and is freed exactly once.
+    unsafe { libc::freeifaddrs(ifaddrs) };
+    addresses
+}
+
+/// Returns this machine's host name in the `.local` domain, e.g. `myhost.local`, keeping
+/// only the first label of a fully qualified system name.
+pub(crate) fn local_hostname() -> String {
+    let name = system_hostname().unwrap_or_default();
+    let label = name.split('.').next().unwrap_or_default();
+    if label.is_empty() {
+        "localhost.local".to_string()
+    } else {
+        format!("{}.local", label)
+    }
+}
+
+/// Reads the system host name with gethostname.
+#[cfg(unix)]
+fn system_hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for writes of its full length.
+    if unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) } != 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&byte| byte == 0).unwrap_or(buffer.len());
+    String::from_utf8(buffer[..len].to_vec()).ok()
+}
+
+/// Reads the system host name from the environment.
+#[cfg(not(unix))]
+fn system_hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+/// Lists the addresses of every interface that is up, skipping loopback.
+///
+/// Interface enumeration is only implemented for Unix; elsewhere this is empty and
+/// callers fall back to the address of the default route.
+#[cfg(not(unix))]
+fn interface_addresses() -> Vec<IpAddr> {
+    Vec::new()
+}
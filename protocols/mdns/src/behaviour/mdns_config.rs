@@ -0,0 +1,201 @@
+// protocols\mdns\src\behaviour\mdns_config.rs
+use crate::behaviour::TimingPolicy;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+/// Whether a service takes part in the network as a responder or only resolves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponderMode {
+    /// Answers queries for local services and announces them periodically.
+    #[default]
+    Active,
+    /// Pure resolver for one-shot lookups: never answers queries or announces.
+    OneShot,
+}
+
+/// Which roles `MdnsService::run` takes on, and so which of its tasks are spawned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RunMode {
+    /// Only advertises and answers for local services; never sends queries.
+    ResponderOnly,
+    /// Only browses for services; never announces or advertises local services.
+    QuerierOnly,
+    /// Both advertises local services and browses for others.
+    #[default]
+    Both,
+}
+
+/// Configuration options for the mDNS service.
+#[derive(Debug, Clone)]
+pub struct MdnsConfig {
+    /// IPv4 multicast group the service joins and sends to (`224.0.0.251` for mDNS).
+    pub multicast_addr: Ipv4Addr,
+
+    /// UDP port the service binds to and multicasts on. Queries arriving from any other
+    /// source port are treated as legacy unicast queries.
+    pub port: u16,
+
+    /// Maximum TTL (in seconds) given to records in legacy unicast replies.
+    ///
+    /// RFC 6762 §6.7 recommends 10 seconds, but some legacy clients misbehave with
+    /// very short TTLs and may need a higher cap.
+    pub legacy_unicast_ttl_cap: u32,
+
+    /// TTL (in seconds) of local records whose service sets no TTL of its own, unless a
+    /// per-type override below applies. RFC 6762 §10 recommends 4500 (75 minutes) for
+    /// stable services; shorter TTLs let peers notice a vanished host sooner.
+    pub default_ttl: u32,
+
+    /// TTL of PTR records, overriding `default_ttl`.
+    pub ptr_ttl: Option<u32>,
+
+    /// TTL of SRV records, overriding `default_ttl`.
+    pub srv_ttl: Option<u32>,
+
+    /// TTL of TXT records, overriding `default_ttl`.
+    pub txt_ttl: Option<u32>,
+
+    /// TTL of A and AAAA records, overriding `default_ttl`. RFC 6762 §10 recommends 120,
+    /// as host addresses change more often than service descriptions.
+    pub address_ttl: Option<u32>,
+
+    /// Host name every local service is advertised under, e.g. `myhost.local`: it becomes
+    /// the SRV target of each service and the one name carrying the host's address records.
+    ///
+    /// `None` uses the system host name in `.local` (see `MdnsService::hostname`).
+    pub hostname: Option<String>,
+
+    /// Keeps the origin host each local service was registered with instead of pointing
+    /// it at `hostname`, so one responder can advertise several hosts. Services registered
+    /// without an origin still get `hostname`. Defaults to `false`.
+    pub keep_service_origins: bool,
+
+    /// Maximum number of instance resolves `resolve_many` keeps in flight at once.
+    pub max_concurrent_resolves: usize,
+
+    /// Address advertised in A records instead of the auto-detected local address.
+    ///
+    /// Useful in containerized or NAT environments where the address of the default
+    /// route is not the one peers should connect to.
+    pub advertised_address: Option<Ipv4Addr>,
+
+    /// Also answer queries with services discovered from peers (proxy/cache-answering mode).
+    pub answer_from_cache: bool,
+
+    /// Minimum remaining TTL (in seconds) a cached record needs to be included in a
+    /// cache-based answer. Records closer to expiry are left out so they don't flap
+    /// in and out of peers' caches.
+    pub min_cache_answer_ttl: u32,
+
+    /// Maximum number of local services that may be registered, `None` for no limit.
+    pub max_local_services: Option<usize>,
+
+    /// Service types the responder answers queries for, e.g. `_http._tcp.local`; `None`
+    /// answers for every local service. Queries for any other type, its instances, or
+    /// hosts only its services use are dropped, even if such a service is registered.
+    pub allowed_service_types: Option<Vec<String>>,
+
+    /// Probe, announce, and response timing. Repeated multicasts of a record within
+    /// `timing.multicast_rate_limit` are suppressed and can be inspected with
+    /// `MdnsService::suppression_state`.
+    pub timing: TimingPolicy,
+
+    /// How long a received packet waits for a registry held by `MdnsRegistry::lock_for_update`
+    /// before it is dropped, so a long update cannot stall packet processing.
+    pub registry_lock_timeout: Duration,
+
+    /// Whether the service answers queries (`Active`) or only resolves (`OneShot`).
+    pub mode: ResponderMode,
+
+    /// Which tasks `run` spawns: advertising, querying, or both.
+    pub run_mode: RunMode,
+
+    /// How often `run` logs the node registry at debug level, `None` to never log it.
+    pub registry_print_interval: Option<Duration>,
+
+    /// Restricts the service to a single interface, given by its local IPv4 address: the
+    /// multicast group is joined only there, outgoing multicasts leave from it, and it is
+    /// the only address advertised. `None` uses every non-loopback interface.
+    pub interface: Option<Ipv4Addr>,
+
+    /// Local IPv4 addresses of the interfaces to multicast on. When empty, packets are sent
+    /// once through the default route; otherwise each multicast goes out every interface.
+    pub interfaces: Vec<Ipv4Addr>,
+
+    /// IP TTL of outgoing IPv4 multicasts, and hop limit of IPv6 ones. RFC 6762 §11 asks
+    /// for 255 so receivers can check the packet never crossed a router; some strict
+    /// responders drop anything lower.
+    pub multicast_ttl: u32,
+
+    /// Whether outgoing multicasts are looped back to this host, so other processes on it
+    /// (and this service) hear its own announcements.
+    pub multicast_loop: bool,
+
+    /// Also multicast on the IPv6 group `ff02::fb` and advertise AAAA records (opt-in).
+    pub ipv6: bool,
+
+    /// Address advertised in AAAA records instead of the auto-detected local IPv6 addresses.
+    pub advertised_address_v6: Option<Ipv6Addr>,
+
+    /// Also advertise the host's link-local IPv6 addresses (`fe80::/10`). They are left
+    /// out by default, since a peer can only use one together with the interface it
+    /// arrived on. Ignored when `advertised_address_v6` is set.
+    pub advertise_link_local_v6: bool,
+
+    /// How many times socket setup is attempted before giving up. Errors that clear once
+    /// the network is up, such as an interface without an address yet, are retried;
+    /// other errors fail at once.
+    pub socket_setup_attempts: u32,
+
+    /// Wait before the first socket setup retry; it doubles after every further attempt.
+    pub socket_setup_backoff: Duration,
+
+    /// Largest serialized size, in bytes, of one advertisement packet. Advertisements
+    /// that would exceed it are split across several packets. The default of 1472 fits
+    /// a 1500-byte Ethernet MTU after the IPv4 and UDP headers.
+    pub max_packet_size: usize,
+
+    /// Size, in bytes, of the buffer each received datagram is read into. Larger datagrams
+    /// are truncated by the socket and then fail to parse; the default of 9000 holds a
+    /// jumbo-frame packet, the largest RFC 6762 §17 allows.
+    pub recv_buffer_size: usize,
+}
+
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            multicast_addr: Ipv4Addr::new(224, 0, 0, 251),
+            port: 5353,
+            legacy_unicast_ttl_cap: 10,
+            default_ttl: 120,
+            ptr_ttl: None,
+            srv_ttl: None,
+            txt_ttl: None,
+            address_ttl: None,
+            hostname: None,
+            keep_service_origins: false,
+            max_concurrent_resolves: 8,
+            advertised_address: None,
+            answer_from_cache: false,
+            min_cache_answer_ttl: 10,
+            max_local_services: None,
+            allowed_service_types: None,
+            timing: TimingPolicy::default(),
+            registry_lock_timeout: Duration::from_millis(100),
+            mode: ResponderMode::Active,
+            run_mode: RunMode::Both,
+            registry_print_interval: Some(Duration::from_secs(10)),
+            interface: None,
+            interfaces: Vec::new(),
+            multicast_ttl: 255,
+            multicast_loop: true,
+            ipv6: false,
+            advertised_address_v6: None,
+            advertise_link_local_v6: false,
+            socket_setup_attempts: 5,
+            socket_setup_backoff: Duration::from_millis(250),
+            max_packet_size: 1472,
+            recv_buffer_size: 9000,
+        }
+    }
+}
@@ -12,9 +12,19 @@ pub enum MdnsError {
     /// A network-related error, e.g., socket bind failure.
     NetworkError(std::io::Error),
 
+    /// Setting up a multicast socket failed; `step` names what was being done, e.g.
+    /// `"binding to port 5353"`.
+    SocketSetup {
+        step: String,
+        error: std::io::Error,
+    },
+
     /// Indicates a timeout during mDNS operations.
     Timeout(String),
 
+    /// A name cannot be encoded as a DNS name, e.g. it is too long.
+    InvalidName(String),
+
     /// A generic error for uncategorized issues.
     Generic(String),
 }
@@ -25,7 +35,11 @@ impl fmt::Display for MdnsError {
             MdnsError::PacketError(msg) => write!(f, "Packet error: {}", msg),
             MdnsError::MulticastError(msg) => write!(f, "Multicast error: {}", msg),
             MdnsError::NetworkError(err) => write!(f, "Network error: {}", err),
+            MdnsError::SocketSetup { step, error } => {
+                write!(f, "Socket setup failed while {}: {}", step, error)
+            }
             MdnsError::Timeout(msg) => write!(f, "Timeout: {}", msg),
+            MdnsError::InvalidName(msg) => write!(f, "Invalid name: {}", msg),
             MdnsError::Generic(msg) => write!(f, "Error: {}", msg),
         }
     }
@@ -1,6 +1,7 @@
 /// Events emitted by the mDNS protocol behavior.
 use crate::record::DnsRecord;
 use crate::packet::DnsQuestion;
+use crate::behaviour::records::NodeRecord;
 #[derive(Debug)]
 pub enum MdnsEvent {
     /// A new service or peer has been discovered.
@@ -25,4 +26,33 @@ pub enum MdnsEvent {
         /// The record that was announced.
         record: DnsRecord,
     },
-}
\ No newline at end of file
+}
+
+/// Discovery updates published to `MdnsService::subscribe` receivers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscoveryEvent {
+    /// A peer host was seen for the first time.
+    ServiceDiscovered(NodeRecord),
+
+    /// A known peer host announced a different address.
+    ServiceUpdated(NodeRecord),
+
+    /// A peer host or service instance was withdrawn by a goodbye or its TTL ran out;
+    /// carries its name.
+    ServiceExpired(String),
+
+    /// A discovered service instance's SRV target has no known address, so the service
+    /// cannot be reached; carries the instance name.
+    ServiceUnresolved(String),
+
+    /// Another host claimed a name we own with different data, e.g. an A record for our
+    /// hostname with a foreign address. The foreign record is not stored.
+    Conflict {
+        /// The contested name.
+        name: String,
+        /// What we advertise for the name.
+        ours: String,
+        /// What the other host claimed.
+        theirs: String,
+    },
+}
@@ -1,61 +1,320 @@
-use crate::behaviour::records::{NodeRecord, ServiceRecord};
-use crate::{DnsName, DnsPacket, DnsRecord, MdnsError, MdnsRegistry};
-use socket2::{Domain, Protocol, Socket, Type};
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
-use std::sync::Arc;
+use crate::behaviour::records::{srv_order, CacheFlush, NodeRecord, Protocol, ServiceRecord, TxtRecord};
+use crate::behaviour::interfaces::{local_hostname, local_ipv4_interfaces, local_ipv6_interfaces};
+use crate::behaviour::advertise_policy::{AdvertiseAll, AdvertisePolicy};
+use crate::behaviour::metrics::MetricsCounters;
+use crate::behaviour::suppression::SuppressionState;
+use crate::{
+    Clock, DiscoveryEvent, DnsHeader, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsError, MdnsMetrics,
+    MdnsRegistry, MemoryStore, RegistryStore, ResolvedInstance, ResolvedService, ResponderMode, RunMode, ServiceInstanceName, SuppressionEntry,
+    TokioClock, Transport,
+};
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt};
+use socket2::{Domain, Socket, Type};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::net::UdpSocket;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::{broadcast, watch, Mutex, Notify, Semaphore};
 use tokio::time::{self, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, debug_span, error, trace, warn, Instrument};
+
+/// Maximum number of received packets buffered for processing before new ones are dropped.
+const PROCESSING_QUEUE_CAPACITY: usize = 256;
+
+/// Number of discovery events buffered for each subscriber before the oldest are dropped.
+const DISCOVERY_EVENT_CAPACITY: usize = 64;
+
+/// Query type asking for records of every type (`ANY`), used for probes.
+const QTYPE_ANY: u16 = 255;
+
+/// Number of renames tried before registering a probed service gives up (RFC 6762 §8.1).
+const MAX_PROBE_RENAMES: u32 = 15;
+
+/// The DNS-SD meta-query name whose PTR records enumerate every service type (RFC 6763 §9).
+const SERVICE_TYPE_ENUMERATION: &str = "_services._dns-sd._udp.local";
+
+/// Pause after a transient receive error, so a socket that keeps failing does not spin.
+const RECV_ERROR_BACKOFF: Duration = Duration::from_millis(10);
+
+/// The IPv6 link-local mDNS multicast group.
+const MDNS_GROUP_V6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+
+/// The cache-flush bits of records only this host owns: every record type but the PTR,
+/// which is shared by every instance of the type (RFC 6762 §10.2).
+const UNIQUE_RECORDS: CacheFlush = CacheFlush { ptr: false, srv: true, txt: true, a: true };
 
 /// Represents the mDNS service, including registry management and network communication.
-pub struct MdnsService {
-    socket: Arc<UdpSocket>,
-    pub registry: MdnsRegistry,
+///
+/// The registry keeps its records in a `RegistryStore`, in memory unless the service is
+/// built with `with_config_and_store` or `with_transport_and_registry`.
+pub struct MdnsService<S: RegistryStore = MemoryStore> {
+    socket: Arc<dyn Transport>,
+    socket_v6: Option<Arc<dyn Transport>>,
+    pub registry: MdnsRegistry<S>,
+    config: MdnsConfig,
+    dropped_packets: AtomicU64,
+    contended_packets: AtomicU64,
+    counters: MetricsCounters,
+    discovery_notify: Notify,
+    suppression: SuppressionState,
+    send_lock: Mutex<()>,
+    last_send_source: StdMutex<Option<SocketAddr>>,
+    events: broadcast::Sender<DiscoveryEvent>,
+    announce_tx: mpsc::UnboundedSender<Vec<String>>,
+    announce_rx: StdMutex<Option<mpsc::UnboundedReceiver<Vec<String>>>>,
+    probes: StdMutex<HashMap<String, bool>>,
+    clock: Arc<dyn Clock>,
+    joined_interfaces: StdMutex<Vec<Ipv4Addr>>,
+    query_interval: watch::Sender<Duration>,
+    hostname: String,
+    reannounce: StdMutex<HashMap<String, ReannounceCycle>>,
+    advertise_policy: StdMutex<Arc<dyn AdvertisePolicy>>,
+    observed_questions: StdMutex<HashMap<(String, u16), time::Instant>>,
 }
 
-impl MdnsService {
-    /// Sets up a multicast UDP socket for mDNS communication.
-    async fn setup_multicast_socket() -> Result<UdpSocket, MdnsError> {
-        let multicast_addr = Ipv4Addr::new(224, 0, 0, 251);
+impl<S: RegistryStore> MdnsService<S> {
+    /// Sets up a multicast UDP socket for mDNS communication, retrying transient failures
+    /// (see `retry_socket_setup`).
+    ///
+    /// Returns the socket with the interfaces the multicast group was joined on.
+    async fn setup_multicast_socket(config: &MdnsConfig) -> Result<(UdpSocket, Vec<Ipv4Addr>), MdnsError> {
+        retry_socket_setup(config, || Self::try_setup_multicast_socket(config)).await
+    }
+
+    /// Makes one attempt at setting up the IPv4 multicast socket.
+    async fn try_setup_multicast_socket(
+        config: &MdnsConfig,
+    ) -> Result<(UdpSocket, Vec<Ipv4Addr>), MdnsError> {
+        let multicast_addr = config.multicast_addr;
         let local_addr = Ipv4Addr::UNSPECIFIED;
-        let port = 5353;
+        let port = config.port;
 
-        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
-            .map_err(MdnsError::NetworkError)?;
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(socket2::Protocol::UDP))
+            .map_err(setup_step("creating the socket"))?;
         socket
             .set_reuse_address(true)
-            .map_err(MdnsError::NetworkError)?;
+            .map_err(setup_step("enabling address reuse"))?;
         #[cfg(unix)]
         socket
             .set_reuse_port(true)
-            .map_err(MdnsError::NetworkError)?;
+            .map_err(setup_step("enabling port reuse"))?;
+        // Tokio requires the underlying socket to be non-blocking.
+        socket
+            .set_nonblocking(true)
+            .map_err(setup_step("making the socket non-blocking"))?;
+        if let Some(interface) = config.interface {
+            socket
+                .set_multicast_if_v4(&interface)
+                .map_err(setup_step(&format!("selecting multicast interface {}", interface)))?;
+        }
+        socket
+            .set_multicast_ttl_v4(config.multicast_ttl)
+            .map_err(setup_step(&format!("setting multicast TTL {}", config.multicast_ttl)))?;
+        socket
+            .set_multicast_loop_v4(config.multicast_loop)
+            .map_err(setup_step("configuring multicast loopback"))?;
 
         socket
             .bind(&SocketAddr::V4(SocketAddrV4::new(local_addr, port)).into())
-            .map_err(MdnsError::NetworkError)?;
+            .map_err(setup_step(&format!("binding to port {}", port)))?;
 
-        let udp_socket = UdpSocket::from_std(socket.into()).map_err(MdnsError::NetworkError)?;
-        udp_socket
-            .join_multicast_v4(multicast_addr, local_addr)
-            .map_err(MdnsError::NetworkError)?;
+        let udp_socket = UdpSocket::from_std(socket.into())
+            .map_err(setup_step("registering the socket with the runtime"))?;
+        let mut joined = Vec::new();
+        for interface in multicast_interfaces(config) {
+            match udp_socket.join_multicast_v4(multicast_addr, interface) {
+                Ok(()) => {
+                    debug!("(INIT) Joined {} on interface {}", multicast_addr, interface);
+                    joined.push(interface);
+                }
+                Err(err) => warn!(
+                    "(INIT) Failed to join {} on interface {}: {}",
+                    multicast_addr, interface, err
+                ),
+            }
+        }
+        // Without any usable interface, let the OS choose one.
+        if joined.is_empty() {
+            udp_socket
+                .join_multicast_v4(multicast_addr, Ipv4Addr::UNSPECIFIED)
+                .map_err(setup_step(&format!("joining multicast group {}", multicast_addr)))?;
+            joined.push(Ipv4Addr::UNSPECIFIED);
+        }
 
-        println!(
+        debug!(
             "(INIT) Multicast socket set up on {}:{}",
             multicast_addr, port
         );
+        Ok((udp_socket, joined))
+    }
+
+    /// Sets up an IPv6 multicast UDP socket joined to `ff02::fb`, retrying transient
+    /// failures (see `retry_socket_setup`).
+    async fn setup_multicast_socket_v6(config: &MdnsConfig) -> Result<UdpSocket, MdnsError> {
+        retry_socket_setup(config, || Self::try_setup_multicast_socket_v6(config)).await
+    }
+
+    /// Makes one attempt at setting up the IPv6 multicast socket.
+    async fn try_setup_multicast_socket_v6(config: &MdnsConfig) -> Result<UdpSocket, MdnsError> {
+        let port = config.port;
+
+        let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(socket2::Protocol::UDP))
+            .map_err(setup_step("creating the IPv6 socket"))?;
+        // Keep this socket IPv6-only so it can share the port with the IPv4 socket.
+        socket
+            .set_only_v6(true)
+            .map_err(setup_step("restricting the socket to IPv6"))?;
+        socket
+            .set_reuse_address(true)
+            .map_err(setup_step("enabling address reuse"))?;
+        #[cfg(unix)]
+        socket
+            .set_reuse_port(true)
+            .map_err(setup_step("enabling port reuse"))?;
+        socket
+            .set_nonblocking(true)
+            .map_err(setup_step("making the socket non-blocking"))?;
+        socket
+            .set_multicast_hops_v6(config.multicast_ttl)
+            .map_err(setup_step(&format!("setting multicast hop limit {}", config.multicast_ttl)))?;
+        socket
+            .set_multicast_loop_v6(config.multicast_loop)
+            .map_err(setup_step("configuring IPv6 multicast loopback"))?;
+
+        socket
+            .bind(&SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, port, 0, 0)).into())
+            .map_err(setup_step(&format!("binding to IPv6 port {}", port)))?;
+
+        let udp_socket = UdpSocket::from_std(socket.into())
+            .map_err(setup_step("registering the socket with the runtime"))?;
+        udp_socket
+            .join_multicast_v6(&MDNS_GROUP_V6, 0)
+            .map_err(setup_step(&format!("joining multicast group {}", MDNS_GROUP_V6)))?;
+
+        debug!("(INIT) Multicast socket set up on [{}]:{}", MDNS_GROUP_V6, port);
         Ok(udp_socket)
     }
 
+}
+
+impl MdnsService {
     /// Creates a new mDNS service instance.
     pub async fn new() -> Result<Arc<Self>, MdnsError> {
-        let socket = Self::setup_multicast_socket().await?;
-        let registry = MdnsRegistry::new();
-        Ok(Arc::new(Self {
-            socket: Arc::new(socket),
+        Self::with_config(MdnsConfig::default()).await
+    }
+
+    /// Creates a resolver for one-shot lookups that never answers queries or announces.
+    pub async fn new_resolver() -> Result<Arc<Self>, MdnsError> {
+        Self::with_config(MdnsConfig {
+            mode: ResponderMode::OneShot,
+            ..MdnsConfig::default()
+        })
+        .await
+    }
+
+    /// Creates a new mDNS service instance with a custom configuration.
+    ///
+    /// With `config.ipv6` set, a second socket is joined to the IPv6 group and the service
+    /// runs dual-stack.
+    pub async fn with_config(config: MdnsConfig) -> Result<Arc<Self>, MdnsError> {
+        Self::with_config_and_store(config, MemoryStore).await
+    }
+
+    /// Creates a new mDNS service instance on top of a custom transport.
+    pub fn with_transport(transport: Arc<dyn Transport>, config: MdnsConfig) -> Arc<Self> {
+        Self::build(transport, None, config, MdnsRegistry::new())
+    }
+
+    /// Creates a new mDNS service instance on top of a custom transport that reads time
+    /// from `clock`, which drives record expiry and the announcement schedule.
+    pub fn with_transport_and_clock(
+        transport: Arc<dyn Transport>,
+        config: MdnsConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Arc<Self> {
+        Self::build(transport, None, config, MdnsRegistry::with_clock(clock))
+    }
+
+    /// Creates a dual-stack mDNS service instance on top of custom IPv4 and IPv6 transports.
+    pub fn with_transports(
+        transport: Arc<dyn Transport>,
+        transport_v6: Arc<dyn Transport>,
+        config: MdnsConfig,
+    ) -> Arc<Self> {
+        Self::build(transport, Some(transport_v6), config, MdnsRegistry::new())
+    }
+}
+
+impl<S: RegistryStore> MdnsService<S> {
+    /// Creates a new mDNS service instance with a custom configuration, whose registry
+    /// keeps its records in `store`, e.g. to persist discovered services across restarts.
+    pub async fn with_config_and_store(config: MdnsConfig, store: S) -> Result<Arc<Self>, MdnsError> {
+        let (socket, joined) = Self::setup_multicast_socket(&config).await?;
+        let socket_v6: Option<Arc<dyn Transport>> = if config.ipv6 {
+            Some(Arc::new(Self::setup_multicast_socket_v6(&config).await?))
+        } else {
+            None
+        };
+        let registry = MdnsRegistry::with_store(store, Arc::new(TokioClock));
+        let service = Self::build(Arc::new(socket), socket_v6, config, registry);
+        *service.joined_interfaces.lock().unwrap() = joined;
+        Ok(service)
+    }
+
+    /// Creates a new mDNS service instance on top of a custom transport and `registry`,
+    /// whose clock also drives the announcement schedule.
+    pub fn with_transport_and_registry(
+        transport: Arc<dyn Transport>,
+        config: MdnsConfig,
+        registry: MdnsRegistry<S>,
+    ) -> Arc<Self> {
+        Self::build(transport, None, config, registry)
+    }
+
+    fn build(
+        transport: Arc<dyn Transport>,
+        transport_v6: Option<Arc<dyn Transport>>,
+        config: MdnsConfig,
+        registry: MdnsRegistry<S>,
+    ) -> Arc<Self> {
+        let clock = registry.clock();
+        let (announce_tx, announce_rx) = mpsc::unbounded_channel();
+        let query_interval = watch::channel(config.timing.requery_interval).0;
+        let hostname = config.hostname.clone().unwrap_or_else(local_hostname);
+        Arc::new(Self {
+            socket: transport,
+            socket_v6: transport_v6,
             registry,
-        }))
+            config,
+            dropped_packets: AtomicU64::new(0),
+            contended_packets: AtomicU64::new(0),
+            counters: MetricsCounters::default(),
+            discovery_notify: Notify::new(),
+            suppression: SuppressionState::default(),
+            send_lock: Mutex::new(()),
+            last_send_source: StdMutex::new(None),
+            events: broadcast::channel(DISCOVERY_EVENT_CAPACITY).0,
+            announce_tx,
+            announce_rx: StdMutex::new(Some(announce_rx)),
+            probes: StdMutex::new(HashMap::new()),
+            query_interval,
+            hostname,
+            reannounce: StdMutex::new(HashMap::new()),
+            advertise_policy: StdMutex::new(Arc::new(AdvertiseAll)),
+            observed_questions: StdMutex::new(HashMap::new()),
+            clock,
+            joined_interfaces: StdMutex::new(Vec::new()),
+        })
     }
 
     /// Registers a local service to the registry.
+    ///
+    /// `txt` holds optional metadata (e.g. `version`, `path`) advertised in the service's
+    /// TXT record as `key=value` strings. Once `run` is active the new service is
+    /// announced right away instead of waiting for the next periodic advertisement.
     pub async fn register_local_service(
         &self,
         id: String,
@@ -63,16 +322,254 @@ impl MdnsService {
         port: u16,
         ttl: Option<u32>,
         origin: String,
+        txt: Option<HashMap<String, String>>,
     ) -> Result<(), MdnsError> {
-        let service = ServiceRecord {
-            id,
-            service_type,
-            port,
-            ttl,
-            origin,
-            priority: Some(0),
-            weight: Some(0),
+        let mut builder = ServiceRecord::builder(&id, &service_type, port).origin(&origin);
+        if let Some(ttl) = ttl {
+            builder = builder.ttl(ttl);
+        }
+        for (key, value) in txt.unwrap_or_default() {
+            builder = builder.txt(&key, &value);
+        }
+        self.register_service(builder.build()).await
+    }
+
+    /// Registers a local service built with `ServiceRecord::builder`.
+    ///
+    /// Like `register_local_service`, the service is announced right away once `run`
+    /// is active.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the service was registered.
+    /// * `Err(MdnsError)` - If one of the record's names is invalid or the local service
+    ///   limit is reached.
+    pub async fn register_service(&self, mut service: ServiceRecord) -> Result<(), MdnsError> {
+        self.assign_host(&mut service);
+        validate_local_service(&service)?;
+        let id = service.id.clone();
+        self.add_local_service(service).await?;
+        // The receiver lives as long as the service, so this cannot fail.
+        let _ = self.announce_tx.send(vec![id]);
+        Ok(())
+    }
+
+    /// Registers a DNS-SD service from its parts, composing the names DNS-SD expects:
+    /// `register_dns_sd("Office Printer", "ipp", Protocol::Tcp, 631, None)` registers
+    /// the instance `Office Printer._ipp._tcp.local` on `MdnsService::hostname`.
+    ///
+    /// `instance` is a free-form, human-readable name; dots and backslashes in it are
+    /// escaped so it stays a single label. `service` may be given with or without its
+    /// leading underscore.
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The full instance name the service was registered under.
+    /// * `Err(MdnsError)` - If the instance is empty, a name is too long, or the local
+    ///   service limit is reached.
+    pub async fn register_dns_sd(
+        &self,
+        instance: &str,
+        service: &str,
+        protocol: Protocol,
+        port: u16,
+        txt: Option<HashMap<String, String>>,
+    ) -> Result<String, MdnsError> {
+        if instance.is_empty() {
+            return Err(MdnsError::InvalidName("Instance name is empty".to_string()));
+        }
+        let name = ServiceInstanceName {
+            instance: instance.to_string(),
+            service_type: format!("_{}.{}", service.trim_start_matches('_'), protocol.label()),
+            domain: "local".to_string(),
         };
+        let mut builder = ServiceRecord::builder(&name.to_string(), &name.service_name(), port)
+            .protocol(protocol);
+        for (key, value) in txt.unwrap_or_default() {
+            builder = builder.txt(&key, &value);
+        }
+        self.register_service(builder.build()).await?;
+        Ok(name.to_string())
+    }
+
+    /// Registers several local services at once, all or none.
+    ///
+    /// Every record is validated before any is added, and they are added while the
+    /// registry is locked for update, so received packets never see half the batch. Once
+    /// `run` is active the services share a single announcement burst.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If every service was registered.
+    /// * `Err(MdnsError)` - If any record is invalid, the batch would exceed
+    ///   `max_local_services`, or the registry rejects a record; no service is registered.
+    pub async fn register_local_services(&self, mut services: Vec<ServiceRecord>) -> Result<(), MdnsError> {
+        for service in &mut services {
+            self.assign_host(service);
+            normalize_service_type(service)?;
+            validate_local_service(service)?;
+        }
+
+        let _guard = self.registry.lock_for_update().await;
+        if let Some(max) = self.config.max_local_services {
+            let existing = self.registry.list_services().await;
+            let mut added: Vec<&str> = Vec::new();
+            for service in &services {
+                let known = existing.iter().any(|existing| existing.id == service.id);
+                if !known && !added.contains(&service.id.as_str()) {
+                    added.push(&service.id);
+                }
+            }
+            if existing.len() + added.len() > max {
+                return Err(MdnsError::Generic(format!(
+                    "Cannot register {} services: limit of {} local services reached",
+                    added.len(),
+                    max
+                )));
+            }
+        }
+
+        // Each applied id with the record it replaced, to undo the batch on failure.
+        let mut applied: Vec<(String, Option<ServiceRecord>)> = Vec::new();
+        for service in services {
+            let id = service.id.clone();
+            let previous = self.registry.get_service(&id).await;
+            if let Err(err) = self.registry.add_service(service).await {
+                for (id, previous) in applied.into_iter().rev() {
+                    let _ = match previous {
+                        Some(previous) => self.registry.add_service(previous).await,
+                        None => self.registry.remove_service(&id).await,
+                    };
+                }
+                return Err(MdnsError::Generic(format!(
+                    "Cannot register '{}': {}; the batch was rolled back",
+                    id, err
+                )));
+            }
+            applied.push((id, previous));
+        }
+
+        let mut ids: Vec<String> = Vec::with_capacity(applied.len());
+        for (id, _) in applied {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        // The receiver lives as long as the service, so this cannot fail.
+        let _ = self.announce_tx.send(ids);
+        Ok(())
+    }
+
+    /// Registers a local service after probing that its name is unused (RFC 6762 §8.1).
+    ///
+    /// Probes for `id` are sent following `timing.probe_schedule`. If another host answers
+    /// for the name, the instance is renamed to `Name (2)`, `Name (3)`, ... and probed again.
+    /// Responses are only seen while the service is listening (`run` or `listen`).
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The instance name the service was registered under.
+    /// * `Err(MdnsError)` - If `id` is not an instance name, every rename conflicted, or
+    ///   registering fails.
+    pub async fn register_local_service_probed(
+        &self,
+        id: String,
+        service_type: String,
+        port: u16,
+        ttl: Option<u32>,
+        origin: String,
+        txt: Option<HashMap<String, String>>,
+    ) -> Result<String, MdnsError> {
+        let instance = ServiceInstanceName::parse(&id).map_err(MdnsError::InvalidName)?;
+        for attempt in 1..=MAX_PROBE_RENAMES {
+            let candidate = if attempt == 1 {
+                instance.clone()
+            } else {
+                ServiceInstanceName {
+                    instance: format!("{} ({})", instance.instance, attempt),
+                    ..instance.clone()
+                }
+            };
+            let candidate = candidate.to_string();
+            if self.probe_name(&candidate).await? {
+                debug!("(PROBE) Name {} is already in use, renaming.", candidate);
+                continue;
+            }
+            self.register_local_service(candidate.clone(), service_type, port, ttl, origin, txt)
+                .await?;
+            return Ok(candidate);
+        }
+        Err(MdnsError::Generic(format!(
+            "Cannot register '{}': {} names in a row are already in use",
+            id, MAX_PROBE_RENAMES
+        )))
+    }
+
+    /// Sends the probe queries for `name` and reports whether another host claimed it.
+    async fn probe_name(&self, name: &str) -> Result<bool, MdnsError> {
+        let qname = dns_name(name)?;
+        let key = probe_key(&qname);
+        self.probes.lock().unwrap().insert(key.clone(), false);
+        let conflicted = || self.probes.lock().unwrap().get(&key).copied().unwrap_or(false);
+
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        packet.questions.push(crate::DnsQuestion {
+            qname,
+            qtype: QTYPE_ANY,
+            qclass: 1,
+            unicast_response: true,
+        });
+
+        let start = time::Instant::now();
+        let mut result = Ok(());
+        for offset in self.config.timing.probe_schedule() {
+            time::sleep_until(start + offset).await;
+            if conflicted() {
+                break;
+            }
+            trace!("(PROBE) Probing for {}", name);
+            result = self.send_packet(&packet).await;
+            if result.is_err() {
+                break;
+            }
+        }
+        // Give the last probe as long to be answered as the others.
+        if result.is_ok() && !conflicted() {
+            time::sleep(self.config.timing.probe_interval).await;
+        }
+
+        let conflict = self.probes.lock().unwrap().remove(&key).unwrap_or(false);
+        result.map(|()| conflict)
+    }
+
+    /// Returns the host name local services are advertised under: `MdnsConfig::hostname`,
+    /// or else the system host name in `.local`.
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    /// Points a local service at `hostname()`, unless `MdnsConfig::keep_service_origins`
+    /// is set and the service names an origin of its own.
+    fn assign_host(&self, service: &mut ServiceRecord) {
+        if !self.config.keep_service_origins || service.origin.is_empty() {
+            service.origin = self.hostname.clone();
+        }
+    }
+
+    /// Adds a local service to the registry, enforcing `max_local_services`.
+    ///
+    /// Re-registering an existing id replaces it and never counts against the limit. The
+    /// service type is completed to its `_service._proto.local` form first.
+    pub(crate) async fn add_local_service(&self, mut service: ServiceRecord) -> Result<(), MdnsError> {
+        self.assign_host(&mut service);
+        normalize_service_type(&mut service)?;
+        validate_service_names(&service)?;
+        if let Some(max) = self.config.max_local_services {
+            let services = self.registry.list_services().await;
+            let replacing = services.iter().any(|existing| existing.id == service.id);
+            if !replacing && services.len() >= max {
+                return Err(MdnsError::Generic(format!(
+                    "Cannot register '{}': limit of {} local services reached",
+                    service.id, max
+                )));
+            }
+        }
         self.registry
             .add_service(service)
             .await
@@ -82,283 +579,2795 @@ impl MdnsService {
     /// Creates an mDNS advertisement packet from the service registry.
     pub async fn create_advertise_packet(&self) -> Result<DnsPacket, MdnsError> {
         let services = self.registry.list_services().await;
-        let mut packet = DnsPacket::new();
-        packet.flags = 0x8400;
+        let mut packet = DnsPacket::with_header(DnsHeader::response());
 
         if services.is_empty() {
-            println!("(ADVERTISE) No local services to advertise.");
+            debug!("(ADVERTISE) No local services to advertise.");
             return Ok(packet);
         }
 
-        // Retrieve the local IP dynamically
-        let local_ip = get_local_ipv4()
-            .ok_or_else(|| MdnsError::Generic("Failed to get local IP".to_string()))?;
+        // Retrieve the local IPs dynamically, one A record per interface
+        let local_ips = self.advertised_ipv4s()?;
+        let local_ipv6s = self.advertised_ipv6s();
 
         for service in services {
-            println!("(ADVERTISE) Including service in packet: {:?}", service);
-
-            packet.answers.push(DnsRecord::PTR {
-                name: DnsName::new(&service.service_type).unwrap(),
-                ttl: service.ttl.unwrap_or(120),
-                ptr_name: DnsName::new(&service.id).unwrap(),
-            });
-
-            packet.answers.push(DnsRecord::SRV {
-                name: DnsName::new(&service.id).unwrap(),
-                ttl: service.ttl.unwrap_or(120),
-                priority: service.priority.unwrap_or(0),
-                weight: service.weight.unwrap_or(0),
-                port: service.port,
-                target: DnsName::new(&service.origin).unwrap(),
-            });
-
-            packet.answers.push(DnsRecord::A {
-                name: DnsName::new(&service.origin).unwrap(),
-                ttl: service.ttl.unwrap_or(120),
-                ip: local_ip.octets(),
-            });
+            debug!("(ADVERTISE) Including service in packet: {:?}", service);
+            push_service_records(
+                &mut packet.answers,
+                &service,
+                service.cache_flush,
+                &local_ips,
+                &local_ipv6s,
+                self.record_ttls(&service),
+            )?;
         }
 
         Ok(packet)
     }
 
+    /// Multicasts the records of one local service following `timing.announce_schedule`,
+    /// so peers learn about it without waiting for the periodic advertisement
+    /// (RFC 6762 §8.3).
+    ///
+    /// The burst stops early if the service is deregistered in the meantime.
+    ///
+    /// # Arguments
+    /// * `id` - The instance name of the service to announce.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If every announcement was sent.
+    /// * `Err(MdnsError)` - If the service is unknown or sending fails.
+    pub async fn announce_service(&self, id: &str) -> Result<(), MdnsError> {
+        self.announce_services(&[id.to_string()]).await
+    }
 
-    /// Sends an mDNS packet over the network.
-    pub async fn send_packet(&self, packet: &DnsPacket) -> Result<(), MdnsError> {
-        let bytes = packet.serialize();
-        let multicast_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(224, 0, 0, 251), 5353));
-        self.socket
-            .send_to(&bytes, multicast_addr)
-            .await
-            .map_err(MdnsError::NetworkError)?;
+    /// Announces several local services in one shared burst, like `announce_service`.
+    ///
+    /// Each announcement carries the records of every listed service still registered,
+    /// split across packets only where `max_packet_size` requires it; the burst ends
+    /// early once all of them have been deregistered.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If every announcement was sent.
+    /// * `Err(MdnsError)` - If a listed service is unknown or sending fails.
+    pub async fn announce_services(&self, ids: &[String]) -> Result<(), MdnsError> {
+        for id in ids {
+            if self.registry.get_service(id).await.is_none() {
+                return Err(MdnsError::Generic(format!("Unknown local service '{}'", id)));
+            }
+        }
 
-        println!(
-            "(SEND) Sent mDNS packet with {} answers",
-            packet.answers.len()
-        );
-        Ok(())
-    }
+        let start = self.clock.now();
+        for offset in self.config.timing.announce_schedule() {
+            self.clock.sleep_until(start + offset).await;
+            let mut services = Vec::with_capacity(ids.len());
+            for id in ids {
+                match self.registry.get_service(id).await {
+                    Some(service) => services.push(service),
+                    None => debug!("(ANNOUNCE) Service {} was removed, no longer announcing it.", id),
+                }
+            }
+            if services.is_empty() {
+                debug!("(ANNOUNCE) Every service was removed, ending the announcements.");
+                return Ok(());
+            }
 
-    /// Periodically sends a PTR query for the given service type.
-    pub async fn periodic_query(&self, service_type: &str, interval_secs: u64) {
-        let mut ticker = time::interval(Duration::from_secs(interval_secs));
-        loop {
-            ticker.tick().await;
-            println!(
-                "(QUERY) Sending periodic query for service type: {}",
-                service_type
-            );
-            let mut packet = DnsPacket::new();
-            packet.flags = 0x0000;
-            packet.questions.push(crate::DnsQuestion {
-                qname: DnsName::new(service_type).unwrap(),
-                qtype: 12,
-                qclass: 1,
-            });
-            if let Err(err) = self.send_packet(&packet).await {
-                eprintln!("(QUERY) Failed to send periodic query: {:?}", err);
-            } else {
-                println!(
-                    "(QUERY) Periodic query sent for service type: {}",
-                    service_type
-                );
+            debug!("(ANNOUNCE) Announcing {:?} after {:?}", ids, offset);
+            for (target, packets) in self.advertisement_packets(&services)? {
+                for packet in packets.iter().filter(|packet| !packet.answers.is_empty()) {
+                    self.send_to_target(packet, target).await?;
+                }
             }
         }
+        Ok(())
     }
-    /// Advertises all local services as unsolicited mDNS responses.
-    pub async fn advertise_services(&self) -> Result<(), MdnsError> {
-        let packet = self.create_advertise_packet().await?;
-        if packet.answers.is_empty() {
-            println!("(ADVERTISE) No answers in the mDNS packet.");
-        } else {
-            println!(
-                "(ADVERTISE) Sending mDNS packet with {} answers.",
-                packet.answers.len()
-            );
+
+    /// Creates the mDNS advertisement packets for the service registry, each no larger
+    /// than `max_packet_size` once serialized.
+    ///
+    /// The records of one service (PTR, SRV, TXT, and its host's addresses) always travel
+    /// in the same packet; a service too large to fit on its own is sent alone.
+    pub async fn create_advertise_packets(&self) -> Result<Vec<DnsPacket>, MdnsError> {
+        let services = self.registry.list_services().await;
+        if services.is_empty() {
+            debug!("(ADVERTISE) No local services to advertise.");
+            return Ok(vec![response_packet(Vec::new())]);
         }
-        self.send_packet(&packet).await
+        self.pack_service_records(services)
     }
 
-    /// Adds a node to the registry.
-    async fn add_node_to_registry(
+    /// Installs the policy shaping what periodic advertisements and announcements carry
+    /// on each interface, replacing the previous one (`AdvertiseAll` by default).
+    pub fn set_advertise_policy(&self, policy: Arc<dyn AdvertisePolicy>) {
+        *self.advertise_policy.lock().unwrap() = policy;
+    }
+
+    /// Packs `services` for each outgoing interface as shaped by the advertise policy.
+    ///
+    /// When every interface ends up with the same packets they are sent once through
+    /// `send_packet`; otherwise each configured interface gets its own packets, and IPv6
+    /// the packets for `Ipv4Addr::UNSPECIFIED`.
+    fn advertisement_packets(
         &self,
-        id: &str,
-        ip_address: &str,
-        ttl: Option<u32>,
-    ) -> Result<(), MdnsError> {
-        let node = NodeRecord {
-            id: id.to_string(),
-            ip_address: ip_address.to_string(),
-            ttl,
+        services: &[ServiceRecord],
+    ) -> Result<Vec<(SendTarget, Vec<DnsPacket>)>, MdnsError> {
+        let policy = self.advertise_policy.lock().unwrap().clone();
+        let shaped = |interface: Ipv4Addr| -> Vec<ServiceRecord> {
+            services
+                .iter()
+                .filter_map(|service| policy.adjust(service, interface))
+                .collect()
         };
-        self.registry
-            .add_node(node)
-            .await
-            .map_err(|e| MdnsError::Generic(e.to_string()))
-    }
 
-    /// Listens for incoming mDNS packets and processes them.
-    pub async fn listen(&self) -> Result<(), MdnsError> {
-        let mut buf = [0; 4096];
-        loop {
-            let (len, src) = self
-                .socket
-                .recv_from(&mut buf)
-                .await
-                .map_err(MdnsError::NetworkError)?;
-            println!("(LISTEN) Packet received from {} with size {}", src, len);
-
-            if let Ok(packet) = DnsPacket::parse(&buf[..len]) {
-                let is_response = (packet.flags & 0x8000) != 0;
-                if is_response {
-                    println!("(LISTEN) Response packet from {}", src);
-                    self.process_response(&packet, &src).await;
-                } else {
-                    println!("(LISTEN) Query packet from {}", src);
-                    self.process_query(&packet, &src).await;
-                }
-            } else {
-                eprintln!("(LISTEN) Failed to parse packet from {}", src);
-            }
+        let default_packets = self.pack_service_records(shaped(Ipv4Addr::UNSPECIFIED))?;
+        let mut targets = Vec::with_capacity(self.config.interfaces.len() + 1);
+        for interface in &self.config.interfaces {
+            let packets = self.pack_service_records(shaped(*interface))?;
+            targets.push((SendTarget::Interface(*interface), packets));
         }
-    }
-
-    /// Periodically prints the node registry for debugging.
-    pub async fn print_node_registry(&self) {
-        loop {
-            time::sleep(Duration::from_secs(10)).await;
-            let nodes = self.registry.list_nodes().await;
-            println!("(NODE REGISTRY) Nodes: {:?}", nodes);
+        if targets.iter().all(|(_, packets)| *packets == default_packets) {
+            return Ok(vec![(SendTarget::All, default_packets)]);
         }
+        if self.socket_v6.is_some() {
+            targets.push((SendTarget::Ipv6, default_packets));
+        }
+        Ok(targets)
     }
 
-    /// Runs the mDNS service, spawning advertise, query, listen, and registry print tasks.
-    pub async fn run(
-        self: Arc<Self>,
-        service_type: String,
-        query_interval: u64,
-        advertise_interval: u64,
-    ) {
-        let advertise_service = Arc::clone(&self);
-        let query_service = Arc::clone(&self);
-        let listen_service = Arc::clone(&self);
-        let registry_service = Arc::clone(&self);
+    /// Packs the records of `services` into response packets no larger than
+    /// `max_packet_size`, keeping each service's records together.
+    fn pack_service_records(&self, services: Vec<ServiceRecord>) -> Result<Vec<DnsPacket>, MdnsError> {
+        let local_ips = self.advertised_ipv4s()?;
+        let local_ipv6s = self.advertised_ipv6s();
+        let max_packet_size = self.config.max_packet_size;
 
-        tokio::spawn(async move {
-            loop {
-                time::sleep(Duration::from_secs(advertise_interval)).await;
-                if let Err(err) = advertise_service.advertise_services().await {
-                    eprintln!("(ADVERTISE) Error: {:?}", err);
+        let mut groups: Vec<Vec<DnsRecord>> = vec![Vec::new()];
+        for service in services {
+            let current = groups.last_mut().expect("groups is never empty");
+            let mut grown = current.clone();
+            let ttls = self.record_ttls(&service);
+            push_service_records(&mut grown, &service, service.cache_flush, &local_ips, &local_ipv6s, ttls)?;
+            let size = response_packet(grown.clone()).serialize().len();
+            if size <= max_packet_size || current.is_empty() {
+                if size > max_packet_size {
+                    warn!(
+                        "(ADVERTISE) Service {} needs {} bytes, over the {} byte limit.",
+                        service.id, size, max_packet_size
+                    );
                 }
+                *current = grown;
+                continue;
             }
-        });
-
-        tokio::spawn(async move {
-            query_service
-                .periodic_query(&service_type, query_interval)
-                .await;
-        });
-
-        tokio::spawn(async move {
-            if let Err(err) = listen_service.listen().await {
-                eprintln!("(LISTEN) Error: {:?}", err);
-            }
-        });
 
-        tokio::spawn(async move {
-            registry_service.print_node_registry().await;
-        });
-
-        println!("(TASK) All tasks are running.");
+            // Start a new packet, which repeats the host's addresses for this service.
+            let mut answers = Vec::new();
+            push_service_records(&mut answers, &service, service.cache_flush, &local_ips, &local_ipv6s, ttls)?;
+            groups.push(answers);
+        }
+        Ok(groups.into_iter().map(response_packet).collect())
     }
 
-    async fn process_response(&self, packet: &DnsPacket, src: &SocketAddr) {
-        if let SocketAddr::V4(_addr) = src {
-            for answer in &packet.answers {
-                match answer {
-                    DnsRecord::A { name, ip, ttl } => {
-                        let ip_address = Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]);
-                        println!("(DISCOVERY) Discovered node: {} -> {}", name, ip_address);
-                        let _ = self
-                            .add_node_to_registry(
-                                &name.to_string(),
-                                &ip_address.to_string(),
-                                Some(*ttl),
-                            )
-                            .await;
+    /// Announces the local services that crossed one of `timing.reannounce_thresholds`
+    /// of their shortest record TTL since their cycle started, all in one burst.
+    ///
+    /// A service's first cycle starts when this method first sees it, and a new one
+    /// starts with the re-announcement at the last threshold.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The number of services re-announced.
+    /// * `Err(MdnsError)` - If the records could not be built or sent.
+    pub async fn reannounce_expiring(&self) -> Result<usize, MdnsError> {
+        let thresholds = &self.config.timing.reannounce_thresholds;
+        let services = self.registry.list_services().await;
+        let now = self.clock.now();
+        let mut due = Vec::new();
+        {
+            let mut cycles = self.reannounce.lock().unwrap();
+            cycles.retain(|id, _| services.iter().any(|service| &service.id == id));
+            for service in services {
+                let cycle = cycles.entry(service.id.clone()).or_insert(ReannounceCycle {
+                    started: now,
+                    next: 0,
+                });
+                let ttl = Duration::from_secs(self.record_ttls(&service).shortest().into());
+                let mut crossed = false;
+                while let Some(percent) = thresholds.get(cycle.next) {
+                    if now < cycle.started + ttl * *percent / 100 {
+                        break;
                     }
-                    _ => {}
+                    crossed = true;
+                    cycle.next += 1;
+                }
+                if !crossed {
+                    continue;
                 }
+                if cycle.next >= thresholds.len() {
+                    *cycle = ReannounceCycle { started: now, next: 0 };
+                }
+                due.push(service);
             }
         }
+
+        if due.is_empty() {
+            return Ok(0);
+        }
+        let count = due.len();
+        debug!("(ANNOUNCE) Re-announcing {} services before their TTL runs out", count);
+        for packet in self.pack_service_records(due)? {
+            self.send_packet(&packet).await?;
+        }
+        Ok(count)
     }
 
-    pub async fn process_query(&self, packet: &DnsPacket, src: &SocketAddr) {
-        for question in &packet.questions {
-            println!("(QUERY) Received question: {:?}", question.qname);
+    /// Replaces a local service and announces its complete new record set in one packet.
+    ///
+    /// The SRV, TXT, and A records are sent with the cache-flush bit set so receivers
+    /// drop the stale copies and switch to the new set together.
+    ///
+    /// # Arguments
+    /// * `id` - The instance name of the service to update.
+    /// * `new_record` - The updated service; its `id` must match `id`.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the service was updated and the announcement sent.
+    /// * `Err(MdnsError)` - If the service is unknown, the ids differ, or sending fails.
+    pub async fn update_and_announce(&self, id: &str, mut new_record: ServiceRecord) -> Result<(), MdnsError> {
+        if new_record.id != id {
+            return Err(MdnsError::Generic(format!(
+                "Updated record id '{}' does not match '{}'",
+                new_record.id, id
+            )));
+        }
+        if self.registry.get_service(id).await.is_none() {
+            return Err(MdnsError::Generic(format!("Unknown local service '{}'", id)));
+        }
+        self.assign_host(&mut new_record);
+        normalize_service_type(&mut new_record)?;
+        validate_service_names(&new_record)?;
+        let local_ips = self.advertised_ipv4s()?;
 
-            if question.qtype == 12 && question.qclass == 1 {
-                let requested_service = question.qname.to_string();
-                let services = self.registry.list_services().await;
-                let matching_services: Vec<_> = services
-                    .into_iter()
-                    .filter(|s| s.service_type == requested_service)
-                    .collect();
+        self.registry
+            .add_service(new_record.clone())
+            .await
+            .map_err(|e| MdnsError::Generic(e.to_string()))?;
 
-                if matching_services.is_empty() {
-                    println!("(QUERY) No matching service for '{}'", requested_service);
-                    continue;
-                }
+        let cache_flush = UNIQUE_RECORDS;
+        let mut packet = DnsPacket::with_header(DnsHeader::response());
+        push_service_records(
+            &mut packet.answers,
+            &new_record,
+            cache_flush,
+            &local_ips,
+            &self.advertised_ipv6s(),
+            self.record_ttls(&new_record),
+        )?;
 
-                let mut response_packet = DnsPacket::new();
-                response_packet.flags = 0x8400; // QR=1, AA=1
+        debug!("(ADVERTISE) Announcing updated service: {:?}", new_record);
+        self.send_packet(&packet).await
+    }
 
-                for service in matching_services {
-                    println!("(QUERY) Responding with service: {:?}", service);
+    /// Removes a local service from the registry and multicasts a goodbye for it, so
+    /// peers drop the service right away instead of waiting for its TTL to run out.
+    ///
+    /// The goodbye covers only this service's records; other local services stay
+    /// advertised, and a host still serving one of them keeps its address records.
+    ///
+    /// # Arguments
+    /// * `id` - The instance name of the service to deregister.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the service was removed and the goodbye sent.
+    /// * `Err(MdnsError)` - If the service is unknown or sending fails.
+    pub async fn deregister_local_service(&self, id: &str) -> Result<(), MdnsError> {
+        let service = self
+            .registry
+            .get_service(id)
+            .await
+            .ok_or_else(|| MdnsError::Generic(format!("Unknown local service '{}'", id)))?;
+        self.registry
+            .remove_service(id)
+            .await
+            .map_err(|e| MdnsError::Generic(e.to_string()))?;
+
+        debug!("(GOODBYE) Deregistered service: {}", id);
+        let mut packet = self.goodbye_packet(std::slice::from_ref(&service))?;
+        let remaining = self.registry.list_services().await;
+        packet.answers.retain(|record| match record {
+            DnsRecord::A { name, .. } | DnsRecord::AAAA { name, .. } => !owns_host(&remaining, name),
+            _ => true,
+        });
+        self.send_packet(&packet).await
+    }
+
+    /// Sends a goodbye for every local service and removes them from the registry.
+    ///
+    /// Call this before the process exits so peers forget our services immediately.
+    pub async fn shutdown(&self) -> Result<(), MdnsError> {
+        let services = self.registry.list_services().await;
+        if services.is_empty() {
+            return Ok(());
+        }
+        for service in &services {
+            let _ = self.registry.remove_service(&service.id).await;
+        }
+
+        debug!("(GOODBYE) Shutting down, withdrawing {} services.", services.len());
+        let packet = self.goodbye_packet(&services)?;
+        self.send_packet(&packet).await
+    }
+
+    /// Builds a packet with the PTR, SRV, TXT, and address records of `services` at a TTL
+    /// of 0, the mDNS goodbye signal (RFC 6762 §10.1).
+    fn goodbye_packet(&self, services: &[ServiceRecord]) -> Result<DnsPacket, MdnsError> {
+        let local_ips = self.advertised_ipv4s()?;
+        let local_ipv6s = self.advertised_ipv6s();
+
+        let mut packet = DnsPacket::with_header(DnsHeader::response());
+        for service in services {
+            push_service_records(
+                &mut packet.answers,
+                service,
+                CacheFlush::default(),
+                &local_ips,
+                &local_ipv6s,
+                RecordTtls::uniform(0),
+            )?;
+        }
+        Ok(packet)
+    }
+
+    /// Returns the hostnames of our local services advertised at `address` in A records,
+    /// for answering reverse (`in-addr.arpa`) PTR queries.
+    async fn reverse_hosts(&self, address: Ipv4Addr) -> Vec<DnsName> {
+        let advertised = self.advertised_ipv4s().unwrap_or_default().contains(&address);
+        let mut hosts: Vec<DnsName> = Vec::new();
+        for service in self.answerable_services().await {
+            let reachable = match service.bind_addr {
+                Some(bind_addr) => bind_addr == address,
+                None => advertised,
+            };
+            if !reachable {
+                continue;
+            }
+            match dns_name(&service.origin) {
+                Ok(host) if !hosts.iter().any(|known| probe_key(known) == probe_key(&host)) => {
+                    hosts.push(host)
+                }
+                Ok(_) => {}
+                Err(err) => warn!("(QUERY) Skipping invalid host name: {}", err),
+            }
+        }
+        hosts.sort_by_key(probe_key);
+        hosts
+    }
+
+    /// Returns whether `allowed_service_types` lets the responder answer for `service_type`,
+    /// which may also be a subtype browse name such as `_printer._sub._http._tcp.local`.
+    fn answers_for_type(&self, service_type: &str) -> bool {
+        let Some(allowed) = &self.config.allowed_service_types else {
+            return true;
+        };
+        let service_type = service_type.trim_end_matches('.');
+        let base = service_type
+            .split_once("._sub.")
+            .map_or(service_type, |(_, base)| base);
+        allowed
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('.').eq_ignore_ascii_case(base))
+    }
+
+    /// Lists the local services whose type the responder answers for.
+    async fn answerable_services(&self) -> Vec<ServiceRecord> {
+        let mut services = self.registry.list_services().await;
+        services.retain(|service| self.answers_for_type(&service.service_type));
+        services
+    }
+
+    /// Returns the TTL of each record advertising `service`: the service's own TTL if set,
+    /// otherwise the configured per-type TTL or `default_ttl`.
+    fn record_ttls(&self, service: &ServiceRecord) -> RecordTtls {
+        if let Some(ttl) = service.ttl {
+            return RecordTtls::uniform(ttl);
+        }
+        let config = &self.config;
+        RecordTtls {
+            ptr: config.ptr_ttl.unwrap_or(config.default_ttl),
+            srv: config.srv_ttl.unwrap_or(config.default_ttl),
+            txt: config.txt_ttl.unwrap_or(config.default_ttl),
+            address: config.address_ttl.unwrap_or(config.default_ttl),
+        }
+    }
+
+    /// Returns the addresses for A records: `advertised_address` if set, otherwise the
+    /// address of every interface the service multicasts on.
+    fn advertised_ipv4s(&self) -> Result<Vec<Ipv4Addr>, MdnsError> {
+        if let Some(address) = self.config.advertised_address {
+            return Ok(vec![address]);
+        }
+        let mut addresses = multicast_interfaces(&self.config);
+        if addresses.is_empty() {
+            addresses.extend(get_local_ipv4());
+        }
+        if addresses.is_empty() {
+            return Err(MdnsError::Generic("Failed to get local IP".to_string()));
+        }
+        Ok(addresses)
+    }
+
+    /// Returns the IPv6 addresses for AAAA records, or none unless running dual-stack.
+    ///
+    /// Without `advertised_address_v6`, these are the global and unique-local addresses of
+    /// the host's interfaces, plus the link-local ones when `advertise_link_local_v6` is
+    /// set, falling back to the address of the default route.
+    fn advertised_ipv6s(&self) -> Vec<Ipv6Addr> {
+        if self.socket_v6.is_none() {
+            return Vec::new();
+        }
+        if let Some(address) = self.config.advertised_address_v6 {
+            return vec![address];
+        }
+        let mut addresses: Vec<Ipv6Addr> = local_ipv6_interfaces()
+            .into_iter()
+            .filter(|ip| advertisable_ipv6(ip, self.config.advertise_link_local_v6))
+            .collect();
+        if addresses.is_empty() {
+            addresses.extend(get_local_ipv6());
+        }
+        addresses
+    }
+
+    /// Sends an mDNS packet over the network.
+    ///
+    /// With `interfaces` configured, the packet is multicast out of each interface in turn
+    /// and the local source address of every send is logged.
+    pub async fn send_packet(&self, packet: &DnsPacket) -> Result<(), MdnsError> {
+        if self.socket_v6.is_some() {
+            let multicast_addr_v6 = SocketAddr::V6(SocketAddrV6::new(MDNS_GROUP_V6, self.config.port, 0, 0));
+            self.send_packet_to(packet, multicast_addr_v6).await?;
+        }
+
+        let multicast_addr =
+            SocketAddr::V4(SocketAddrV4::new(self.config.multicast_addr, self.config.port));
+        if self.config.interfaces.is_empty() {
+            return self.send_packet_to(packet, multicast_addr).await;
+        }
+
+        let bytes = packet.serialize();
+        // Selecting the outgoing interface and sending must not interleave between tasks.
+        let _guard = self.send_lock.lock().await;
+        for interface in &self.config.interfaces {
+            self.send_via(packet, &bytes, *interface).await?;
+        }
+        Ok(())
+    }
+
+    /// Multicasts the serialized `packet` over IPv4 out of `interface`.
+    ///
+    /// The caller must hold `send_lock`.
+    async fn send_via(&self, packet: &DnsPacket, bytes: &[u8], interface: Ipv4Addr) -> Result<(), MdnsError> {
+        let multicast_addr =
+            SocketAddr::V4(SocketAddrV4::new(self.config.multicast_addr, self.config.port));
+        self.socket
+            .send_to_via(bytes, multicast_addr, interface)
+            .await
+            .map_err(MdnsError::NetworkError)?;
+        MetricsCounters::bump(&self.counters.packets_sent);
+
+        let source = SocketAddr::V4(SocketAddrV4::new(interface, self.config.port));
+        *self.last_send_source.lock().unwrap() = Some(source);
+        trace!(
+            "(SEND) Sent mDNS packet with {} answers to {} from {}",
+            packet.answers.len(),
+            multicast_addr,
+            source
+        );
+        Ok(())
+    }
+
+    /// Multicasts `packet` to one `SendTarget`.
+    async fn send_to_target(&self, packet: &DnsPacket, target: SendTarget) -> Result<(), MdnsError> {
+        match target {
+            SendTarget::All => self.send_packet(packet).await,
+            SendTarget::Interface(interface) => {
+                let _guard = self.send_lock.lock().await;
+                self.send_via(packet, &packet.serialize(), interface).await
+            }
+            SendTarget::Ipv6 => {
+                let multicast_addr_v6 = SocketAddr::V6(SocketAddrV6::new(MDNS_GROUP_V6, self.config.port, 0, 0));
+                self.send_packet_to(packet, multicast_addr_v6).await
+            }
+        }
+    }
+
+    /// Returns the local source address of the most recent multi-interface send, if any.
+    pub fn last_send_source(&self) -> Option<SocketAddr> {
+        *self.last_send_source.lock().unwrap()
+    }
+
+    /// Sends an mDNS packet directly to a specific address, using the socket of its family.
+    pub async fn send_packet_to(&self, packet: &DnsPacket, dest: SocketAddr) -> Result<(), MdnsError> {
+        let socket = match (dest, &self.socket_v6) {
+            (SocketAddr::V6(_), Some(socket_v6)) => socket_v6,
+            (SocketAddr::V6(_), None) => {
+                return Err(MdnsError::Generic(format!(
+                    "Cannot send to {}: IPv6 is not enabled",
+                    dest
+                )))
+            }
+            (SocketAddr::V4(_), _) => &self.socket,
+        };
+        let bytes = packet.serialize();
+        socket
+            .send_to(&bytes, dest)
+            .await
+            .map_err(MdnsError::NetworkError)?;
+        MetricsCounters::bump(&self.counters.packets_sent);
+
+        trace!(
+            "(SEND) Sent mDNS packet with {} answers to {}",
+            packet.answers.len(),
+            dest
+        );
+        Ok(())
+    }
+
+    /// Sends a PTR query for `service_type` repeatedly until `shutdown` is cancelled.
+    ///
+    /// The first query goes out immediately. The delay before the next one starts at the
+    /// current value of `interval` and doubles after every query, up to
+    /// `TimingPolicy::requery_interval_max` (RFC 6762 §5.2); a cap at or below the interval
+    /// keeps the cadence fixed. A new value sent on `interval` sends a query at once and
+    /// restarts the backoff from it, e.g. to speed up during active discovery.
+    ///
+    /// A query is skipped when another host asked the same question without known
+    /// answers within `timing.duplicate_question_window` (RFC 6762 §7.3).
+    ///
+    /// # Returns
+    /// * `Ok(())` - Once `shutdown` is cancelled.
+    /// * `Err(MdnsError)` - If `service_type` is not a valid name. Failed sends are logged
+    ///   and retried with the next query.
+    pub async fn periodic_query(
+        &self,
+        service_type: &str,
+        mut interval: watch::Receiver<Duration>,
+        shutdown: CancellationToken,
+    ) -> Result<(), MdnsError> {
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        packet.questions.push(crate::DnsQuestion {
+            qname: dns_name(service_type)?,
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+
+        let mut base = *interval.borrow_and_update();
+        let mut delay = base;
+        // Once every sender is gone the interval can no longer change.
+        let mut watching = true;
+        loop {
+            trace!(
+                "(QUERY) Sending periodic query for service type: {}",
+                service_type
+            );
+            if self.asked_by_peer(&packet.questions[0]) {
+                // Another host just asked the same thing; its answers reach us too.
+                debug!("(QUERY) Suppressing duplicate query for {}", service_type);
+            } else if let Err(err) = self.send_packet(&packet).await {
+                error!("(QUERY) Failed to send periodic query: {:?}", err);
+            } else {
+                trace!(
+                    "(QUERY) Periodic query sent for service type: {}, next in {:?}",
+                    service_type, delay
+                );
+            }
+
+            let next_query = time::Instant::now() + delay;
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    _ = time::sleep_until(next_query) => {
+                        delay = (delay * 2).min(self.config.timing.requery_interval_max.max(base));
+                        break;
+                    }
+                    changed = interval.changed(), if watching => match changed {
+                        Ok(()) => {
+                            base = *interval.borrow_and_update();
+                            delay = base;
+                            debug!("(QUERY) Query interval for {} changed to {:?}", service_type, base);
+                            break;
+                        }
+                        Err(_) => watching = false,
+                    },
+                }
+            }
+        }
+    }
+
+    /// Changes the base interval of the periodic query sent by `run`, restarting its
+    /// backoff and sending a query at once.
+    pub fn set_query_interval(&self, interval: Duration) {
+        self.query_interval.send_replace(interval);
+    }
+
+    /// Advertises all local services as unsolicited mDNS responses.
+    ///
+    /// Large advertisements are split into several packets, sent one after another, and
+    /// the advertise policy may give each interface its own packets.
+    pub async fn advertise_services(&self) -> Result<(), MdnsError> {
+        let services = self.registry.list_services().await;
+        let mut targets = Vec::new();
+        let mut packets = Vec::new();
+        if services.is_empty() {
+            debug!("(ADVERTISE) No local services to advertise.");
+            targets.push(SendTarget::All);
+            packets.push(response_packet(Vec::new()));
+        } else {
+            for (target, shaped) in self.advertisement_packets(&services)? {
+                targets.extend(std::iter::repeat_n(target, shaped.len()));
+                packets.extend(shaped);
+            }
+        }
+
+        let advertising = packets.iter().any(|packet| !packet.answers.is_empty());
+        if advertising {
+            // Coalesce records already multicast within the rate limit (RFC 6762 §6);
+            // the copies of a record for different interfaces share one decision.
+            self.suppression
+                .retain_round(&mut packets, self.config.timing.multicast_rate_limit);
+            if packets.iter().all(|packet| packet.answers.is_empty()) {
+                debug!("(ADVERTISE) All answers suppressed by rate limit.");
+            }
+        }
+        for (target, packet) in targets.into_iter().zip(packets) {
+            if advertising && packet.answers.is_empty() {
+                continue;
+            }
+            if packet.answers.is_empty() {
+                debug!("(ADVERTISE) No answers in the mDNS packet.");
+            } else {
+                debug!(
+                    "(ADVERTISE) Sending mDNS packet with {} answers.",
+                    packet.answers.len()
+                );
+            }
+            self.send_to_target(&packet, target).await?;
+        }
+        Ok(())
+    }
+
+    /// Adds a node learned from `source` to the registry, seen now.
+    async fn add_node_to_registry(
+        &self,
+        id: &str,
+        ip_address: &str,
+        ttl: Option<u32>,
+        cache_flush: bool,
+        source: SocketAddr,
+    ) -> Result<(), MdnsError> {
+        let node = NodeRecord {
+            id: id.to_string(),
+            ip_address: ip_address.to_string(),
+            ttl,
+            source: Some(source),
+            last_seen: Some(self.clock.now()),
+            scope_id: None,
+        };
+        let previous = if cache_flush {
+            self.registry.flush_and_set(id, node.clone()).await
+        } else {
+            let previous = self.registry.get_node(id).await;
+            self.registry.add_node(node.clone()).await.map(|()| previous)
+        }
+        .map_err(|e| MdnsError::Generic(e.to_string()))?;
+        self.publish_node(previous, node);
+        Ok(())
+    }
+
+    /// Subscribes to discovery events. Each receiver sees every event published after it
+    /// subscribed; a receiver that falls more than 64 events behind skips the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<DiscoveryEvent> {
+        self.events.subscribe()
+    }
+
+    /// Returns the discovery events as a `Stream`, for consumers that would rather write
+    /// `while let Some(event) = stream.next().await` than loop over a receiver.
+    ///
+    /// Like `subscribe`, it only sees events published after the call. Events skipped
+    /// because the stream fell behind are logged and dropped; the stream ends when the
+    /// service is dropped.
+    pub fn discovery_stream(&self) -> impl Stream<Item = DiscoveryEvent> + Send + 'static {
+        stream::unfold(self.subscribe(), |mut events| async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => return Some((event, events)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("(DISCOVERY) Event stream fell behind, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// Publishes `ServiceDiscovered` for a new node, or `ServiceUpdated` if its address changed.
+    fn publish_node(&self, previous: Option<NodeRecord>, node: NodeRecord) {
+        let event = match previous {
+            None => DiscoveryEvent::ServiceDiscovered(node),
+            Some(previous) if previous.ip_address != node.ip_address => {
+                DiscoveryEvent::ServiceUpdated(node)
+            }
+            Some(_) => return,
+        };
+        // Sending only fails when nobody is subscribed.
+        let _ = self.events.send(event);
+    }
+
+    /// Listens for incoming mDNS packets and processes them.
+    ///
+    /// Received packets are handed to a bounded processing queue so that slow processing
+    /// never blocks `recv_from`. When the queue is full, new packets are dropped and counted
+    /// (see [`MdnsService::dropped_packets`]).
+    ///
+    /// Transient receive errors, such as an ICMP port-unreachable reported for an earlier
+    /// send, are logged and skipped; `listen` only returns once a transport fails for good.
+    pub async fn listen(&self) -> Result<(), MdnsError> {
+        let (tx, rx) = mpsc::channel(PROCESSING_QUEUE_CAPACITY);
+        let receive_v6 = async {
+            match &self.socket_v6 {
+                Some(socket_v6) => self.receive_loop(socket_v6.as_ref(), tx.clone()).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            result = self.receive_loop(self.socket.as_ref(), tx.clone()) => result,
+            result = receive_v6 => result,
+            _ = self.process_loop(rx) => Ok(()),
+        }
+    }
+
+    /// Returns the number of received packets dropped because the processing queue was full.
+    pub fn dropped_packets(&self) -> u64 {
+        self.dropped_packets.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of received packets dropped because the registry stayed locked
+    /// for longer than `registry_lock_timeout`.
+    pub fn contended_packets(&self) -> u64 {
+        self.contended_packets.load(Ordering::Relaxed)
+    }
+
+    /// Returns a snapshot of the traffic and discovery counters.
+    pub fn metrics(&self) -> MdnsMetrics {
+        MdnsMetrics {
+            packets_sent: self.counters.packets_sent.load(Ordering::Relaxed),
+            packets_received: self.counters.packets_received.load(Ordering::Relaxed),
+            parse_failures: self.counters.parse_failures.load(Ordering::Relaxed),
+            queries_answered: self.counters.queries_answered.load(Ordering::Relaxed),
+            dropped_packets: self.dropped_packets(),
+            contended_packets: self.contended_packets(),
+            nodes_known: self.registry.node_count(),
+        }
+    }
+
+    /// Receives packets from the transport and enqueues them without waiting on processing.
+    ///
+    /// Transient receive errors (see `is_transient_recv_error`) are logged and the loop
+    /// carries on; any other error ends it.
+    async fn receive_loop(
+        &self,
+        socket: &dyn Transport,
+        tx: mpsc::Sender<(Vec<u8>, SocketAddr)>,
+    ) -> Result<(), MdnsError> {
+        let mut buf = vec![0; self.config.recv_buffer_size.max(1)];
+        loop {
+            let (len, src) = match socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(err) if is_transient_recv_error(&err) => {
+                    warn!("(LISTEN) Transient receive error, continuing: {}", err);
+                    time::sleep(RECV_ERROR_BACKOFF).await;
+                    continue;
+                }
+                Err(err) => return Err(MdnsError::NetworkError(err)),
+            };
+            trace!("(LISTEN) Packet received from {} with size {}", src, len);
+            MetricsCounters::bump(&self.counters.packets_received);
+            if len == buf.len() {
+                warn!(
+                    "(LISTEN) Packet from {} filled the {} byte receive buffer and may be truncated",
+                    src, len
+                );
+            }
+
+            if let Err(TrySendError::Full(_)) = tx.try_send((buf[..len].to_vec(), src)) {
+                let dropped = self.dropped_packets.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!(
+                    "(LISTEN) Processing queue full, dropped packet from {} ({} dropped)",
+                    src, dropped
+                );
+            }
+        }
+    }
+
+    /// Drains the processing queue, dispatching each packet to the query or response path.
+    async fn process_loop(&self, mut rx: mpsc::Receiver<(Vec<u8>, SocketAddr)>) {
+        while let Some((data, src)) = rx.recv().await {
+            let lock = self.registry.lock_for_processing();
+            let _guard = match time::timeout(self.config.registry_lock_timeout, lock).await {
+                Ok(guard) => guard,
+                Err(_) => {
+                    let contended = self.contended_packets.fetch_add(1, Ordering::Relaxed) + 1;
+                    warn!(
+                        "(LISTEN) Registry busy, dropped packet from {} ({} dropped)",
+                        src, contended
+                    );
+                    continue;
+                }
+            };
+
+            let span = debug_span!("packet", %src, len = data.len());
+            self.dispatch_packet(&data, &src).instrument(span).await;
+        }
+    }
+
+    /// Parses and validates a received packet and hands it to the query or response path.
+    async fn dispatch_packet(&self, data: &[u8], src: &SocketAddr) {
+        let packet = DnsPacket::parse(data).map_err(|err| err.to_string()).and_then(|packet| {
+            packet.validate().map(|()| packet).map_err(|err| err.to_string())
+        });
+        match packet {
+            Ok(packet) if packet.header().opcode() != 0 => {
+                // RFC 6762 §18.3: messages with any other opcode are ignored.
+                trace!("(LISTEN) Ignoring packet with opcode {} from {}", packet.header().opcode(), src);
+            }
+            Ok(packet) if packet.is_response() => {
+                trace!("(LISTEN) Response packet from {}", src);
+                self.process_response(&packet, src).await;
+            }
+            Ok(packet) => {
+                trace!("(LISTEN) Query packet from {}", src);
+                self.process_query(&packet, src).await;
+            }
+            Err(err) => {
+                MetricsCounters::bump(&self.counters.parse_failures);
+                warn!("(LISTEN) Dropping malformed packet from {}: {}", src, err);
+            }
+        }
+    }
+
+    /// Periodically prints the node registry for debugging, every
+    /// `config.registry_print_interval`. Returns at once if the interval is `None`.
+    pub async fn print_node_registry(&self) {
+        let Some(interval) = self.config.registry_print_interval else {
+            return;
+        };
+        loop {
+            time::sleep(interval).await;
+            let nodes = self.registry.list_nodes().await;
+            debug!("(NODE REGISTRY) Nodes: {:?}", nodes);
+        }
+    }
+
+    /// Runs the mDNS service, spawning advertise, query, listen, and registry print tasks.
+    ///
+    /// `config.run_mode` selects the tasks: `ResponderOnly` sends no queries, `QuerierOnly`
+    /// neither announces nor advertises, and the registry print task only runs while
+    /// `config.registry_print_interval` is set.
+    ///
+    /// Every task exits once `shutdown` is cancelled, after which local services are
+    /// withdrawn with a goodbye announcement.
+    pub async fn run(
+        self: Arc<Self>,
+        service_type: String,
+        query_interval: u64,
+        advertise_interval: u64,
+        shutdown: CancellationToken,
+    ) {
+        let advertise_service = Arc::clone(&self);
+        let query_service = Arc::clone(&self);
+        let listen_service = Arc::clone(&self);
+        let registry_service = Arc::clone(&self);
+        let refresh_service = Arc::clone(&self);
+        let sweep_service = Arc::clone(&self);
+        let goodbye_service = Arc::clone(&self);
+
+        let responds = self.config.run_mode != RunMode::QuerierOnly;
+        let queries = self.config.run_mode != RunMode::ResponderOnly;
+
+        if self.config.mode == ResponderMode::Active && responds {
+            let announce_rx = self.announce_rx.lock().unwrap().take();
+            match announce_rx {
+                Some(mut announce_rx) => {
+                    let announce_service = Arc::clone(&self);
+                    let announce_shutdown = shutdown.clone();
+                    spawn_until_cancelled(&shutdown, async move {
+                        while let Some(ids) = announce_rx.recv().await {
+                            let service = Arc::clone(&announce_service);
+                            spawn_until_cancelled(&announce_shutdown, async move {
+                                if let Err(err) = service.announce_services(&ids).await {
+                                    error!("(ANNOUNCE) Error: {:?}", err);
+                                }
+                            });
+                        }
+                    });
+                }
+                None => warn!("(ANNOUNCE) Service is already running, not announcing new services."),
+            }
+
+            spawn_until_cancelled(&shutdown, async move {
+                loop {
+                    time::sleep(Duration::from_secs(advertise_interval)).await;
+                    if let Err(err) = advertise_service.advertise_services().await {
+                        error!("(ADVERTISE) Error: {:?}", err);
+                    }
+                }
+            });
+
+            if !self.config.timing.reannounce_thresholds.is_empty() {
+                let reannounce_service = Arc::clone(&self);
+                spawn_until_cancelled(&shutdown, async move {
+                    loop {
+                        reannounce_service
+                            .clock
+                            .sleep(reannounce_service.config.timing.reannounce_check_interval)
+                            .await;
+                        if let Err(err) = reannounce_service.reannounce_expiring().await {
+                            error!("(ANNOUNCE) Re-announcement error: {:?}", err);
+                        }
+                    }
+                });
+            }
+        }
+
+        if queries {
+            self.query_interval.send_replace(Duration::from_secs(query_interval));
+            let interval = self.query_interval.subscribe();
+            let query_shutdown = shutdown.clone();
+            spawn_until_cancelled(&shutdown, async move {
+                if let Err(err) = query_service
+                    .periodic_query(&service_type, interval, query_shutdown)
+                    .await
+                {
+                    error!("(QUERY) Cannot query for service type: {}", err);
+                }
+            });
+        }
+
+        spawn_until_cancelled(&shutdown, async move {
+            if let Err(err) = listen_service.listen().await {
+                error!("(LISTEN) Error: {:?}", err);
+            }
+        });
+
+        if self.config.registry_print_interval.is_some() {
+            spawn_until_cancelled(&shutdown, async move {
+                registry_service.print_node_registry().await;
+            });
+        }
+
+        if queries {
+            spawn_until_cancelled(&shutdown, async move {
+                loop {
+                    time::sleep(refresh_service.config.timing.address_refresh_interval).await;
+                    if let Err(err) = refresh_service.refresh_expired_addresses().await {
+                        error!("(REFRESH) Error: {:?}", err);
+                    }
+                }
+            });
+        }
+
+        let rejoin_service = Arc::clone(&self);
+        spawn_until_cancelled(&shutdown, async move {
+            rejoin_service.watch_local_addresses().await;
+        });
+
+        spawn_until_cancelled(&shutdown, async move {
+            loop {
+                sweep_service
+                    .clock
+                    .sleep(sweep_service.config.timing.record_sweep_interval)
+                    .await;
+                for id in sweep_service.registry.sweep_expired().await {
+                    debug!("(EXPIRY) Record expired: {}", id);
+                    let _ = sweep_service.events.send(DiscoveryEvent::ServiceExpired(id));
+                }
+                for id in sweep_service.registry.flag_unresolved().await {
+                    warn!("(EXPIRY) SRV target of {} does not resolve to an address", id);
+                    let _ = sweep_service.events.send(DiscoveryEvent::ServiceUnresolved(id));
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            shutdown.cancelled().await;
+            debug!("(TASK) Shutdown requested, stopping tasks.");
+            if let Err(err) = goodbye_service.shutdown().await {
+                error!("(GOODBYE) Error: {:?}", err);
+            }
+        });
+
+        debug!("(TASK) All tasks are running.");
+    }
+
+    /// Returns the local addresses of the interfaces the IPv4 multicast group was last
+    /// joined on, by socket setup or `rejoin_multicast`. `0.0.0.0` stands for the interface
+    /// the OS chose; the list is empty for custom transports until `rejoin_multicast` runs.
+    pub fn joined_interfaces(&self) -> Vec<Ipv4Addr> {
+        self.joined_interfaces.lock().unwrap().clone()
+    }
+
+    /// Joins the IPv4 multicast group again on the current interfaces, leaving it first.
+    ///
+    /// Memberships are lost when an interface goes down or its address changes, e.g. after
+    /// a DHCP renewal or a suspend and resume; the service then silently stops receiving.
+    /// Interfaces are chosen as at startup, falling back to the OS's choice.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the group was joined on at least one interface.
+    /// * `Err(MdnsError)` - If joining failed everywhere.
+    pub async fn rejoin_multicast(&self) -> Result<(), MdnsError> {
+        let group = self.config.multicast_addr;
+        let mut joined = Vec::new();
+        for interface in multicast_interfaces(&self.config) {
+            match self.socket.rejoin_multicast_v4(group, interface) {
+                Ok(()) => joined.push(interface),
+                Err(err) => warn!("(REJOIN) Failed to join {} on {}: {}", group, interface, err),
+            }
+        }
+        if joined.is_empty() {
+            self.socket
+                .rejoin_multicast_v4(group, Ipv4Addr::UNSPECIFIED)
+                .map_err(|err| MdnsError::MulticastError(format!("Failed to rejoin {}: {}", group, err)))?;
+            joined.push(Ipv4Addr::UNSPECIFIED);
+        }
+
+        debug!("(REJOIN) Joined {} on {:?}", group, joined);
+        *self.joined_interfaces.lock().unwrap() = joined;
+        Ok(())
+    }
+
+    /// Rejoins the multicast group whenever the local addresses change, checking every
+    /// `timing.interface_check_interval`.
+    async fn watch_local_addresses(&self) {
+        let local_addresses = || {
+            let mut addresses = multicast_interfaces(&self.config);
+            addresses.extend(get_local_ipv4());
+            addresses.sort();
+            addresses.dedup();
+            addresses
+        };
+        let mut known = local_addresses();
+        loop {
+            self.clock.sleep(self.config.timing.interface_check_interval).await;
+            let current = local_addresses();
+            if current == known {
+                continue;
+            }
+            debug!("(REJOIN) Local addresses changed from {:?} to {:?}", known, current);
+            if let Err(err) = self.rejoin_multicast().await {
+                error!("(REJOIN) Error: {:?}", err);
+            }
+            known = current;
+        }
+    }
+
+    /// Forgets everything learned from peers: discovered services, their TXT data, and
+    /// host addresses. Local services stay registered and advertised.
+    ///
+    /// Meant for when the host moves to another network, where peers from the previous one
+    /// would otherwise linger until their TTLs run out. Received packets wait for the reset
+    /// to finish, and it never interleaves with the TTL sweep.
+    pub async fn reset_discovery(&self) {
+        let _guard = self.registry.lock_for_update().await;
+        self.registry.clear_discovered().await;
+        self.registry.clear_nodes().await;
+        debug!("(RESET) Cleared discovered services and nodes");
+    }
+
+    /// Sends an A query for every discovered service whose SRV record is still cached but
+    /// whose target host no longer has a known address.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The number of target hosts that were queried.
+    /// * `Err(MdnsError)` - If the query could not be sent.
+    pub async fn refresh_expired_addresses(&self) -> Result<usize, MdnsError> {
+        let mut stale_targets: Vec<String> = Vec::new();
+        for service in self.registry.list_discovered_services().await {
+            if stale_targets.contains(&service.origin) {
+                continue;
+            }
+            if self.registry.denies_type(&service.origin, 1) {
+                trace!("(REFRESH) {} has no A record, not querying it", service.origin);
+                continue;
+            }
+            if self.registry.get_node(&service.origin).await.is_none() {
+                stale_targets.push(service.origin);
+            }
+        }
+
+        if stale_targets.is_empty() {
+            return Ok(0);
+        }
+
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        for target in &stale_targets {
+            debug!("(REFRESH) Address for {} expired, re-querying", target);
+            packet.questions.push(crate::DnsQuestion {
+                qname: dns_name(target)?,
+                qtype: 1,
+                qclass: 1,
+                unicast_response: false,
+            });
+        }
+        self.send_packet(&packet).await?;
+        Ok(stale_targets.len())
+    }
+
+    /// Resolves several service instances concurrently.
+    ///
+    /// At most `MdnsConfig::max_concurrent_resolves` instances are queried at once, so
+    /// resolving hundreds of instances never turns into a query storm.
+    ///
+    /// # Arguments
+    /// * `instances` - Full instance names, e.g. `Printer._ipp._tcp.local`.
+    /// * `timeout` - How long to wait for each individual instance.
+    ///
+    /// # Returns
+    /// * `Vec<(String, Result<ResolvedService, MdnsError>)>` - One entry per instance, in
+    ///   the order the resolves completed.
+    pub async fn resolve_many(
+        &self,
+        instances: &[String],
+        timeout: Duration,
+    ) -> Vec<(String, Result<ResolvedService, MdnsError>)> {
+        let limit = Semaphore::new(self.config.max_concurrent_resolves.max(1));
+        let limit = &limit;
+        let mut pending: FuturesUnordered<_> = instances
+            .iter()
+            .map(|instance| async move {
+                let _permit = limit.acquire().await;
+                let result = self.resolve_one(instance, timeout).await;
+                (instance.clone(), result)
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(instances.len());
+        while let Some(result) = pending.next().await {
+            results.push(result);
+        }
+        results
+    }
+
+    /// Sends a single PTR query for `service_type` and collects the instances that answer.
+    ///
+    /// Answers are gathered until `timeout` elapses, or earlier once no new instance has
+    /// arrived for `TimingPolicy::resolve_quiet_period`. Responses are processed by
+    /// `listen`, which must be running.
+    ///
+    /// # Arguments
+    /// * `service_type` - The service type to browse, e.g. `_http._tcp.local`, or a subtype
+    ///   of it such as `_printer._sub._http._tcp.local` to find only the instances
+    ///   advertising that subtype.
+    /// * `timeout` - The longest time to wait for answers.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<ServiceRecord>)` - The matching services, one per instance, in the order
+    ///   RFC 2782 says to try them: by ascending SRV priority, then at random weighted by
+    ///   SRV weight. Instances that tie on both keep id order when all weights are 0.
+    /// * `Err(MdnsError)` - If the query could not be sent.
+    pub async fn resolve(
+        &self,
+        service_type: &str,
+        timeout: Duration,
+    ) -> Result<Vec<ServiceRecord>, MdnsError> {
+        let mut resolved = self.resolve_types(&[service_type], timeout).await?;
+        Ok(resolved.remove(service_type).unwrap_or_default())
+    }
+
+    /// Browses several service types at once, like `resolve` but with one PTR question per
+    /// type packed into a single query packet.
+    ///
+    /// Answers are gathered until `timeout` elapses, or earlier once no new instance of any
+    /// of the types has arrived for `TimingPolicy::resolve_quiet_period`.
+    ///
+    /// # Returns
+    /// * `Ok(HashMap<String, Vec<ServiceRecord>>)` - The instances found for each type in
+    ///   `service_types`, keyed as given and ordered as `resolve` orders them. Every type
+    ///   has an entry, empty when nothing answered.
+    /// * `Err(MdnsError)` - If a type is not a valid name or the query could not be sent.
+    pub async fn resolve_types(
+        &self,
+        service_types: &[&str],
+        timeout: Duration,
+    ) -> Result<HashMap<String, Vec<ServiceRecord>>, MdnsError> {
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        let mut lookups = Vec::with_capacity(service_types.len());
+        for service_type in service_types {
+            let qname = dns_name(service_type)?;
+            lookups.push((service_type.to_string(), qname.to_string()));
+            if packet.questions.iter().all(|question| question.qname != qname) {
+                packet.questions.push(crate::DnsQuestion {
+                    qname,
+                    qtype: 12,
+                    qclass: 1,
+                    unicast_response: false,
+                });
+            }
+        }
+        self.send_packet(&packet).await?;
+
+        let deadline = time::Instant::now() + timeout;
+        let mut wait_until = deadline;
+        let mut found: HashMap<String, HashMap<String, ServiceRecord>> = HashMap::new();
+        loop {
+            let notified = self.discovery_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let mut new_answers = false;
+            for service in self.registry.list_discovered_services().await {
+                let browse_names = service.browse_names();
+                for (requested, lookup) in &lookups {
+                    let instances = found.entry(requested.clone()).or_default();
+                    let browsed = browse_names.iter().any(|name| name.eq_ignore_ascii_case(lookup));
+                    if browsed && !instances.contains_key(&service.id) {
+                        instances.insert(service.id.clone(), service.clone());
+                        new_answers = true;
+                    }
+                }
+            }
+            let now = time::Instant::now();
+            if new_answers {
+                wait_until = (now + self.config.timing.resolve_quiet_period).min(deadline);
+            }
+            if now >= wait_until {
+                break;
+            }
+            let _ = time::timeout_at(wait_until, notified).await;
+        }
+
+        let mut resolved = HashMap::with_capacity(lookups.len());
+        for (requested, lookup) in lookups {
+            let mut services: Vec<ServiceRecord> = found
+                .get(&requested)
+                .map(|instances| instances.values().cloned().collect())
+                .unwrap_or_default();
+            services.sort_by(|a, b| a.id.cmp(&b.id));
+            let services = srv_order(services, |service| {
+                (service.priority.unwrap_or(0), service.weight.unwrap_or(0))
+            });
+            debug!("(RESOLVE) Found {} instances of {}", services.len(), lookup);
+            resolved.insert(requested, services);
+        }
+        Ok(resolved)
+    }
+
+    /// Waits until at least `count` distinct instances of `service_type` are known, e.g.
+    /// for a quorum of cluster peers at startup.
+    ///
+    /// A PTR query is sent at once and repeated while waiting, first after
+    /// `TimingPolicy::requery_interval` and then at doubling intervals up to
+    /// `TimingPolicy::requery_interval_max`. Instances already cached count towards
+    /// `count`. Responses are processed by `listen`, which must be running.
+    ///
+    /// # Arguments
+    /// * `service_type` - The service type to browse, e.g. `_http._tcp.local`.
+    /// * `count` - The number of instances to wait for.
+    /// * `timeout` - The longest time to wait.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<ServiceRecord>)` - Every known instance of the type, at least `count` of
+    ///   them, sorted by instance name.
+    /// * `Err(MdnsError)` - If a query could not be sent, or fewer than `count` instances
+    ///   were known when `timeout` elapsed.
+    pub async fn wait_for_services(
+        &self,
+        service_type: &str,
+        count: usize,
+        timeout: Duration,
+    ) -> Result<Vec<ServiceRecord>, MdnsError> {
+        let qname = dns_name(service_type)?;
+        let lookup = qname.to_string();
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        packet.questions.push(crate::DnsQuestion {
+            qname,
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        self.send_packet(&packet).await?;
+
+        let deadline = time::Instant::now() + timeout;
+        let mut interval = self.config.timing.requery_interval;
+        let mut next_query = time::Instant::now() + interval;
+        loop {
+            let notified = self.discovery_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let mut services: Vec<ServiceRecord> = self
+                .registry
+                .list_discovered_services()
+                .await
+                .into_iter()
+                .filter(|service| {
+                    service
+                        .browse_names()
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(&lookup))
+                })
+                .collect();
+            if services.len() >= count {
+                services.sort_by(|a, b| a.id.cmp(&b.id));
+                debug!("(RESOLVE) Found {} instances of {}", services.len(), lookup);
+                return Ok(services);
+            }
+
+            let now = time::Instant::now();
+            if now >= deadline {
+                return Err(MdnsError::Timeout(format!(
+                    "Found {} of {} instances of {}",
+                    services.len(),
+                    count,
+                    lookup
+                )));
+            }
+            if now >= next_query {
+                trace!("(RESOLVE) Repeating query for {}", lookup);
+                self.send_packet(&packet).await?;
+                interval = (interval * 2).min(self.config.timing.requery_interval_max);
+                next_query = now + interval;
+            }
+            let _ = time::timeout_at(next_query.min(deadline), notified).await;
+        }
+    }
+
+    /// Asks the network which service types exist with the DNS-SD meta-query
+    /// `_services._dns-sd._udp.local` (RFC 6763 §9).
+    ///
+    /// Answers are gathered like `resolve`: until `timeout` elapses, or earlier once no new
+    /// type has arrived for `TimingPolicy::resolve_quiet_period`. Types already cached from
+    /// earlier answers are included. `listen` must be running.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<String>)` - The service types, e.g. `_http._tcp.local`, sorted.
+    /// * `Err(MdnsError)` - If the query could not be sent.
+    pub async fn enumerate_service_types(&self, timeout: Duration) -> Result<Vec<String>, MdnsError> {
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        packet.questions.push(crate::DnsQuestion {
+            qname: dns_name(SERVICE_TYPE_ENUMERATION)?,
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        self.send_packet(&packet).await?;
+
+        let deadline = time::Instant::now() + timeout;
+        let mut wait_until = deadline;
+        let mut found = 0;
+        loop {
+            let notified = self.discovery_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let known = self.registry.list_service_types().len();
+            let now = time::Instant::now();
+            if known > found {
+                found = known;
+                wait_until = (now + self.config.timing.resolve_quiet_period).min(deadline);
+            }
+            if now >= wait_until {
+                break;
+            }
+            let _ = time::timeout_at(wait_until, notified).await;
+        }
+
+        let service_types = self.registry.list_service_types();
+        debug!("(RESOLVE) Found {} service types", service_types.len());
+        Ok(service_types)
+    }
+
+    /// Sends an SRV query for a single instance and waits until its record is discovered.
+    async fn resolve_one(&self, instance: &str, timeout: Duration) -> Result<ResolvedService, MdnsError> {
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        packet.questions.push(crate::DnsQuestion {
+            qname: dns_name(instance)?,
+            qtype: 33,
+            qclass: 1,
+            unicast_response: false,
+        });
+        self.send_packet(&packet).await?;
+
+        let lookup = dns_name(instance)?.to_string();
+        time::timeout(timeout, async {
+            loop {
+                let notified = self.discovery_notify.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if let Some(resolved) = self.registry.instances().await.remove(&lookup) {
+                    return resolved;
+                }
+                notified.await;
+            }
+        })
+        .await
+        .map_err(|_| MdnsError::Timeout(format!("No SRV record received for {}", instance)))
+    }
+
+    /// Resolves a single service instance to everything needed to connect to it.
+    ///
+    /// SRV and TXT queries are sent for the instance; once its SRV record arrives, the
+    /// target host is queried for its addresses unless they are already known. Responses
+    /// are processed by `listen`, which must be running.
+    ///
+    /// # Arguments
+    /// * `instance_id` - The full instance name, e.g. `Printer._ipp._tcp.local`.
+    /// * `timeout` - The longest time to wait for the SRV record and the host's addresses.
+    ///
+    /// # Returns
+    /// * `Ok(ResolvedInstance)` - The instance's host, port, addresses, and TXT metadata.
+    /// * `Err(MdnsError)` - If a query could not be sent, or the SRV record or an address
+    ///   did not arrive in time.
+    pub async fn resolve_instance(
+        &self,
+        instance_id: &str,
+        timeout: Duration,
+    ) -> Result<ResolvedInstance, MdnsError> {
+        let qname = dns_name(instance_id)?;
+        let lookup = qname.to_string();
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        for qtype in [33, 16] {
+            packet.questions.push(crate::DnsQuestion {
+                qname: qname.clone(),
+                qtype,
+                qclass: 1,
+                unicast_response: false,
+            });
+        }
+        self.send_packet(&packet).await?;
+
+        let deadline = time::Instant::now() + timeout;
+        let mut queried_host = None;
+        loop {
+            let notified = self.discovery_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(resolved) = self.registry.instances().await.remove(&lookup) {
+                if !resolved.addresses.is_empty() {
+                    debug!("(RESOLVE) Resolved {} to {:?}", lookup, resolved.addresses);
+                    return Ok(ResolvedInstance {
+                        instance: resolved.instance,
+                        host: resolved.host,
+                        port: resolved.port,
+                        priority: resolved.priority,
+                        weight: resolved.weight,
+                        addresses: resolved.addresses,
+                        txt: txt_pairs(&resolved.txt).into_iter().collect(),
+                    });
+                }
+                // Follow the SRV target, asking again if it moved to another host.
+                if queried_host.as_ref() != Some(&resolved.host) {
+                    self.query_host_addresses(&resolved.host).await?;
+                    queried_host = Some(resolved.host);
+                }
+            }
+
+            if time::timeout_at(deadline, notified).await.is_err() {
+                return Err(MdnsError::Timeout(match queried_host {
+                    Some(host) => format!("No address received for {} (host of {})", host, instance_id),
+                    None => format!("No SRV record received for {}", instance_id),
+                }));
+            }
+        }
+    }
+
+    /// Sends an A query (and an AAAA query when running dual-stack) for `host`.
+    async fn query_host_addresses(&self, host: &str) -> Result<(), MdnsError> {
+        let qname = dns_name(host)?;
+        let mut qtypes = vec![1];
+        if self.socket_v6.is_some() {
+            qtypes.push(28);
+        }
+        // Types the host's NSEC record denies have no answer to wait for.
+        qtypes.retain(|qtype| !self.registry.denies_type(host, *qtype));
+        if qtypes.is_empty() {
+            debug!("(RESOLVE) {} has no address records, not querying it", host);
+            return Ok(());
+        }
+
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        for qtype in qtypes {
+            packet.questions.push(crate::DnsQuestion {
+                qname: qname.clone(),
+                qtype,
+                qclass: 1,
+                unicast_response: false,
+            });
+        }
+        debug!("(RESOLVE) Querying addresses of {}", host);
+        self.send_packet(&packet).await
+    }
+
+    /// Processes an incoming mDNS response, recording discovered nodes and services.
+    ///
+    /// A response with the TC bit set holds only part of its answers, so the PTR names it
+    /// answered are asked again with its PTRs as known answers, to pull the rest.
+    pub async fn process_response(&self, packet: &DnsPacket, src: &SocketAddr) {
+        // Peers on a dual-stack socket are recorded by their plain IPv4 address.
+        let src = &normalize_source(src);
+        let accepted = match src {
+            SocketAddr::V4(_) => true,
+            SocketAddr::V6(_) => self.socket_v6.is_some(),
+        };
+        if accepted {
+            self.note_probe_conflicts(packet);
+            let local_services = self.registry.list_services().await;
+            // Records in the additional section are cached like answers (RFC 6762 §10),
+            // except NSEC, handled below.
+            let additionals = packet
+                .additionals
+                .iter()
+                .filter(|record| !matches!(record, DnsRecord::NSEC { .. }));
+            for answer in packet.answers.iter().chain(additionals) {
+                // Our own goodbyes loop back to us; they must never touch our view of ourselves.
+                if answer.ttl() == 0 && owns_record(&local_services, answer) {
+                    debug!(
+                        "(DISCOVERY) Ignoring echo of our own goodbye for {}",
+                        answer.name()
+                    );
+                    continue;
+                }
+                if answer.ttl() == 0 {
+                    self.forget_record(answer).await;
+                    continue;
+                }
+                if let Some((ours, theirs)) = self.conflicting_claim(&local_services, answer) {
+                    warn!(
+                        "(CONFLICT) {} claims {} as {}, but we advertise {}",
+                        src, answer.name(), theirs, ours
+                    );
+                    let _ = self.events.send(DiscoveryEvent::Conflict {
+                        name: answer.name().to_string(),
+                        ours,
+                        theirs,
+                    });
+                    continue;
+                }
+                match answer {
+                    DnsRecord::A { name, ip, ttl, cache_flush } => {
+                        let ip_address = Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]);
+                        debug!("(DISCOVERY) Discovered node: {} -> {}", name, ip_address);
+                        let _ = self
+                            .add_node_to_registry(
+                                &name.to_string(),
+                                &ip_address.to_string(),
+                                Some(*ttl),
+                                *cache_flush,
+                                *src,
+                            )
+                            .await;
+                    }
+                    DnsRecord::AAAA { name, ip, ttl, .. } => {
+                        let ip_address = Ipv6Addr::from(*ip);
+                        debug!("(DISCOVERY) Discovered node: {} -> {}", name, ip_address);
+                        let node = NodeRecord {
+                            id: name.to_string(),
+                            ip_address: ip_address.to_string(),
+                            ttl: Some(*ttl),
+                            source: Some(*src),
+                            last_seen: Some(self.clock.now()),
+                            scope_id: link_local_scope(&ip_address, src),
+                        };
+                        let previous = self.registry.get_node_v6(&node.id).await;
+                        if self.registry.add_node_v6(node.clone()).await.is_ok() {
+                            self.publish_node(previous, node);
+                        }
+                    }
+                    DnsRecord::PTR { name, ptr_name, ttl, .. } if is_service_type_enumeration(name) => {
+                        debug!("(DISCOVERY) Discovered service type {}", ptr_name);
+                        self.registry.add_service_type(&ptr_name.to_string(), Some(*ttl));
+                    }
+                    DnsRecord::PTR { name, ptr_name, .. } => {
+                        match ServiceInstanceName::from_dns_name(ptr_name) {
+                            Ok(instance) => {
+                                debug!(
+                                    "(DISCOVERY) Discovered instance '{}' of type {} in domain {}",
+                                    instance.instance, instance.service_type, instance.domain
+                                );
+                                if let Some(subtype) = subtype_of(name, &instance.service_name()) {
+                                    debug!("(DISCOVERY) {} has subtype {}", ptr_name, subtype);
+                                    let _ = self
+                                        .registry
+                                        .add_discovered_subtype(&ptr_name.to_string(), &subtype)
+                                        .await;
+                                }
+                            }
+                            Err(err) => warn!(
+                                "(DISCOVERY) Ignoring PTR to non-instance name {}: {}",
+                                ptr_name, err
+                            ),
+                        }
+                    }
+                    DnsRecord::SRV { name, ttl, priority, weight, port, target, cache_flush } => {
+                        if *cache_flush {
+                            self.flush_srv_target(name, target).await;
+                        }
+                        if let Ok(instance) = ServiceInstanceName::from_dns_name(name) {
+                            debug!(
+                                "(DISCOVERY) Instance '{}' ({}) is served by {}:{}",
+                                instance.instance,
+                                instance.service_name(),
+                                target,
+                                port
+                            );
+                            let service = ServiceRecord {
+                                id: name.to_string(),
+                                service_type: instance.service_name(),
+                                port: *port,
+                                ttl: Some(*ttl),
+                                origin: target.to_string(),
+                                priority: Some(*priority),
+                                weight: Some(*weight),
+                                cache_flush: CacheFlush::default(),
+                                txt: self
+                                    .registry
+                                    .get_txt(&name.to_string())
+                                    .await
+                                    .map(|record| txt_pairs(&record.entries))
+                                    .unwrap_or_default(),
+                                subtypes: self.registry.discovered_subtypes(&name.to_string()),
+                                protocol: Protocol::of_service_type(&instance.service_type),
+                                bind_addr: None,
+                            };
+                            let _ = self.registry.add_discovered_service(service).await;
+                        }
+                    }
+                    DnsRecord::TXT { name, ttl, txt_data, .. } => {
+                        let entries = decode_txt_strings(txt_data);
+                        debug!("(DISCOVERY) TXT for {}: {:?}", name, entries);
+                        // Keep the decoded metadata on the discovered service, whichever
+                        // of its SRV and TXT records arrived first.
+                        if let Some(mut service) = self
+                            .registry
+                            .get_discovered_service(&name.to_string())
+                            .await
+                        {
+                            service.txt = txt_pairs(&entries);
+                            let _ = self.registry.add_discovered_service(service).await;
+                        }
+                        let _ = self
+                            .registry
+                            .add_txt(TxtRecord {
+                                id: name.to_string(),
+                                entries,
+                                ttl: Some(*ttl),
+                            })
+                            .await;
+                    }
+                    DnsRecord::NSEC { name, types, ttl, .. } => {
+                        self.note_record_types(name, types, *ttl);
+                    }
+                    // OPT only belongs in the additional section and carries no discovery data.
+                    DnsRecord::OPT { .. } => {}
+                }
+            }
+            // Responders usually send their NSEC records as additional records (RFC 6762 §6.1).
+            for additional in &packet.additionals {
+                if let DnsRecord::NSEC { name, types, ttl, .. } = additional {
+                    if *ttl == 0 {
+                        self.registry.remove_record_types(&name.to_string());
+                    } else {
+                        self.note_record_types(name, types, *ttl);
+                    }
+                }
+            }
+            self.discovery_notify.notify_waiters();
+            if packet.header().is_truncated() {
+                self.query_truncated_remainder(packet, src).await;
+            }
+        }
+    }
+
+    /// Re-asks the PTR questions a truncated response answered, listing the PTRs it
+    /// carried as known answers so responders only send the instances that did not fit
+    /// (RFC 6762 §7.1).
+    async fn query_truncated_remainder(&self, packet: &DnsPacket, src: &SocketAddr) {
+        let received: Vec<DnsRecord> = packet
+            .answers
+            .iter()
+            .filter(|record| matches!(record, DnsRecord::PTR { .. }) && record.ttl() > 0)
+            .cloned()
+            .collect();
+        let mut query = DnsPacket::with_header(DnsHeader::query());
+        for record in &received {
+            if !query.questions.iter().any(|question| question.qname == *record.name()) {
+                query.questions.push(crate::DnsQuestion {
+                    qname: record.name().clone(),
+                    qtype: 12,
+                    qclass: 1,
+                    unicast_response: false,
+                });
+            }
+        }
+        if query.questions.is_empty() {
+            debug!("(DISCOVERY) Truncated response from {} has no PTR answers to follow up", src);
+            return;
+        }
+        query.answers = received;
+        debug!(
+            "(DISCOVERY) Response from {} was truncated, querying again with {} known answers",
+            src,
+            query.answers.len()
+        );
+        if let Err(err) = self.send_packet(&query).await {
+            warn!("(DISCOVERY) Failed to send follow-up query: {:?}", err);
+        }
+    }
+
+    /// Remembers which record types a peer's NSEC record lists for `name`, so no query is
+    /// sent for the types it denies.
+    fn note_record_types(&self, name: &DnsName, types: &[u16], ttl: u32) {
+        debug!("(DISCOVERY) {} only has record types {:?}", name, types);
+        self.registry.set_record_types(&name.to_string(), types.to_vec(), ttl);
+    }
+
+    /// Honors the cache-flush bit of a peer's SRV record (RFC 6762 §10.2). The record replaces
+    /// the cached one, so if it moves the instance to another host, the old host's addresses
+    /// are purged unless another discovered service still points at it.
+    ///
+    /// A, AAAA, and TXT records keep a single cached value per name, so inserting a flushed
+    /// record already replaces what was cached for them.
+    async fn flush_srv_target(&self, instance: &DnsName, target: &DnsName) {
+        let Some(cached) = self.registry.get_discovered_service(&instance.to_string()).await else {
+            return;
+        };
+        let old_target = cached.origin;
+        if old_target.eq_ignore_ascii_case(&target.to_string()) {
+            return;
+        }
+        let still_used = self
+            .registry
+            .list_discovered_services()
+            .await
+            .iter()
+            .any(|service| service.id != cached.id && service.origin.eq_ignore_ascii_case(&old_target));
+        if still_used {
+            return;
+        }
+
+        debug!(
+            "(DISCOVERY) {} moved from {} to {}, flushing the old host.",
+            instance, old_target, target
+        );
+        let mut flushed = false;
+        if self.registry.get_node(&old_target).await.is_some() {
+            flushed |= self.registry.remove_node(&old_target).await.is_ok();
+        }
+        if self.registry.get_node_v6(&old_target).await.is_some() {
+            flushed |= self.registry.remove_node_v6(&old_target).await.is_ok();
+        }
+        if flushed {
+            let _ = self.events.send(DiscoveryEvent::ServiceExpired(old_target));
+        }
+    }
 
-                    // Add PTR record
-                    response_packet.answers.push(DnsRecord::PTR {
-                        name: DnsName::new(&service.service_type).unwrap(),
-                        ttl: service.ttl.unwrap_or(120),
-                        ptr_name: DnsName::new(&service.id).unwrap(),
-                    });
+    /// Removes whatever a peer's goodbye record withdraws from the registry.
+    async fn forget_record(&self, record: &DnsRecord) {
+        let result = match record {
+            DnsRecord::A { name, .. } => self.registry.remove_node(&name.to_string()).await,
+            DnsRecord::AAAA { name, .. } => self.registry.remove_node_v6(&name.to_string()).await,
+            DnsRecord::PTR { name, ptr_name, .. } if is_service_type_enumeration(name) => {
+                self.registry.remove_service_type(&ptr_name.to_string());
+                return;
+            }
+            DnsRecord::PTR { ptr_name, .. } => {
+                self.registry
+                    .remove_discovered_service(&ptr_name.to_string())
+                    .await
+            }
+            DnsRecord::SRV { name, .. } => {
+                self.registry
+                    .remove_discovered_service(&name.to_string())
+                    .await
+            }
+            DnsRecord::TXT { name, .. } => self.registry.remove_txt(&name.to_string()).await,
+            DnsRecord::NSEC { name, .. } => {
+                self.registry.remove_record_types(&name.to_string());
+                return;
+            }
+            DnsRecord::OPT { .. } => return,
+        };
+        if result.is_ok() {
+            debug!("(GOODBYE) Peer withdrew {}", record.name());
+            if !matches!(record, DnsRecord::TXT { .. }) {
+                let _ = self
+                    .events
+                    .send(DiscoveryEvent::ServiceExpired(record.name().to_string()));
+            }
+        }
+    }
 
-                    // Add SRV record
-                    response_packet.answers.push(DnsRecord::SRV {
-                        name: DnsName::new(&service.id).unwrap(),
-                        ttl: service.ttl.unwrap_or(120),
-                        priority: service.priority.unwrap_or(0),
-                        weight: service.weight.unwrap_or(0),
-                        port: service.port,
-                        target: DnsName::new(&service.origin).unwrap(),
-                    });
+    /// Returns the multicast send history of every record answered so far, keyed by
+    /// record (e.g. `SRV Web._http._tcp.local`), including how often each was suppressed.
+    pub fn suppression_state(&self) -> HashMap<String, SuppressionEntry> {
+        self.suppression.snapshot()
+    }
 
-                    // Add A record
-                    if let SocketAddr::V4(addr) = src {
-                        let ip = addr.ip().octets();
-                        response_packet.answers.push(DnsRecord::A {
-                            name: DnsName::new(&service.origin).unwrap(),
-                            ttl: service.ttl.unwrap_or(120),
-                            ip,
-                        });
+    /// Clears the answer suppression state so every record is answered again immediately.
+    pub fn clear_suppression(&self) {
+        self.suppression.clear();
+    }
+
+    /// Collects the discovered services of `service_type` that may be answered from cache.
+    ///
+    /// Services whose remaining TTL is below `min_cache_answer_ttl` are skipped, since
+    /// advertising records that are about to expire makes them flap in peers' caches.
+    ///
+    /// # Returns
+    /// * `Vec<(ServiceRecord, u32)>` - Each eligible service with its remaining TTL.
+    async fn cached_answers(&self, service_type: &str) -> Vec<(ServiceRecord, u32)> {
+        let mut answers = Vec::new();
+        for service in self.registry.list_discovered_services().await {
+            if !service.service_type.eq_ignore_ascii_case(service_type) {
+                continue;
+            }
+            match self.registry.discovered_remaining_ttl(&service.id) {
+                Some(remaining) if remaining >= self.config.min_cache_answer_ttl => {
+                    answers.push((service, remaining));
+                }
+                _ => debug!(
+                    "(QUERY) Skipping near-expiry cached service '{}'",
+                    service.id
+                ),
+            }
+        }
+        answers
+    }
+
+    /// Marks names being probed that the answers of a response lay claim to.
+    fn note_probe_conflicts(&self, packet: &DnsPacket) {
+        let mut probes = self.probes.lock().unwrap();
+        if probes.is_empty() {
+            return;
+        }
+        for answer in &packet.answers {
+            if let Some(conflict) = probes.get_mut(&probe_key(answer.name())) {
+                debug!("(PROBE) Name {} is claimed by another host.", answer.name());
+                *conflict = true;
+            }
+        }
+    }
+
+    /// Returns what we advertise and what `record` claims instead, if `record` is for a
+    /// name one of `local_services` owns but carries data we do not advertise.
+    ///
+    /// An A or AAAA record conflicts when its address is not one of ours, an SRV record
+    /// when its target or port differs. Echoes of our own records never conflict.
+    fn conflicting_claim(
+        &self,
+        local_services: &[ServiceRecord],
+        record: &DnsRecord,
+    ) -> Option<(String, String)> {
+        match record {
+            DnsRecord::A { name, ip, .. } => {
+                if !owns_host(local_services, name) {
+                    return None;
+                }
+                // A bound service's host is advertised with its `bind_addr` instead.
+                let mut ours = self.advertised_ipv4s().unwrap_or_default();
+                ours.extend(bound_addresses(local_services, name));
+                let theirs = Ipv4Addr::from(*ip);
+                (!ours.is_empty() && !ours.contains(&theirs))
+                    .then(|| (join_addresses(&ours), theirs.to_string()))
+            }
+            DnsRecord::AAAA { name, ip, .. } => {
+                if !owns_host(local_services, name) {
+                    return None;
+                }
+                let ours = self.advertised_ipv6s();
+                let theirs = Ipv6Addr::from(*ip);
+                (!ours.is_empty() && !ours.contains(&theirs))
+                    .then(|| (join_addresses(&ours), theirs.to_string()))
+            }
+            DnsRecord::SRV { name, port, target, .. } => {
+                let service = local_services
+                    .iter()
+                    .find(|service| probe_key_of(&service.id) == Some(probe_key(name)))?;
+                let same_target = probe_key_of(&service.origin) == Some(probe_key(target));
+                (!same_target || service.port != *port).then(|| {
+                    (
+                        format!("{}:{}", service.origin, service.port),
+                        format!("{}:{}", target, port),
+                    )
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Remembers the multicast questions another host asked without known answers, so the
+    /// periodic query can treat them as its own (RFC 6762 §7.3). Our own queries loop
+    /// back from one of our addresses and are not noted.
+    fn note_observed_questions(&self, packet: &DnsPacket, src: &SocketAddr) {
+        if !packet.answers.is_empty() || self.is_own_source(src) {
+            return;
+        }
+        let now = self.clock.now();
+        let window = self.config.timing.duplicate_question_window;
+        let mut observed = self.observed_questions.lock().unwrap();
+        observed.retain(|_, seen| now.saturating_duration_since(*seen) < window);
+        for question in packet.questions.iter().filter(|question| !question.unicast_response) {
+            observed.insert((probe_key(&question.qname), question.qtype), now);
+        }
+    }
+
+    /// Returns whether another host asked `question` within `duplicate_question_window`.
+    fn asked_by_peer(&self, question: &crate::DnsQuestion) -> bool {
+        let key = (probe_key(&question.qname), question.qtype);
+        let window = self.config.timing.duplicate_question_window;
+        match self.observed_questions.lock().unwrap().get(&key) {
+            Some(seen) => self.clock.now().saturating_duration_since(*seen) < window,
+            None => false,
+        }
+    }
+
+    /// Returns whether `src` is one of the addresses this host sends from.
+    fn is_own_source(&self, src: &SocketAddr) -> bool {
+        match normalize_source(src).ip() {
+            IpAddr::V4(ip) => {
+                self.config.interfaces.contains(&ip)
+                    || self.advertised_ipv4s().is_ok_and(|ips| ips.contains(&ip))
+            }
+            IpAddr::V6(ip) => self.advertised_ipv6s().contains(&ip),
+        }
+    }
+
+    /// Collects the records of local services owned by `qname` that answer a question
+    /// of type `qtype`: SRV and TXT for an instance name, A and AAAA for a host name, and
+    /// all of them for ANY. The addresses of the SRV target go in the additional records.
+    ///
+    /// # Returns
+    /// * `(Vec<DnsRecord>, Vec<DnsRecord>)` - The answers and additional records, both
+    ///   empty if no local service owns `qname`.
+    async fn records_for_name(
+        &self,
+        qname: &DnsName,
+        qtype: u16,
+        ttls_for: impl Fn(&ServiceRecord) -> RecordTtls,
+    ) -> (Vec<DnsRecord>, Vec<DnsRecord>) {
+        let local_ips = self.advertised_ipv4s().unwrap_or_default();
+        let local_ipv6s = self.advertised_ipv6s();
+        let cache_flush = UNIQUE_RECORDS;
+
+        let mut records = Vec::new();
+        for service in self.answerable_services().await {
+            let owns = |name: &str| DnsName::new(name).is_ok_and(|name| name == *qname);
+            if !owns(&service.id) && !owns(&service.origin) {
+                continue;
+            }
+            let pushed = records.len();
+            if let Err(err) = push_service_records(
+                &mut records,
+                &service,
+                cache_flush,
+                &local_ips,
+                &local_ipv6s,
+                ttls_for(&service),
+            ) {
+                warn!("(QUERY) Skipping service with invalid name: {}", err);
+                records.truncate(pushed);
+            }
+        }
+
+        let (answers, others): (Vec<DnsRecord>, Vec<DnsRecord>) = records.into_iter().partition(|record| {
+            record.name() == qname && (qtype == QTYPE_ANY || record.record_type() == qtype)
+        });
+        let targets: Vec<&DnsName> = answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::SRV { target, .. } => Some(target),
+                _ => None,
+            })
+            .collect();
+        let additionals = others
+            .iter()
+            .filter(|record| matches!(record, DnsRecord::A { .. } | DnsRecord::AAAA { .. }))
+            .filter(|record| targets.contains(&record.name()))
+            .cloned()
+            .collect();
+        (answers, additionals)
+    }
+
+    pub async fn process_query(&self, packet: &DnsPacket, src: &SocketAddr) {
+        self.note_observed_questions(packet, src);
+        if self.config.mode == ResponderMode::OneShot {
+            debug!("(QUERY) One-shot resolver, not answering query from {}", src);
+            return;
+        }
+        let src = &normalize_source(src);
+        // Queries from a port other than the mDNS port come from legacy resolvers that
+        // expect a unicast reply with capped TTLs (RFC 6762 §6.7).
+        let legacy_unicast = src.port() != self.config.port;
+        let cap_ttl = |ttl: u32| {
+            if legacy_unicast {
+                ttl.min(self.config.legacy_unicast_ttl_cap)
+            } else {
+                ttl
+            }
+        };
+        let ttls_for = |service: &ServiceRecord| self.record_ttls(service).map(cap_ttl);
+        // Multicast answers to this query wait for one shared random delay.
+        let mut delayed = false;
+
+        for question in &packet.questions {
+            debug!("(QUERY) Received question: {:?}", question.qname);
+
+            let asks_name = matches!(question.qtype, 1 | 16 | 28 | 33 | QTYPE_ANY);
+            if asks_name && question.qclass == 1 {
+                let (answers, additionals) =
+                    self.records_for_name(&question.qname, question.qtype, ttls_for).await;
+                if !answers.is_empty() {
+                    let mut response_packet = DnsPacket::with_header(DnsHeader::response());
+                    response_packet.answers = answers;
+                    response_packet.additionals = additionals;
+                    if legacy_unicast {
+                        echo_query(&mut response_packet, packet.id, question);
+                    }
+                    if question.qtype == QTYPE_ANY && !packet.authorities.is_empty() {
+                        // A probe for one of our names: defend it at once, by multicast and
+                        // regardless of the rate limit (RFC 6762 §8.1).
+                        debug!("(PROBE) Defending name {}", question.qname);
+                        match self.send_packet(&response_packet).await {
+                            Ok(()) => MetricsCounters::bump(&self.counters.queries_answered),
+                            Err(err) => error!("(PROBE) Failed to defend {}: {:?}", question.qname, err),
+                        }
                     } else {
-                        eprintln!("(QUERY) Source address is not IPv4, skipping A record.");
+                        // Unique records are answered without the shared-record delay.
+                        let mut no_delay = true;
+                        self.send_response(
+                            response_packet,
+                            legacy_unicast || question.unicast_response,
+                            src,
+                            &mut no_delay,
+                        )
+                        .await;
+                    }
+                    continue;
+                }
+                if question.qtype != QTYPE_ANY {
+                    continue;
+                }
+            }
+            let asks_ptr = question.qtype == 12 || question.qtype == QTYPE_ANY;
+
+            if asks_ptr && question.qclass == 1 && is_service_type_enumeration(&question.qname) {
+                let mut service_types: Vec<String> = self
+                    .answerable_services()
+                    .await
+                    .into_iter()
+                    .map(|service| service.service_type.trim_end_matches('.').to_string())
+                    .collect();
+                service_types.sort();
+                service_types.dedup();
+
+                let mut response_packet = DnsPacket::with_header(DnsHeader::response());
+                for service_type in service_types {
+                    match dns_name(&service_type) {
+                        Ok(ptr_name) => response_packet.answers.push(DnsRecord::PTR {
+                            name: question.qname.clone(),
+                            ttl: cap_ttl(self.config.ptr_ttl.unwrap_or(self.config.default_ttl)),
+                            ptr_name,
+                            cache_flush: false,
+                        }),
+                        Err(err) => warn!("(QUERY) Skipping invalid service type: {}", err),
+                    }
+                }
+                if response_packet.answers.is_empty() {
+                    debug!("(QUERY) No local service types to enumerate.");
+                    continue;
+                }
+                if legacy_unicast {
+                    echo_query(&mut response_packet, packet.id, question);
+                }
+                self.send_response(
+                    response_packet,
+                    legacy_unicast || question.unicast_response,
+                    src,
+                    &mut delayed,
+                )
+                .await;
+                continue;
+            }
+
+            if asks_ptr && question.qclass == 1 {
+                if let Some(address) = reverse_ipv4_name(&question.qname) {
+                    let hosts = self.reverse_hosts(address).await;
+                    if hosts.is_empty() {
+                        debug!("(QUERY) {} is not one of our addresses", address);
+                        continue;
+                    }
+                    let mut response_packet = DnsPacket::with_header(DnsHeader::response());
+                    for host in hosts {
+                        response_packet.answers.push(DnsRecord::PTR {
+                            name: question.qname.clone(),
+                            ttl: cap_ttl(self.config.address_ttl.unwrap_or(self.config.default_ttl)),
+                            ptr_name: host,
+                            cache_flush: true,
+                        });
                     }
+                    if legacy_unicast {
+                        echo_query(&mut response_packet, packet.id, question);
+                    }
+                    self.send_response(
+                        response_packet,
+                        legacy_unicast || question.unicast_response,
+                        src,
+                        &mut delayed,
+                    )
+                    .await;
+                    continue;
+                }
+            }
+
+            if asks_ptr && question.qclass == 1 {
+                let requested_service = question.qname.to_string();
+                if !self.answers_for_type(&requested_service) {
+                    debug!("(QUERY) '{}' is not an allowed service type, not answering", requested_service);
+                    continue;
                 }
+                let services = self.registry.list_services().await;
+                let matching_services: Vec<_> = services
+                    .into_iter()
+                    .filter(|s| {
+                        s.browse_names()
+                            .iter()
+                            .any(|name| name.eq_ignore_ascii_case(requested_service.trim_end_matches('.')))
+                    })
+                    .collect();
 
-                if let Err(err) = self.send_packet(&response_packet).await {
-                    eprintln!("(QUERY->RESP) Failed to send response: {:?}", err);
+                let mut cached_services = if self.config.answer_from_cache {
+                    self.cached_answers(&requested_service).await
                 } else {
-                    println!(
-                        "(QUERY->RESP) Sent response with {} answers.",
-                        response_packet.answers.len()
+                    Vec::new()
+                };
+
+                if matching_services.is_empty() && cached_services.is_empty() {
+                    debug!("(QUERY) No matching service for '{}'", requested_service);
+                    continue;
+                }
+
+                // Known-answer suppression (RFC 6762 §7.1): leave out instances the querier
+                // already lists with at least half their TTL left, together with the SRV,
+                // TXT, and address records that would accompany their PTR.
+                let known = |instance: &str, ttl: u32| {
+                    is_known_answer(&packet.answers, &question.qname, instance, ttl)
+                };
+                let matching_services: Vec<_> = matching_services
+                    .into_iter()
+                    .filter(|s| !known(&s.id, self.record_ttls(s).ptr))
+                    .collect();
+                cached_services.retain(|(s, remaining_ttl)| !known(&s.id, *remaining_ttl));
+                if matching_services.is_empty() && cached_services.is_empty() {
+                    debug!(
+                        "(QUERY) Querier already knows every answer for '{}'",
+                        requested_service
+                    );
+                    continue;
+                }
+
+                let mut response_packet = DnsPacket::with_header(DnsHeader::response());
+                let local_ips = self.advertised_ipv4s().unwrap_or_default();
+                let local_ipv6s = self.advertised_ipv6s();
+
+                if !matching_services.is_empty() && local_ips.is_empty() {
+                    warn!("(QUERY) No local IPv4 address available, skipping A records.");
+                }
+                let mut records = Vec::new();
+                for service in matching_services {
+                    debug!("(QUERY) Responding with service: {:?}", service);
+                    let answered = records.len();
+                    if let Err(err) = push_service_records(
+                        &mut records,
+                        &service,
+                        UNIQUE_RECORDS,
+                        &local_ips,
+                        &local_ipv6s,
+                        ttls_for(&service),
+                    ) {
+                        warn!("(QUERY) Skipping service with invalid name: {}", err);
+                        records.truncate(answered);
+                    }
+                }
+                // Only the PTR for the browsed type or subtype answers the question; the
+                // SRV, TXT, and address records it leads to go in the additional records
+                // (RFC 6763 §12.1).
+                for record in records {
+                    match record {
+                        DnsRecord::PTR { ref name, .. } if *name != question.qname => {}
+                        DnsRecord::PTR { .. } => response_packet.answers.push(record),
+                        _ => response_packet.additionals.push(record),
+                    }
+                }
+
+                // Answer for peers' services from the cache, with their remaining TTLs.
+                for (service, remaining_ttl) in cached_services {
+                    debug!("(QUERY) Responding from cache with service: {:?}", service);
+                    let names = (
+                        dns_name(&service.service_type),
+                        dns_name(&service.id),
+                        dns_name(&service.origin),
                     );
+                    let (service_type, instance, host) = match names {
+                        (Ok(service_type), Ok(instance), Ok(host)) => (service_type, instance, host),
+                        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+                            warn!("(QUERY) Skipping cached service with invalid name: {}", err);
+                            continue;
+                        }
+                    };
+
+                    response_packet.answers.push(DnsRecord::PTR {
+                        name: service_type,
+                        ttl: cap_ttl(remaining_ttl),
+                        ptr_name: instance.clone(),
+                        cache_flush: false,
+                    });
+                    response_packet.additionals.push(DnsRecord::SRV {
+                        name: instance,
+                        ttl: cap_ttl(remaining_ttl),
+                        priority: service.priority.unwrap_or(0),
+                        weight: service.weight.unwrap_or(0),
+                        port: service.port,
+                        target: host,
+                        cache_flush: false,
+                    });
                 }
+
+                if legacy_unicast {
+                    echo_query(&mut response_packet, packet.id, question);
+                }
+                self.send_response(
+                    response_packet,
+                    legacy_unicast || question.unicast_response,
+                    src,
+                    &mut delayed,
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Sends the response to a query: directly to `src` for legacy resolvers and queriers
+    /// that set the QU bit, otherwise multicast without the records sent too recently.
+    ///
+    /// The first multicast response to a query is held back by a random
+    /// `timing.response_delay()` so responders answering together do not collide
+    /// (RFC 6762 §6); `delayed` records that the wait already happened.
+    async fn send_response(
+        &self,
+        mut response_packet: DnsPacket,
+        unicast: bool,
+        src: &SocketAddr,
+        delayed: &mut bool,
+    ) {
+        let result = if unicast {
+            self.send_packet_to(&response_packet, *src).await
+        } else {
+            if !*delayed {
+                time::sleep(self.config.timing.response_delay()).await;
+                *delayed = true;
+            }
+            // Leave out records multicast too recently (RFC 6762 §6).
+            response_packet.answers.retain(|record| {
+                self.suppression
+                    .try_send(record, self.config.timing.multicast_rate_limit)
+            });
+            if response_packet.answers.is_empty() {
+                debug!("(QUERY->RESP) All answers suppressed by rate limit.");
+                return;
+            }
+            self.send_packet(&response_packet).await
+        };
+        if let Err(err) = result {
+            error!("(QUERY->RESP) Failed to send response: {:?}", err);
+        } else {
+            MetricsCounters::bump(&self.counters.queries_answered);
+            trace!(
+                "(QUERY->RESP) Sent response with {} answers.",
+                response_packet.answers.len()
+            );
+        }
+    }
+}
+
+/// Makes a response usable by a legacy resolver: it echoes the query's transaction id,
+/// repeats the question, and clears every cache-flush bit, which a resolver outside mDNS
+/// would read as part of the record class (RFC 6762 §6.7).
+fn echo_query(response_packet: &mut DnsPacket, id: u16, question: &crate::DnsQuestion) {
+    response_packet.id = id;
+    response_packet.questions = vec![question.clone()];
+    for record in response_packet
+        .answers
+        .iter_mut()
+        .chain(response_packet.additionals.iter_mut())
+    {
+        record.set_cache_flush(false);
+    }
+}
+
+/// Wraps `answers` in an authoritative response packet.
+fn response_packet(answers: Vec<DnsRecord>) -> DnsPacket {
+    let mut packet = DnsPacket::with_header(DnsHeader::response());
+    packet.answers = answers;
+    packet
+}
+
+/// Returns whether `name` is the DNS-SD service type enumeration name.
+fn is_service_type_enumeration(name: &DnsName) -> bool {
+    name.to_string().eq_ignore_ascii_case(SERVICE_TYPE_ENUMERATION)
+}
+
+/// Parses a reverse-lookup name such as `10.1.168.192.in-addr.arpa` into the address it
+/// stands for (`192.168.1.10`), or `None` if `name` is not one.
+fn reverse_ipv4_name(name: &DnsName) -> Option<Ipv4Addr> {
+    let name = name.to_string().to_ascii_lowercase();
+    let reversed = name.trim_end_matches('.').strip_suffix(".in-addr.arpa")?;
+    let octets: Vec<u8> = reversed
+        .split('.')
+        .map(|label| {
+            if label.is_empty() || !label.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            label.parse().ok()
+        })
+        .collect::<Option<_>>()?;
+    match octets[..] {
+        [d, c, b, a] => Some(Ipv4Addr::new(a, b, c, d)),
+        _ => None,
+    }
+}
+
+/// Returns true if the answers already contain an address record of the same type, host,
+/// and address as `record`.
+fn has_address_record(answers: &[DnsRecord], record: &DnsRecord) -> bool {
+    answers.iter().any(|existing| match (existing, record) {
+        (DnsRecord::A { name, ip, .. }, DnsRecord::A { name: host, ip: address, .. }) => {
+            name == host && ip == address
+        }
+        (DnsRecord::AAAA { name, ip, .. }, DnsRecord::AAAA { name: host, ip: address, .. }) => {
+            name == host && ip == address
+        }
+        _ => false,
+    })
+}
+
+/// Where an advertisement packet is multicast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SendTarget {
+    /// Every configured interface and address family, as `send_packet` does.
+    All,
+    /// The IPv4 group, out of one interface.
+    Interface(Ipv4Addr),
+    /// The IPv6 group only.
+    Ipv6,
+}
+
+/// The TTL (in seconds) of each kind of record advertising a local service.
+#[derive(Debug, Clone, Copy)]
+struct RecordTtls {
+    ptr: u32,
+    srv: u32,
+    txt: u32,
+    address: u32,
+}
+
+impl RecordTtls {
+    /// Gives every record the same TTL, e.g. 0 for a goodbye.
+    fn uniform(ttl: u32) -> Self {
+        Self { ptr: ttl, srv: ttl, txt: ttl, address: ttl }
+    }
+
+    /// Applies `f` to every TTL, e.g. to cap them for a legacy unicast reply.
+    fn map(self, f: impl Fn(u32) -> u32) -> Self {
+        Self { ptr: f(self.ptr), srv: f(self.srv), txt: f(self.txt), address: f(self.address) }
+    }
+
+    /// Returns the TTL of the record that expires first.
+    fn shortest(&self) -> u32 {
+        self.ptr.min(self.srv).min(self.txt).min(self.address)
+    }
+}
+
+/// Where a local service is in its re-announcement cycle (see `reannounce_expiring`).
+#[derive(Debug, Clone, Copy)]
+struct ReannounceCycle {
+    started: time::Instant,
+    next: usize, // Index of the next threshold to cross.
+}
+
+/// Appends the PTR (one per type and subtype), SRV, TXT (if any), A, and (when running
+/// dual-stack) AAAA records that advertise a local service.
+///
+/// An address record an earlier service already added for the same host and address is
+/// not repeated. A service with a `bind_addr` gets a single A record for it and no AAAA
+/// record.
+/// Each record carries the TTL `ttls` gives its type.
+///
+/// This is the one place local services are turned into records, shared by
+/// advertisements, announcements, goodbyes, and query responses.
+fn push_service_records(
+    answers: &mut Vec<DnsRecord>,
+    service: &ServiceRecord,
+    cache_flush: CacheFlush,
+    local_ips: &[Ipv4Addr],
+    local_ipv6s: &[Ipv6Addr],
+    ttls: RecordTtls,
+) -> Result<(), MdnsError> {
+    let instance = dns_name(&service.id)?;
+    let host = dns_name(&service.origin)?;
+    for browse_name in service.browse_names() {
+        answers.push(DnsRecord::PTR {
+            name: dns_name(&browse_name)?,
+            ttl: ttls.ptr,
+            ptr_name: instance.clone(),
+            cache_flush: cache_flush.ptr,
+        });
+    }
+
+    answers.push(DnsRecord::SRV {
+        name: instance.clone(),
+        ttl: ttls.srv,
+        priority: service.priority.unwrap_or(0),
+        weight: service.weight.unwrap_or(0),
+        port: service.port,
+        target: host.clone(),
+        cache_flush: cache_flush.srv,
+    });
+
+    if !service.txt.is_empty() {
+        answers.push(DnsRecord::TXT {
+            name: instance.clone(),
+            ttl: ttls.txt,
+            txt_data: encode_txt_strings(&service.txt),
+            cache_flush: cache_flush.txt,
+        });
+    }
+
+    // A service bound to one address advertises only that address.
+    let (local_ips, local_ipv6s) = match &service.bind_addr {
+        Some(bind_addr) => (std::slice::from_ref(bind_addr), &[][..]),
+        None => (local_ips, local_ipv6s),
+    };
+    let a_records = local_ips.iter().map(|local_ip| DnsRecord::A {
+        name: host.clone(),
+        ttl: ttls.address,
+        ip: local_ip.octets(),
+        cache_flush: cache_flush.a,
+    });
+    let aaaa_records = local_ipv6s.iter().map(|local_ipv6| DnsRecord::AAAA {
+        name: host.clone(),
+        ttl: ttls.address,
+        ip: local_ipv6.octets(),
+        cache_flush: cache_flush.a,
+    });
+    // Services sharing a host only need each of its address records once.
+    for record in a_records.chain(aaaa_records) {
+        if !has_address_record(answers, &record) {
+            answers.push(record);
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether `known_answers` holds a PTR from `qname` to `instance` whose TTL is at
+/// least half of `ttl`, the TTL the responder would answer with.
+fn is_known_answer(known_answers: &[DnsRecord], qname: &DnsName, instance: &str, ttl: u32) -> bool {
+    known_answers.iter().any(|record| match record {
+        DnsRecord::PTR { name, ttl: known_ttl, ptr_name, .. } => {
+            name == qname
+                && ptr_name.to_string().eq_ignore_ascii_case(instance.trim_end_matches('.'))
+                && u64::from(*known_ttl) * 2 >= u64::from(ttl)
+        }
+        _ => false,
+    })
+}
+
+/// Returns whether a receive error leaves the socket usable: an interrupted or spurious
+/// wakeup, or an ICMP error for an earlier send (port unreachable surfaces as
+/// `ConnectionRefused` on Linux and `ConnectionReset` on Windows).
+fn is_transient_recv_error(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::Interrupted
+            | std::io::ErrorKind::WouldBlock
+            | std::io::ErrorKind::TimedOut
+            | std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionReset
+    )
+}
+
+/// Converts an IPv4-mapped IPv6 source (`::ffff:a.b.c.d`) into its plain IPv4 form,
+/// leaving every other address unchanged.
+fn normalize_source(src: &SocketAddr) -> SocketAddr {
+    match src {
+        SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+            Some(ip) => SocketAddr::V4(SocketAddrV4::new(ip, v6.port())),
+            None => *src,
+        },
+        SocketAddr::V4(_) => *src,
+    }
+}
+
+/// Returns the interface index a link-local `ip` is reachable through: the scope id of
+/// the IPv6 packet that carried it. Other addresses need no scope.
+fn link_local_scope(ip: &Ipv6Addr, src: &SocketAddr) -> Option<u32> {
+    match src {
+        SocketAddr::V6(src) if ip.is_unicast_link_local() && src.scope_id() != 0 => {
+            Some(src.scope_id())
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the record describes one of the given local services.
+fn owns_record(local_services: &[ServiceRecord], record: &DnsRecord) -> bool {
+    let matches = |candidate: &str, name: &DnsName| {
+        DnsName::new(candidate).map(|owned| owned == *name).unwrap_or(false)
+    };
+    local_services.iter().any(|service| match record {
+        DnsRecord::PTR { ptr_name, .. } => matches(&service.id, ptr_name),
+        DnsRecord::SRV { name, .. } | DnsRecord::TXT { name, .. } => matches(&service.id, name),
+        DnsRecord::A { name, .. } | DnsRecord::AAAA { name, .. } => {
+            matches(&service.origin, name)
+        }
+        DnsRecord::NSEC { name, .. } => matches(&service.id, name) || matches(&service.origin, name),
+        DnsRecord::OPT { .. } => false,
+    })
+}
+
+/// Returns whether `ip` belongs in AAAA records: a global or unique-local unicast address,
+/// or a link-local one when `link_local` is set.
+fn advertisable_ipv6(ip: &Ipv6Addr, link_local: bool) -> bool {
+    if ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() {
+        return false;
+    }
+    link_local || !ip.is_unicast_link_local()
+}
+
+fn get_local_ipv6() -> Option<Ipv6Addr> {
+    use std::net::{IpAddr, UdpSocket};
+
+    let socket = UdpSocket::bind("[::]:0").ok()?;
+    socket.connect("[2001:4860:4860::8888]:80").ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V6(ip) => Some(ip),
+        IpAddr::V4(_) => None,
+    }
+}
+
+/// Key under which a probed name is tracked; mDNS names compare case-insensitively.
+fn probe_key(name: &DnsName) -> String {
+    name.to_string().to_ascii_lowercase()
+}
+
+/// Returns the `probe_key` of a name given as a string, or `None` if it is not a valid name.
+fn probe_key_of(name: &str) -> Option<String> {
+    DnsName::new(name).ok().map(|name| probe_key(&name))
+}
+
+/// Returns whether `name` is the host of one of the given local services, ignoring case.
+fn owns_host(local_services: &[ServiceRecord], name: &DnsName) -> bool {
+    let key = probe_key(name);
+    local_services
+        .iter()
+        .any(|service| probe_key_of(&service.origin).as_ref() == Some(&key))
+}
+
+/// Returns the `bind_addr` of every local service whose host is `name`.
+fn bound_addresses(local_services: &[ServiceRecord], name: &DnsName) -> Vec<Ipv4Addr> {
+    let key = probe_key(name);
+    local_services
+        .iter()
+        .filter(|service| probe_key_of(&service.origin).as_ref() == Some(&key))
+        .filter_map(|service| service.bind_addr)
+        .collect()
+}
+
+/// Formats addresses as a comma-separated list for logs and events.
+fn join_addresses<A: ToString>(addresses: &[A]) -> String {
+    addresses.iter().map(A::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Spawns `task`, dropping it as soon as `shutdown` is cancelled.
+fn spawn_until_cancelled<F>(shutdown: &CancellationToken, task: F)
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    let shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = shutdown.cancelled() => {}
+            _ = task => {}
+        }
+    });
+}
+
+/// Converts `name` to a `DnsName`, reporting names that cannot be encoded as `InvalidName`.
+fn dns_name(name: &str) -> Result<DnsName, MdnsError> {
+    DnsName::new(name).map_err(|e| MdnsError::InvalidName(format!("'{}': {}", name, e)))
+}
+
+/// Checks that a local service has an origin host and valid names before it is registered.
+fn validate_local_service(service: &ServiceRecord) -> Result<(), MdnsError> {
+    if service.origin.is_empty() {
+        return Err(MdnsError::Generic(format!("Service '{}' has no origin host", service.id)));
+    }
+    validate_service_names(service)
+}
+
+/// Checks that every name a service is advertised under can be encoded, so invalid
+/// services are rejected at registration instead of failing while building packets.
+fn validate_service_names(service: &ServiceRecord) -> Result<(), MdnsError> {
+    dns_name(&service.id)?;
+    dns_name(&service.origin)?;
+    for browse_name in service.browse_names() {
+        dns_name(&browse_name)?;
+    }
+    Ok(())
+}
+
+/// Completes a local service's type to the full `_service._proto.local` form, adding the
+/// protocol label and the domain where they are omitted, so `_http` becomes
+/// `_http._tcp.local`. A service without a `protocol` takes the one its type names, or
+/// TCP; one with a `protocol` fails if its type names the other.
+fn normalize_service_type(service: &mut ServiceRecord) -> Result<(), MdnsError> {
+    let mut labels: Vec<&str> = service.service_type.trim_end_matches('.').split('.').collect();
+    if !labels[0].starts_with('_') || labels[0].len() < 2 {
+        return Err(MdnsError::InvalidName(format!(
+            "Service type '{}' must start with a '_service' label",
+            service.service_type
+        )));
+    }
+    match (labels.get(1).copied().and_then(Protocol::from_label), service.protocol) {
+        (Some(named), Some(protocol)) if named != protocol => {
+            return Err(MdnsError::InvalidName(format!(
+                "Service type '{}' names {} but the service uses {}",
+                service.service_type,
+                labels[1],
+                protocol.label()
+            )))
+        }
+        (Some(named), _) => service.protocol = Some(named),
+        (None, protocol) => {
+            let protocol = protocol.unwrap_or_default();
+            labels.insert(1, protocol.label());
+            service.protocol = Some(protocol);
+        }
+    }
+    if labels.len() == 2 {
+        labels.push("local");
+    }
+    let normalized = labels.join(".");
+    if normalized != service.service_type {
+        debug!("(REGISTER) Normalized service type {} to {}", service.service_type, normalized);
+        service.service_type = normalized;
+    }
+    Ok(())
+}
+
+/// Encodes key/value pairs as the length-prefixed `key=value` strings of a TXT RDATA.
+fn encode_txt_strings(entries: &[(String, String)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (key, value) in entries {
+        let entry = format!("{}={}", key, value);
+        // A TXT character-string is limited to 255 bytes.
+        let bytes = &entry.as_bytes()[..entry.len().min(255)];
+        data.push(bytes.len() as u8);
+        data.extend_from_slice(bytes);
+    }
+    data
+}
+
+/// Decodes the length-prefixed character strings of a TXT record's RDATA.
+fn decode_txt_strings(data: &[u8]) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let len = data[offset] as usize;
+        let end = (offset + 1 + len).min(data.len());
+        if len > 0 {
+            entries.push(String::from_utf8_lossy(&data[offset + 1..end]).into_owned());
+        }
+        offset = end;
+    }
+    entries
+}
+
+/// Splits decoded TXT strings into key/value pairs. A string without `=` is a boolean
+/// attribute (RFC 6763 §6.4) and maps to an empty value.
+fn txt_pairs(entries: &[String]) -> Vec<(String, String)> {
+    entries
+        .iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (entry.clone(), String::new()),
+        })
+        .collect()
+}
+
+/// Runs `setup` up to `config.socket_setup_attempts` times, waiting
+/// `config.socket_setup_backoff` (doubled after each retry) between attempts. Only
+/// transient errors are retried; see `is_transient_setup_error`.
+async fn retry_socket_setup<T, F, Fut>(config: &MdnsConfig, mut setup: F) -> Result<T, MdnsError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, MdnsError>>,
+{
+    let attempts = config.socket_setup_attempts.max(1);
+    let mut backoff = config.socket_setup_backoff;
+    let mut attempt = 1;
+    loop {
+        match setup().await {
+            Err(err) if attempt < attempts && is_transient_setup_error(&err) => {
+                warn!(
+                    "(INIT) Socket setup attempt {}/{} failed: {}; retrying in {:?}",
+                    attempt, attempts, err, backoff
+                );
+                time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+                attempt += 1;
             }
+            result => return result,
         }
     }
 }
 
+/// Returns a `map_err` adapter that wraps an I/O error in `MdnsError::SocketSetup`.
+fn setup_step(step: &str) -> impl FnOnce(std::io::Error) -> MdnsError + '_ {
+    move |error| MdnsError::SocketSetup {
+        step: step.to_string(),
+        error,
+    }
+}
+
+/// Whether a socket setup error may clear by itself, e.g. while the network is still
+/// coming up at boot or a previous instance is releasing the port.
+fn is_transient_setup_error(err: &MdnsError) -> bool {
+    /// `ENODEV`: joining a group before any interface is up.
+    const NO_SUCH_DEVICE: i32 = 19;
+
+    let MdnsError::SocketSetup { error, .. } = err else {
+        return false;
+    };
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::AddrInUse
+            | std::io::ErrorKind::AddrNotAvailable
+            | std::io::ErrorKind::NetworkDown
+            | std::io::ErrorKind::NetworkUnreachable
+            | std::io::ErrorKind::HostUnreachable
+    ) || (cfg!(unix) && error.raw_os_error() == Some(NO_SUCH_DEVICE))
+}
+
+/// Returns the subtype a PTR owner name selects, e.g. `_printer` for
+/// `_printer._sub._http._tcp.local`, if it is a subtype browse name of `service_type`.
+fn subtype_of(name: &DnsName, service_type: &str) -> Option<String> {
+    let name = name.to_string();
+    let (subtype, parent) = name.split_once("._sub.")?;
+    (parent.eq_ignore_ascii_case(service_type) && !subtype.is_empty()).then(|| subtype.to_string())
+}
+
+/// Chooses the interfaces the multicast group is joined on: the single `interface` when
+/// set, else the configured `interfaces`, else every non-loopback IPv4 interface.
+fn multicast_interfaces(config: &MdnsConfig) -> Vec<Ipv4Addr> {
+    if let Some(interface) = config.interface {
+        vec![interface]
+    } else if !config.interfaces.is_empty() {
+        config.interfaces.clone()
+    } else {
+        local_ipv4_interfaces()
+    }
+}
+
 fn get_local_ipv4() -> Option<Ipv4Addr> {
     use std::net::{IpAddr, UdpSocket};
 
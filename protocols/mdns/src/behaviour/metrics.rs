@@ -0,0 +1,39 @@
+// protocols\mdns\src\behaviour\metrics.rs
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A snapshot of the service's traffic and discovery counters, from `MdnsService::metrics`.
+///
+/// Counters only grow over the life of the service; `nodes_known` is the current count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MdnsMetrics {
+    /// Datagrams sent, counting each interface and address family separately.
+    pub packets_sent: u64,
+    /// Datagrams received from the transports.
+    pub packets_received: u64,
+    /// Received packets dropped because they could not be parsed or failed validation.
+    pub parse_failures: u64,
+    /// Responses sent to queries, including probe defences.
+    pub queries_answered: u64,
+    /// Received packets dropped because the processing queue was full.
+    pub dropped_packets: u64,
+    /// Received packets dropped because the registry stayed locked too long.
+    pub contended_packets: u64,
+    /// Peer hosts with a known IPv4 or IPv6 address.
+    pub nodes_known: usize,
+}
+
+/// The live counters behind `MdnsMetrics`.
+#[derive(Debug, Default)]
+pub(crate) struct MetricsCounters {
+    pub(crate) packets_sent: AtomicU64,
+    pub(crate) packets_received: AtomicU64,
+    pub(crate) parse_failures: AtomicU64,
+    pub(crate) queries_answered: AtomicU64,
+}
+
+impl MetricsCounters {
+    /// Adds one to `counter`.
+    pub(crate) fn bump(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
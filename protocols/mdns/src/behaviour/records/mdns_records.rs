@@ -1,5 +1,9 @@
+use rand::Rng;
 use serde::{Serialize, Deserialize};
-use std::time::{SystemTime, Duration};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV6};
+use std::time::SystemTime;
+use tokio::time::Instant;
 use registry::Record;
 
 
@@ -12,6 +16,160 @@ pub struct ServiceRecord {
     pub origin: String,        // Origin of the service (e.g., "local" or a peer's ID).
     pub priority: Option<u16>, // Optional SRV priority.
     pub weight: Option<u16>,   // Optional SRV weight.
+    #[serde(default)]
+    pub cache_flush: CacheFlush, // Cache-flush bit to set on each advertised record type.
+    #[serde(default)]
+    pub txt: Vec<(String, String)>, // TXT key/value metadata, e.g. `("path", "/print")`.
+    #[serde(default)]
+    pub subtypes: Vec<String>,      // DNS-SD subtypes, e.g. `_printer`.
+    #[serde(default)]
+    pub protocol: Option<Protocol>, // Transport; `None` takes the type's `_tcp`/`_udp` label.
+    #[serde(default)]
+    pub bind_addr: Option<Ipv4Addr>, // The only address advertised for the host, if set.
+}
+
+/// The transport protocol of a DNS-SD service, named by the `_tcp` or `_udp` label of
+/// its service type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Protocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl Protocol {
+    /// Returns the protocol label used in service types, `_tcp` or `_udp`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Protocol::Tcp => "_tcp",
+            Protocol::Udp => "_udp",
+        }
+    }
+
+    /// Returns the protocol named by a label, ignoring case, e.g. `Udp` for `_UDP`.
+    pub fn from_label(label: &str) -> Option<Protocol> {
+        [Protocol::Tcp, Protocol::Udp]
+            .into_iter()
+            .find(|protocol| label.eq_ignore_ascii_case(protocol.label()))
+    }
+
+    /// Returns the protocol a service type such as `_ipp._tcp.local` names, or `None` if
+    /// its second label is not a protocol label.
+    pub fn of_service_type(service_type: &str) -> Option<Protocol> {
+        service_type.split('.').nth(1).and_then(Protocol::from_label)
+    }
+}
+
+/// Selects which of a service's advertised records carry the cache-flush bit.
+///
+/// By default no record sets the bit. Setting a flag marks that record type as unique,
+/// so receivers replace their cached copies instead of adding to them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheFlush {
+    pub ptr: bool,
+    pub srv: bool,
+    pub txt: bool,
+    pub a: bool,
+}
+
+impl ServiceRecord {
+    /// Returns every name a browser may query to find this service: the base service
+    /// type followed by one `<subtype>._sub.<service_type>` name per subtype.
+    pub fn browse_names(&self) -> Vec<String> {
+        std::iter::once(self.service_type.clone())
+            .chain(
+                self.subtypes
+                    .iter()
+                    .map(|subtype| format!("{}._sub.{}", subtype, self.service_type)),
+            )
+            .collect()
+    }
+}
+
+impl ServiceRecord {
+    /// Starts building a service record for the instance `id` of `service_type`.
+    ///
+    /// The record has no TTL, priority and weight `0`, no TXT entries, and no origin
+    /// host; set one with `origin`, or leave it empty to register the service under
+    /// `MdnsService::hostname`. The protocol is
+    /// the one `service_type` names; if it names none, registration picks TCP.
+    pub fn builder(id: &str, service_type: &str, port: u16) -> ServiceRecordBuilder {
+        ServiceRecordBuilder {
+            record: ServiceRecord {
+                id: id.to_string(),
+                service_type: service_type.to_string(),
+                port,
+                ttl: None,
+                origin: String::new(),
+                priority: Some(0),
+                weight: Some(0),
+                cache_flush: CacheFlush::default(),
+                txt: Vec::new(),
+                subtypes: Vec::new(),
+                protocol: Protocol::of_service_type(service_type),
+                bind_addr: None,
+            },
+        }
+    }
+}
+
+/// A builder for `ServiceRecord`, created by `ServiceRecord::builder`.
+#[derive(Debug, Clone)]
+pub struct ServiceRecordBuilder {
+    record: ServiceRecord,
+}
+
+impl ServiceRecordBuilder {
+    /// Sets the time-to-live of the advertised records, in seconds.
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.record.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the SRV priority.
+    pub fn priority(mut self, priority: u16) -> Self {
+        self.record.priority = Some(priority);
+        self
+    }
+
+    /// Sets the SRV weight.
+    pub fn weight(mut self, weight: u16) -> Self {
+        self.record.weight = Some(weight);
+        self
+    }
+
+    /// Sets the transport protocol. Registration fails if the service type names the
+    /// other protocol; a service type without a protocol label gets this one.
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.record.protocol = Some(protocol);
+        self
+    }
+
+    /// Advertises `addr` as the only address of the service's host, instead of the
+    /// detected interface addresses, e.g. to keep a service on the management network.
+    pub fn bind_addr(mut self, addr: Ipv4Addr) -> Self {
+        self.record.bind_addr = Some(addr);
+        self
+    }
+
+    /// Sets the host the service runs on, e.g. `office-host.local`.
+    pub fn origin(mut self, origin: &str) -> Self {
+        self.record.origin = origin.to_string();
+        self
+    }
+
+    /// Adds a TXT entry, replacing any earlier value for the same key.
+    pub fn txt(mut self, key: &str, value: &str) -> Self {
+        self.record.txt.retain(|(existing, _)| existing != key);
+        self.record.txt.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Builds the service record, with its TXT entries sorted by key.
+    pub fn build(mut self) -> ServiceRecord {
+        self.record.txt.sort();
+        self.record
+    }
 }
 
 impl Record for ServiceRecord {
@@ -19,16 +177,41 @@ impl Record for ServiceRecord {
         self.id.clone()
     }
 
+    /// Always `None`: the store never drops services by itself. Discovered services
+    /// expire on the registry's `Clock` (see `MdnsRegistry::sweep_expired`), and local
+    /// services stay until they are unregistered.
     fn expires_at(&self) -> Option<SystemTime> {
-        self.ttl.map(|ttl_secs| SystemTime::now() + Duration::from_secs(ttl_secs.into()))
+        None
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NodeRecord {
     pub id: String,         // Unique node ID
     pub ip_address: String, // IP address of the node
     pub ttl: Option<u32>,   // Time-to-live for the node record
+    #[serde(default)]
+    pub source: Option<SocketAddr>, // Address of the peer whose response carried the record.
+    #[serde(skip)]
+    pub last_seen: Option<Instant>, // When the record was last added or refreshed.
+    #[serde(default)]
+    pub scope_id: Option<u32>, // Interface a link-local IPv6 address was heard on.
+}
+
+impl NodeRecord {
+    /// Returns the socket address for reaching the node on `port`, carrying the scope id
+    /// of a link-local IPv6 address, or `None` if `ip_address` does not parse.
+    pub fn socket_addr(&self, port: u16) -> Option<SocketAddr> {
+        match self.ip_address.parse::<IpAddr>().ok()? {
+            IpAddr::V6(ip) => Some(SocketAddr::V6(SocketAddrV6::new(
+                ip,
+                port,
+                0,
+                self.scope_id.unwrap_or(0),
+            ))),
+            ip => Some(SocketAddr::new(ip, port)),
+        }
+    }
 }
 
 impl Record for NodeRecord {
@@ -36,8 +219,112 @@ impl Record for NodeRecord {
         self.id.clone()
     }
 
+    /// Always `None`: the registry expires records on its own `Clock` rather than the
+    /// store doing so on the wall clock.
+    fn expires_at(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxtRecord {
+    pub id: String,           // Instance name the TXT data belongs to.
+    pub entries: Vec<String>, // Decoded TXT strings, e.g. `path=/print`.
+    pub ttl: Option<u32>,     // Time-to-live for the TXT record
+}
+
+impl Record for TxtRecord {
+    fn identifier(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Always `None`: the registry expires records on its own `Clock` rather than the
+    /// store doing so on the wall clock.
     fn expires_at(&self) -> Option<SystemTime> {
-        self.ttl
-            .map(|ttl_secs| SystemTime::now() + Duration::from_secs(ttl_secs.into()))
+        None
+    }
+}
+
+/// The complete view of a discovered service instance, assembled from its PTR, SRV,
+/// TXT, and A records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedService {
+    pub instance: String,       // Full instance name, e.g. `Printer._ipp._tcp.local`.
+    pub service_type: String,   // e.g. `_ipp._tcp.local`
+    pub host: String,           // SRV target host.
+    pub port: u16,              // SRV port.
+    pub priority: u16,          // SRV priority.
+    pub weight: u16,            // SRV weight.
+    pub txt: Vec<String>,       // TXT entries, empty if none were received.
+    pub addresses: Vec<String>, // Addresses of the target host, empty if unresolved.
+}
+
+/// Everything needed to connect to one service instance, as returned by
+/// `MdnsService::resolve_instance`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedInstance {
+    pub instance: String,             // Full instance name, e.g. `Printer._ipp._tcp.local`.
+    pub host: String,                 // SRV target host.
+    pub port: u16,                    // SRV port.
+    pub priority: u16,                // SRV priority.
+    pub weight: u16,                  // SRV weight.
+    pub addresses: Vec<String>,       // IPv4 and IPv6 addresses of the target host.
+    pub txt: HashMap<String, String>, // TXT metadata, empty if none was received.
+}
+
+impl ResolvedInstance {
+    /// Picks the instance a client should connect to, per RFC 2782: one with the lowest
+    /// SRV priority, chosen at random in proportion to its weight.
+    ///
+    /// Returns `None` if `instances` is empty.
+    pub fn pick(instances: &[ResolvedInstance]) -> Option<&ResolvedInstance> {
+        let lowest = instances.iter().map(|instance| instance.priority).min()?;
+        let mut candidates: Vec<&ResolvedInstance> = instances
+            .iter()
+            .filter(|instance| instance.priority == lowest)
+            .collect();
+        candidates.sort_by_key(|instance| instance.weight != 0);
+        let index = weighted_index(&candidates, |instance| instance.weight, &mut rand::thread_rng());
+        Some(candidates[index])
+    }
+}
+
+/// Orders SRV targets the way RFC 2782 says to try them: by ascending priority, and within
+/// one priority by repeated random selection weighted by SRV weight.
+///
+/// Entries of equal priority and weight keep their relative order when every weight is 0.
+pub(crate) fn srv_order<T>(mut items: Vec<T>, srv: impl Fn(&T) -> (u16, u16)) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    // The RFC places zero-weight entries first so they keep a small chance of selection.
+    items.sort_by_key(|item| {
+        let (priority, weight) = srv(item);
+        (priority, weight != 0)
+    });
+
+    let mut ordered = Vec::with_capacity(items.len());
+    while !items.is_empty() {
+        let priority = srv(&items[0]).0;
+        let end = items.iter().take_while(|item| srv(item).0 == priority).count();
+        let mut group: Vec<T> = items.drain(..end).collect();
+        while !group.is_empty() {
+            let index = weighted_index(&group, |item| srv(item).1, &mut rng);
+            ordered.push(group.remove(index));
+        }
     }
+    ordered
+}
+
+/// Selects an index of `items` at random with probability proportional to `weight`,
+/// following the running-sum selection of RFC 2782. `items` must not be empty.
+fn weighted_index<T>(items: &[T], weight: impl Fn(&T) -> u16, rng: &mut impl Rng) -> usize {
+    let total: u32 = items.iter().map(|item| u32::from(weight(item))).sum();
+    let target = rng.gen_range(0..=total);
+    let mut running = 0;
+    items
+        .iter()
+        .position(|item| {
+            running += u32::from(weight(item));
+            running >= target
+        })
+        .unwrap_or(0)
 }
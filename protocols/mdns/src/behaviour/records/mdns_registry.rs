@@ -1,22 +1,99 @@
 // protocols\mdns\src\behaviour\records\mdns_registry.rs
-use crate::behaviour::records::mdns_records::{NodeRecord, ServiceRecord};
-use registry::{InMemoryRegistry, Registry, RegistryError};
+use crate::behaviour::clock::{Clock, TokioClock};
+use crate::behaviour::records::mdns_records::{
+    NodeRecord, ResolvedInstance, ResolvedService, ServiceRecord, TxtRecord,
+};
+use crate::behaviour::records::registry_store::{MemoryStore, RegistryStore};
+use registry::{Registry, RegistryError};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Lifetime (in seconds) assumed for peer records that arrive without a TTL.
+const DEFAULT_RECORD_TTL: u32 = 120;
+
+/// Maximum number of records each table of the registry holds.
+const REGISTRY_CAPACITY: usize = 50;
 
 /// Represents the mDNS registry for managing service and node records.
-pub struct MdnsRegistry {
-    pub service_registry: InMemoryRegistry<ServiceRecord>,
-    pub node_registry: InMemoryRegistry<NodeRecord>,
+///
+/// Local services (the ones this host advertises) and services discovered from peers
+/// are kept in separate registries so that peers' records are never re-advertised.
+/// The records live in tables opened from a `RegistryStore`, in memory by default.
+pub struct MdnsRegistry<S: RegistryStore = MemoryStore> {
+    pub service_registry: S::Table<ServiceRecord>,
+    pub discovered_service_registry: S::Table<ServiceRecord>,
+    pub node_registry: S::Table<NodeRecord>,
+    pub node_v6_registry: S::Table<NodeRecord>,
+    pub txt_registry: S::Table<TxtRecord>,
+    discovered_expiry: RwLock<HashMap<String, Instant>>, // When each discovered service expires.
+    txt_expiry: RwLock<HashMap<String, Instant>>, // When each instance's TXT data expires.
+    node_ids: RwLock<HashSet<String>>, // Ids of the stored IPv4 nodes, for counting without awaiting.
+    node_v6_ids: RwLock<HashSet<String>>, // Ids of the stored IPv6 nodes.
+    service_types: RwLock<HashMap<String, Instant>>, // Service types enumerated by peers, with their expiry.
+    unresolved: RwLock<HashSet<String>>, // Discovered services last seen with an SRV target that has no address.
+    discovered_subtypes: RwLock<HashMap<String, Vec<String>>>, // Subtypes peers advertised for each instance.
+    record_types: RwLock<HashMap<String, (Vec<u16>, Instant)>>, // Types a name has per its NSEC record, with expiry.
+    update_lock: AsyncRwLock<()>, // Held exclusively by batch updates, shared by packet processing.
+    node_lock: AsyncMutex<()>, // Serializes node writes, TTL sweeps, and clears so none interleaves with another.
+    clock: Arc<dyn Clock>, // Time source for expiry bookkeeping.
 }
 
 impl MdnsRegistry {
     /// Creates a new `MdnsRegistry` with default configurations.
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(TokioClock))
+    }
+
+    /// Creates a new `MdnsRegistry` that reads the time for TTL expiry from `clock`.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self::with_store(MemoryStore, clock)
+    }
+}
+
+impl<S: RegistryStore> MdnsRegistry<S> {
+    /// Creates a new `MdnsRegistry` keeping its records in tables opened from `store`,
+    /// reading the time for TTL expiry from `clock`.
+    pub fn with_store(store: S, clock: Arc<dyn Clock>) -> Self {
         Self {
-            service_registry: InMemoryRegistry::new(50),
-            node_registry: InMemoryRegistry::new(50),
+            service_registry: store.open("services", REGISTRY_CAPACITY),
+            discovered_service_registry: store.open("discovered_services", REGISTRY_CAPACITY),
+            node_registry: store.open("nodes", REGISTRY_CAPACITY),
+            node_v6_registry: store.open("nodes_v6", REGISTRY_CAPACITY),
+            txt_registry: store.open("txt", REGISTRY_CAPACITY),
+            discovered_expiry: RwLock::new(HashMap::new()),
+            txt_expiry: RwLock::new(HashMap::new()),
+            node_ids: RwLock::new(HashSet::new()),
+            node_v6_ids: RwLock::new(HashSet::new()),
+            service_types: RwLock::new(HashMap::new()),
+            unresolved: RwLock::new(HashSet::new()),
+            discovered_subtypes: RwLock::new(HashMap::new()),
+            record_types: RwLock::new(HashMap::new()),
+            update_lock: AsyncRwLock::new(()),
+            node_lock: AsyncMutex::new(()),
+            clock,
         }
     }
 
+    /// Returns the clock the registry reads expiry times from.
+    pub(crate) fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Locks the registry for a batch of updates that packet processing must not observe
+    /// half-applied. Received packets wait for the guard to be dropped, up to the
+    /// configured `registry_lock_timeout`.
+    pub async fn lock_for_update(&self) -> RwLockWriteGuard<'_, ()> {
+        self.update_lock.write().await
+    }
+
+    /// Acquires the shared side of the update lock while a received packet is processed.
+    pub(crate) async fn lock_for_processing(&self) -> RwLockReadGuard<'_, ()> {
+        self.update_lock.read().await
+    }
+
     /// Adds a service record to the service registry.
     pub async fn add_service(&self, record: ServiceRecord) -> Result<(), RegistryError> {
         self.service_registry.add(record).await
@@ -27,31 +104,561 @@ impl MdnsRegistry {
         self.service_registry.get(id).await
     }
 
+    /// Removes a local service record by its ID.
+    pub async fn remove_service(&self, id: &str) -> Result<(), RegistryError> {
+        self.service_registry.remove(id).await
+    }
+
     /// Lists all service records in the registry.
     pub async fn list_services(&self) -> Vec<ServiceRecord> {
         self.service_registry.list().await
     }
 
-    /// Adds a node record to the node registry.
+    /// Adds a service record learned from a peer to the discovered service registry.
+    pub async fn add_discovered_service(&self, record: ServiceRecord) -> Result<(), RegistryError> {
+        {
+            let lifetime = record.ttl.unwrap_or(DEFAULT_RECORD_TTL);
+            let expires_at = self.clock.now() + Duration::from_secs(lifetime.into());
+            self.discovered_expiry
+                .write()
+                .unwrap()
+                .insert(record.id.clone(), expires_at);
+        }
+        self.discovered_service_registry.add(record).await
+    }
+
+    /// Removes a service learned from a peer, e.g. after the peer sent a goodbye.
+    pub async fn remove_discovered_service(&self, id: &str) -> Result<(), RegistryError> {
+        self.discovered_expiry.write().unwrap().remove(id);
+        self.unresolved.write().unwrap().remove(id);
+        self.discovered_subtypes.write().unwrap().remove(id);
+        self.discovered_service_registry.remove(id).await
+    }
+
+    /// Records that a peer advertised the instance `id` under `subtype` (e.g. `_printer`),
+    /// adding the subtype to the discovered service if it is already cached.
+    pub async fn add_discovered_subtype(&self, id: &str, subtype: &str) -> Result<(), RegistryError> {
+        {
+            let mut subtypes = self.discovered_subtypes.write().unwrap();
+            let known = subtypes.entry(id.to_string()).or_default();
+            if known.iter().any(|existing| existing == subtype) {
+                return Ok(());
+            }
+            known.push(subtype.to_string());
+        }
+        match self.discovered_service_registry.get(id).await {
+            Some(mut service) => {
+                service.subtypes = self.discovered_subtypes(id);
+                self.discovered_service_registry.add(service).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the subtypes peers advertised for the instance `id`, in the order seen.
+    pub fn discovered_subtypes(&self, id: &str) -> Vec<String> {
+        self.discovered_subtypes
+            .read()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns how many whole seconds a discovered service has left before it expires.
+    ///
+    /// # Returns
+    /// * `Some(u32)` - The remaining TTL, `0` once the record has expired.
+    /// * `None` - If the service is unknown. Services cached without a TTL count down
+    ///   from a default of 120 seconds.
+    pub fn discovered_remaining_ttl(&self, id: &str) -> Option<u32> {
+        let expires_at = *self.discovered_expiry.read().unwrap().get(id)?;
+        let remaining = expires_at
+            .saturating_duration_since(self.clock.now())
+            .as_secs();
+        Some(remaining.min(u32::MAX as u64) as u32)
+    }
+
+    /// Retrieves a service discovered from a peer by its instance name, unless it has
+    /// expired.
+    pub async fn get_discovered_service(&self, id: &str) -> Option<ServiceRecord> {
+        let now = self.clock.now();
+        self.discovered_service_registry
+            .get(id)
+            .await
+            .filter(|service| !self.discovered_expired(&service.id, now))
+    }
+
+    /// Lists all unexpired services discovered from peers.
+    pub async fn list_discovered_services(&self) -> Vec<ServiceRecord> {
+        let now = self.clock.now();
+        let mut services = self.discovered_service_registry.list().await;
+        services.retain(|service| !self.discovered_expired(&service.id, now));
+        services
+    }
+
+    /// Returns whether the discovered service `id` is past its expiry at `now`.
+    fn discovered_expired(&self, id: &str, now: Instant) -> bool {
+        self.discovered_expiry
+            .read()
+            .unwrap()
+            .get(id)
+            .is_some_and(|expires_at| *expires_at <= now)
+    }
+
+    /// Adds a node record to the node registry. A node already known under the same id is
+    /// replaced, and its TTL counts down again from the record's `last_seen`, or from now
+    /// if it has none.
     pub async fn add_node(&self, record: NodeRecord) -> Result<(), RegistryError> {
+        let _guard = self.node_lock.lock().await;
+        self.insert_node(record).await
+    }
+
+    /// Replaces every IPv4 node named `name` (compared case-insensitively, as mDNS names
+    /// are) with `record`, for an A record with the cache-flush bit set (RFC 6762 §10.2).
+    ///
+    /// The removal and insertion happen under one lock, so concurrent flushes of the same
+    /// name never leave more than one node behind.
+    ///
+    /// # Returns
+    /// * `Ok(Option<NodeRecord>)` - The node that was replaced, if any.
+    /// * `Err(RegistryError)` - If the new node could not be stored.
+    pub async fn flush_and_set(
+        &self,
+        name: &str,
+        record: NodeRecord,
+    ) -> Result<Option<NodeRecord>, RegistryError> {
+        let _guard = self.node_lock.lock().await;
+        let mut previous = None;
+        for node in self.node_registry.list().await {
+            if node.id.eq_ignore_ascii_case(name) {
+                self.node_ids.write().unwrap().remove(&node.id);
+                let _ = self.node_registry.remove(&node.id).await;
+                previous.get_or_insert(node);
+            }
+        }
+        self.insert_node(record).await?;
+        Ok(previous)
+    }
+
+    /// Stores a node and restarts its TTL; callers hold `node_lock`.
+    async fn insert_node(&self, mut record: NodeRecord) -> Result<(), RegistryError> {
+        record.last_seen.get_or_insert_with(|| self.clock.now());
+        self.node_ids.write().unwrap().insert(record.id.clone());
         self.node_registry.add(record).await
     }
 
-    /// Retrieves a node record by its ID.
+    /// Retrieves a node record by its ID, unless its TTL has passed.
     pub async fn get_node(&self, id: &str) -> Option<NodeRecord> {
-        self.node_registry.get(id).await
+        self.node_registry.get(id).await.filter(|node| self.is_alive(node))
+    }
+
+    /// Removes a node record by its ID.
+    pub async fn remove_node(&self, id: &str) -> Result<(), RegistryError> {
+        self.node_ids.write().unwrap().remove(id);
+        self.node_registry.remove(id).await
     }
 
-    /// Lists all node records in the registry.
+    /// Lists all node records in the registry whose TTL has not passed.
     pub async fn list_nodes(&self) -> Vec<NodeRecord> {
-        self.node_registry.list().await
+        let now = self.clock.now();
+        let mut nodes = self.node_registry.list().await;
+        nodes.retain(|node| Self::node_alive(node, now));
+        nodes
+    }
+
+    /// Records the record types a peer's NSEC record lists for `name`; every other type is
+    /// known not to exist for it until the TTL runs out.
+    pub fn set_record_types(&self, name: &str, types: Vec<u16>, ttl: u32) {
+        let expires_at = self.clock.now() + Duration::from_secs(ttl.into());
+        self.record_types
+            .write()
+            .unwrap()
+            .insert(name.to_string(), (types, expires_at));
+    }
+
+    /// Returns the record types `name` has according to an unexpired NSEC record.
+    pub fn record_types(&self, name: &str) -> Option<Vec<u16>> {
+        let record_types = self.record_types.read().unwrap();
+        let (types, expires_at) = record_types.get(name)?;
+        (*expires_at > self.clock.now()).then(|| types.clone())
+    }
+
+    /// Returns whether an NSEC record says `name` has no record of type `rtype`.
+    pub fn denies_type(&self, name: &str, rtype: u16) -> bool {
+        self.record_types(name)
+            .is_some_and(|types| !types.contains(&rtype))
+    }
+
+    /// Forgets the NSEC record of `name`, e.g. after the peer withdrew it.
+    pub fn remove_record_types(&self, name: &str) {
+        self.record_types.write().unwrap().remove(name);
+    }
+
+    /// Returns how many hosts have a known IPv4 or IPv6 address.
+    pub fn node_count(&self) -> usize {
+        let v4 = self.node_ids.read().unwrap();
+        let v6 = self.node_v6_ids.read().unwrap();
+        v4.len() + v6.difference(&v4).count()
+    }
+
+    /// Adds a node record holding a host's IPv6 address (learned from an AAAA record),
+    /// replacing any IPv6 node with the same id. Like `add_node`, it stamps `last_seen`
+    /// with the current time when the record has none.
+    pub async fn add_node_v6(&self, mut record: NodeRecord) -> Result<(), RegistryError> {
+        record.last_seen.get_or_insert_with(|| self.clock.now());
+        self.node_v6_ids.write().unwrap().insert(record.id.clone());
+        self.node_v6_registry.add(record).await
+    }
+
+    /// Retrieves the IPv6 node record of a host by its ID, unless its TTL has passed.
+    pub async fn get_node_v6(&self, id: &str) -> Option<NodeRecord> {
+        self.node_v6_registry.get(id).await.filter(|node| self.is_alive(node))
+    }
+
+    /// Removes the IPv6 node record of a host by its ID.
+    pub async fn remove_node_v6(&self, id: &str) -> Result<(), RegistryError> {
+        self.node_v6_ids.write().unwrap().remove(id);
+        self.node_v6_registry.remove(id).await
+    }
+
+    /// Adds the TXT data learned for a discovered service instance. It expires after its
+    /// TTL, or 120 seconds if it has none.
+    pub async fn add_txt(&self, record: TxtRecord) -> Result<(), RegistryError> {
+        {
+            let lifetime = record.ttl.unwrap_or(DEFAULT_RECORD_TTL);
+            let expires_at = self.clock.now() + Duration::from_secs(lifetime.into());
+            self.txt_expiry
+                .write()
+                .unwrap()
+                .insert(record.id.clone(), expires_at);
+        }
+        self.txt_registry.add(record).await
+    }
+
+    /// Retrieves the TXT data for a discovered service instance, unless it has expired.
+    pub async fn get_txt(&self, id: &str) -> Option<TxtRecord> {
+        let now = self.clock.now();
+        let expired = self
+            .txt_expiry
+            .read()
+            .unwrap()
+            .get(id)
+            .is_some_and(|expires_at| *expires_at <= now);
+        if expired {
+            return None;
+        }
+        self.txt_registry.get(id).await
+    }
+
+    /// Removes the TXT data of a discovered service instance.
+    pub async fn remove_txt(&self, id: &str) -> Result<(), RegistryError> {
+        self.txt_expiry.write().unwrap().remove(id);
+        self.txt_registry.remove(id).await
+    }
+
+    /// Records a service type a peer listed in answer to a service type enumeration.
+    pub fn add_service_type(&self, service_type: &str, ttl: Option<u32>) {
+        let lifetime = Duration::from_secs(ttl.unwrap_or(DEFAULT_RECORD_TTL).into());
+        let mut service_types = self.service_types.write().unwrap();
+        service_types.retain(|known, _| !known.eq_ignore_ascii_case(service_type));
+        service_types.insert(service_type.to_string(), self.clock.now() + lifetime);
+    }
+
+    /// Forgets a service type a peer no longer offers.
+    pub fn remove_service_type(&self, service_type: &str) {
+        self.service_types
+            .write()
+            .unwrap()
+            .retain(|known, _| !known.eq_ignore_ascii_case(service_type));
+    }
+
+    /// Lists the service types peers have enumerated whose TTL has not yet passed, sorted.
+    pub fn list_service_types(&self) -> Vec<String> {
+        let now = self.clock.now();
+        let mut service_types = self.service_types.write().unwrap();
+        service_types.retain(|_, expires_at| *expires_at > now);
+        let mut listed: Vec<String> = service_types.keys().cloned().collect();
+        listed.sort();
+        listed
+    }
+
+    /// Returns whether `node` was seen within its TTL. Records without a TTL are given
+    /// 120 seconds; records never stamped with `last_seen` do not expire.
+    pub fn is_alive(&self, node: &NodeRecord) -> bool {
+        Self::node_alive(node, self.clock.now())
+    }
+
+    fn node_alive(node: &NodeRecord, now: Instant) -> bool {
+        let lifetime = Duration::from_secs(node.ttl.unwrap_or(DEFAULT_RECORD_TTL).into());
+        node.last_seen
+            .is_none_or(|last_seen| now.saturating_duration_since(last_seen) < lifetime)
+    }
+
+    /// Removes every node whose TTL has passed since its `last_seen`, and every discovered
+    /// service and TXT record past its expiry. Records without a TTL are given 120
+    /// seconds. Nodes the store evicted by itself stop counting towards `node_count`.
+    ///
+    /// # Returns
+    /// * `Vec<String>` - The names of the removed nodes and services.
+    pub async fn sweep_expired(&self) -> Vec<String> {
+        let _guard = self.node_lock.lock().await;
+        let mut removed = Vec::new();
+        let now = self.clock.now();
+        let nodes = [
+            (&self.node_registry, &self.node_ids),
+            (&self.node_v6_registry, &self.node_v6_ids),
+        ];
+        for (registry, ids) in nodes {
+            let mut stored = HashSet::new();
+            for node in registry.list().await {
+                if Self::node_alive(&node, now) {
+                    stored.insert(node.id);
+                } else {
+                    let _ = registry.remove(&node.id).await;
+                    removed.push(node.id);
+                }
+            }
+            // The store may also have dropped nodes on its own, e.g. when full.
+            ids.write().unwrap().retain(|id| stored.contains(id));
+        }
+
+        let expired_services: Vec<String> = self
+            .discovered_expiry
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in expired_services {
+            let _ = self.remove_discovered_service(&id).await;
+            removed.push(id);
+        }
+
+        let expired_txt: Vec<String> = self
+            .txt_expiry
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in expired_txt {
+            let _ = self.remove_txt(&id).await;
+        }
+        removed
+    }
+
+    /// Removes every IPv4 and IPv6 node, along with the NSEC record types learned for
+    /// peers' names.
+    pub async fn clear_nodes(&self) {
+        let _guard = self.node_lock.lock().await;
+        let nodes = [
+            (&self.node_registry, &self.node_ids),
+            (&self.node_v6_registry, &self.node_v6_ids),
+        ];
+        for (registry, ids) in nodes {
+            for node in registry.list().await {
+                let _ = registry.remove(&node.id).await;
+            }
+            ids.write().unwrap().clear();
+        }
+        self.record_types.write().unwrap().clear();
+    }
+
+    /// Removes every service learned from peers, with their TXT data, subtypes, and the
+    /// service types peers enumerated. Local services are kept.
+    pub async fn clear_discovered(&self) {
+        let _guard = self.node_lock.lock().await;
+        for service in self.discovered_service_registry.list().await {
+            let _ = self.discovered_service_registry.remove(&service.id).await;
+        }
+        for txt in self.txt_registry.list().await {
+            let _ = self.txt_registry.remove(&txt.id).await;
+        }
+        self.discovered_expiry.write().unwrap().clear();
+        self.txt_expiry.write().unwrap().clear();
+        self.unresolved.write().unwrap().clear();
+        self.discovered_subtypes.write().unwrap().clear();
+        self.service_types.write().unwrap().clear();
+    }
+
+    /// Removes every service, local ones included; see `clear_discovered` to keep them.
+    pub async fn clear_services(&self) {
+        self.clear_discovered().await;
+        for service in self.service_registry.list().await {
+            let _ = self.service_registry.remove(&service.id).await;
+        }
+    }
+
+    /// Lists the discovered services whose SRV target is `origin`, sorted by instance name.
+    /// Host names are compared case-insensitively.
+    pub async fn services_for_origin(&self, origin: &str) -> Vec<ServiceRecord> {
+        let mut services: Vec<ServiceRecord> = self
+            .list_discovered_services()
+            .await
+            .into_iter()
+            .filter(|service| service.origin.eq_ignore_ascii_case(origin))
+            .collect();
+        services.sort_by(|a, b| a.id.cmp(&b.id));
+        services
+    }
+
+    /// Follows a discovered service's SRV target to the node holding its IPv4 address.
+    /// Host names are compared case-insensitively, as in `services_for_origin`.
+    ///
+    /// # Returns
+    /// * `Some(NodeRecord)` - The target host's node.
+    /// * `None` - If the service is unknown or its target has no known IPv4 address.
+    pub async fn node_for_service(&self, service_id: &str) -> Option<NodeRecord> {
+        let service = self.get_discovered_service(service_id).await?;
+        self.host_node(&service.origin).await
+    }
+
+    /// Retrieves the live IPv4 node of `host`, comparing host names case-insensitively.
+    async fn host_node(&self, host: &str) -> Option<NodeRecord> {
+        if let Some(node) = self.get_node(host).await {
+            return Some(node);
+        }
+        self.list_nodes()
+            .await
+            .into_iter()
+            .find(|node| node.id.eq_ignore_ascii_case(host))
+    }
+
+    /// Retrieves the live IPv6 node of `host`, comparing host names case-insensitively.
+    async fn host_node_v6(&self, host: &str) -> Option<NodeRecord> {
+        if let Some(node) = self.get_node_v6(host).await {
+            return Some(node);
+        }
+        let now = self.clock.now();
+        self.node_v6_registry
+            .list()
+            .await
+            .into_iter()
+            .find(|node| node.id.eq_ignore_ascii_case(host) && Self::node_alive(node, now))
+    }
+
+    /// Returns whether a host has a known IPv4 or IPv6 address.
+    async fn target_resolves(&self, host: &str) -> bool {
+        self.host_node(host).await.is_some() || self.host_node_v6(host).await.is_some()
+    }
+
+    /// Flags every discovered service whose SRV target has no known address as unresolved,
+    /// and clears the flag of services whose target has since resolved.
+    ///
+    /// # Returns
+    /// * `Vec<String>` - The instance names flagged by this call, sorted. Services that were
+    ///   already flagged are not reported again.
+    pub async fn flag_unresolved(&self) -> Vec<String> {
+        let mut flagged = Vec::new();
+        for service in self.list_discovered_services().await {
+            let resolves = self.target_resolves(&service.origin).await;
+            let mut unresolved = self.unresolved.write().unwrap();
+            if resolves {
+                unresolved.remove(&service.id);
+            } else if unresolved.insert(service.id.clone()) {
+                flagged.push(service.id);
+            }
+        }
+        flagged.sort();
+        flagged
+    }
+
+    /// Lists every discovered service with whether its SRV target currently has a known
+    /// address, sorted by instance name.
+    pub async fn list_services_with_state(&self) -> Vec<(ServiceRecord, bool)> {
+        let mut services = Vec::new();
+        for service in self.list_discovered_services().await {
+            let resolves = self.target_resolves(&service.origin).await;
+            services.push((service, resolves));
+        }
+        services.sort_by(|(a, _), (b, _)| a.id.cmp(&b.id));
+        services
+    }
+
+    /// Assembles the complete record set of every discovered service instance.
+    ///
+    /// Each entry is keyed by instance name and combines the instance's SRV data with its
+    /// TXT entries and the addresses of its target host, whichever of those are known.
+    /// Host names are compared case-insensitively.
+    pub async fn instances(&self) -> HashMap<String, ResolvedService> {
+        let mut instances = HashMap::new();
+        for service in self.list_discovered_services().await {
+            let txt = self
+                .get_txt(&service.id)
+                .await
+                .map(|record| record.entries)
+                .unwrap_or_default();
+            let mut addresses = Vec::new();
+            if let Some(node) = self.host_node(&service.origin).await {
+                addresses.push(node.ip_address);
+            }
+            if let Some(node) = self.host_node_v6(&service.origin).await {
+                addresses.push(node.ip_address);
+            }
+
+            instances.insert(
+                service.id.clone(),
+                ResolvedService {
+                    instance: service.id,
+                    service_type: service.service_type,
+                    host: service.origin,
+                    port: service.port,
+                    priority: service.priority.unwrap_or(0),
+                    weight: service.weight.unwrap_or(0),
+                    txt,
+                    addresses,
+                },
+            );
+        }
+        instances
+    }
+
+    /// Lists the discovered instances that can be connected to right now, sorted by
+    /// instance name.
+    ///
+    /// An instance is listed only while its SRV record and an IPv4 address of its target
+    /// host are both cached and unexpired; instances still missing either are left out
+    /// rather than returned half-filled. A live IPv6 address of the target is included
+    /// alongside the IPv4 one. Host names are compared case-insensitively.
+    pub async fn list_resolvable_services(&self) -> Vec<ResolvedInstance> {
+        let mut resolvable = Vec::new();
+        for service in self.list_discovered_services().await {
+            let Some(node) = self.host_node(&service.origin).await else {
+                continue;
+            };
+            let mut addresses = vec![node.ip_address];
+            if let Some(node) = self.host_node_v6(&service.origin).await {
+                addresses.push(node.ip_address);
+            }
+
+            resolvable.push(ResolvedInstance {
+                instance: service.id,
+                host: service.origin,
+                port: service.port,
+                priority: service.priority.unwrap_or(0),
+                weight: service.weight.unwrap_or(0),
+                addresses,
+                txt: service.txt.into_iter().collect(),
+            });
+        }
+        resolvable.sort_by(|a, b| a.instance.cmp(&b.instance));
+        resolvable
+    }
+}
+
+impl Default for MdnsRegistry {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::behaviour::records::mdns_records::{NodeRecord, ServiceRecord};
+    use crate::behaviour::records::mdns_records::{CacheFlush, NodeRecord, Protocol, ServiceRecord};
     use std::time::Duration;
 
     #[tokio::test]
@@ -66,6 +673,11 @@ mod tests {
             origin: "local".to_string(),
             priority: Some(10),
             weight: Some(5),
+            cache_flush: CacheFlush::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: Some(Protocol::Tcp),
+            bind_addr: None,
         };
 
         registry.add_service(service.clone()).await.unwrap();
@@ -79,7 +691,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_service_expiration() {
+    async fn test_discovered_service_expiration() {
         let registry = MdnsRegistry::new();
 
         let service = ServiceRecord {
@@ -90,13 +702,19 @@ mod tests {
             origin: "local".to_string(),
             priority: Some(10),
             weight: Some(5),
+            cache_flush: CacheFlush::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: Some(Protocol::Tcp),
+            bind_addr: None,
         };
 
-        registry.add_service(service).await.unwrap();
+        registry.add_discovered_service(service).await.unwrap();
         tokio::time::sleep(Duration::from_secs(2)).await;
 
-        let retrieved = registry.get_service("service2").await;
+        let retrieved = registry.get_discovered_service("service2").await;
         assert!(retrieved.is_none(), "Expired service should not be retrievable");
+        assert!(registry.list_discovered_services().await.is_empty());
     }
 
     #[tokio::test]
@@ -107,6 +725,9 @@ mod tests {
             id: "node1".to_string(),
             ip_address: "192.168.1.1".to_string(),
             ttl: Some(10),
+            source: None,
+            last_seen: None,
+            scope_id: None,
         };
 
         registry.add_node(node.clone()).await.unwrap();
@@ -124,6 +745,9 @@ mod tests {
             id: "node2".to_string(),
             ip_address: "192.168.1.2".to_string(),
             ttl: Some(1),
+            source: None,
+            last_seen: None,
+            scope_id: None,
         };
 
         registry.add_node(node).await.unwrap();
@@ -146,6 +770,11 @@ mod tests {
                 origin: "local".to_string(),
                 priority: Some(10),
                 weight: Some(5),
+                cache_flush: CacheFlush::default(),
+                txt: Vec::new(),
+                subtypes: Vec::new(),
+                protocol: Some(Protocol::Tcp),
+                bind_addr: None,
             };
             registry.add_service(service).await.unwrap();
         }
@@ -162,12 +791,18 @@ mod tests {
             id: "evictable_node".to_string(),
             ip_address: "192.168.1.100".to_string(),
             ttl: Some(1),
+            source: None,
+            last_seen: None,
+            scope_id: None,
         };
 
         let new_node = NodeRecord {
             id: "new_node".to_string(),
             ip_address: "192.168.1.101".to_string(),
             ttl: None,
+            source: None,
+            last_seen: None,
+            scope_id: None,
         };
 
         registry.add_node(evictable_node).await.unwrap();
@@ -193,6 +828,11 @@ mod tests {
                 origin: "local".to_string(),
                 priority: Some(10),
                 weight: Some(5),
+                cache_flush: CacheFlush::default(),
+                txt: Vec::new(),
+                subtypes: Vec::new(),
+                protocol: Some(Protocol::Tcp),
+                bind_addr: None,
             };
             registry.add_service(service).await.unwrap();
         }
@@ -206,6 +846,11 @@ mod tests {
             origin: "local".to_string(),
             priority: Some(10),
             weight: Some(5),
+            cache_flush: CacheFlush::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: Some(Protocol::Tcp),
+            bind_addr: None,
         };
         registry.add_service(new_service.clone()).await.unwrap();
 
@@ -219,4 +864,128 @@ mod tests {
         // Check that the oldest record was evicted
         assert!(!services.iter().any(|s| s.id == "service0"), "Oldest service should be evicted from the registry");
     }
+
+    #[tokio::test]
+    async fn test_sweep_removes_nodes_past_their_ttl() {
+        let registry = MdnsRegistry::new();
+        let node = |id: &str| NodeRecord {
+            id: id.to_string(),
+            ip_address: "192.168.1.30".to_string(),
+            ttl: Some(1),
+            source: None,
+            last_seen: None,
+            scope_id: None,
+        };
+        registry.add_node(node("stale.local")).await.unwrap();
+        registry.add_node(node("fresh.local")).await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        // Seeing a node again restarts its TTL.
+        registry.add_node(node("fresh.local")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(600)).await;
+
+        registry.sweep_expired().await;
+        assert!(registry.get_node("stale.local").await.is_none());
+        assert!(registry.get_node("fresh.local").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_forgets_nodes_evicted_by_the_store() {
+        let registry = MdnsRegistry::new();
+        for index in 0..REGISTRY_CAPACITY + 5 {
+            registry
+                .add_node(NodeRecord {
+                    id: format!("host{}.local", index),
+                    ip_address: "192.168.1.30".to_string(),
+                    ttl: Some(120),
+                    source: None,
+                    last_seen: None,
+                    scope_id: None,
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(registry.list_nodes().await.len(), REGISTRY_CAPACITY);
+
+        registry.sweep_expired().await;
+        assert_eq!(registry.node_count(), REGISTRY_CAPACITY);
+    }
+
+    #[tokio::test]
+    async fn test_relational_lookups_between_services_and_hosts() {
+        let registry = MdnsRegistry::new();
+        let discovered = |id: &str, origin: &str| ServiceRecord {
+            id: id.to_string(),
+            service_type: "_http._tcp.local".to_string(),
+            port: 80,
+            ttl: Some(120),
+            origin: origin.to_string(),
+            priority: Some(0),
+            weight: Some(0),
+            cache_flush: CacheFlush::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: Some(Protocol::Tcp),
+            bind_addr: None,
+        };
+        registry.add_discovered_service(discovered("B._http._tcp.local", "peer.local")).await.unwrap();
+        registry.add_discovered_service(discovered("A._http._tcp.local", "Peer.local")).await.unwrap();
+        registry.add_discovered_service(discovered("C._http._tcp.local", "other.local")).await.unwrap();
+        registry
+            .add_node(NodeRecord {
+                id: "peer.local".to_string(),
+                ip_address: "192.168.1.30".to_string(),
+                ttl: Some(120),
+                source: None,
+                last_seen: None,
+                scope_id: None,
+            })
+            .await
+            .unwrap();
+
+        let ids: Vec<String> = registry
+            .services_for_origin("peer.local")
+            .await
+            .into_iter()
+            .map(|service| service.id)
+            .collect();
+        assert_eq!(ids, vec!["A._http._tcp.local", "B._http._tcp.local"]);
+
+        let node = registry.node_for_service("B._http._tcp.local").await.unwrap();
+        assert_eq!(node.ip_address, "192.168.1.30");
+        let node = registry.node_for_service("A._http._tcp.local").await.unwrap();
+        assert_eq!(node.id, "peer.local", "Origins match nodes case-insensitively");
+        assert!(registry.node_for_service("C._http._tcp.local").await.is_none());
+        assert!(registry.node_for_service("Unknown._http._tcp.local").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_clear_discovered_keeps_local_services() {
+        let registry = MdnsRegistry::new();
+        let service = |id: &str| ServiceRecord {
+            id: id.to_string(),
+            service_type: "_http._tcp.local".to_string(),
+            port: 80,
+            ttl: Some(120),
+            origin: "host.local".to_string(),
+            priority: Some(0),
+            weight: Some(0),
+            cache_flush: CacheFlush::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: Some(Protocol::Tcp),
+            bind_addr: None,
+        };
+        registry.add_service(service("Local._http._tcp.local")).await.unwrap();
+        registry.add_discovered_service(service("Peer._http._tcp.local")).await.unwrap();
+        registry.add_service_type("_http._tcp.local", Some(120));
+
+        registry.clear_discovered().await;
+        assert!(registry.list_discovered_services().await.is_empty());
+        assert!(registry.list_service_types().is_empty());
+        assert_eq!(registry.list_services().await.len(), 1);
+
+        registry.clear_services().await;
+        assert!(registry.list_services().await.is_empty());
+    }
 }
@@ -1,7 +1,10 @@
 // protocols\mdns\src\behaviour\records\mod.rs
 mod mdns_registry;
 mod mdns_records;
+mod registry_store;
 
 pub use mdns_registry::MdnsRegistry;
-pub use mdns_records::{ServiceRecord,NodeRecord};
+pub use registry_store::{MemoryStore, RegistryStore};
+pub(crate) use mdns_records::srv_order;
+pub use mdns_records::{ServiceRecord,ServiceRecordBuilder,NodeRecord,TxtRecord,ResolvedService,ResolvedInstance,CacheFlush,Protocol};
 
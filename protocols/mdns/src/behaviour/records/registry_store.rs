@@ -0,0 +1,29 @@
+// protocols\mdns\src\behaviour\records\registry_store.rs
+use registry::{InMemoryRegistry, Record, Registry};
+
+/// Storage backend holding the records of an `MdnsRegistry`.
+///
+/// The registry keeps each kind of record in its own table: local and discovered
+/// services, IPv4 and IPv6 hosts, and TXT data. A store opens those tables, each one a
+/// `Registry` with async `add`, `get`, `list`, and `remove`, so a sled- or file-backed
+/// store can keep the discovered topology across restarts. Expiry bookkeeping stays in
+/// memory, so records loaded from a durable store only expire once they are seen again.
+pub trait RegistryStore: Send + Sync + 'static {
+    /// A table holding records of type `R`.
+    type Table<R: Record + 'static>: Registry<R>;
+
+    /// Opens the table called `name`, holding at most `capacity` records.
+    fn open<R: Record + 'static>(&self, name: &str, capacity: usize) -> Self::Table<R>;
+}
+
+/// The default store, keeping every table in an `InMemoryRegistry`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemoryStore;
+
+impl RegistryStore for MemoryStore {
+    type Table<R: Record + 'static> = InMemoryRegistry<R>;
+
+    fn open<R: Record + 'static>(&self, _name: &str, capacity: usize) -> Self::Table<R> {
+        InMemoryRegistry::new(capacity)
+    }
+}
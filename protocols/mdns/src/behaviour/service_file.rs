@@ -0,0 +1,138 @@
+// protocols\mdns\src\behaviour\service_file.rs
+use crate::behaviour::records::{CacheFlush, Protocol, ServiceRecord};
+use crate::behaviour::{MdnsError, MdnsService, RegistryStore};
+use crate::ServiceInstanceName;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use std::path::Path;
+use tracing::{error, warn};
+
+/// A single service entry of a service definition file.
+///
+/// A definition file is a JSON array of these entries, for example:
+///
+/// ```json
+/// [
+///   {
+///     "id": "Printer._ipp._tcp.local",
+///     "type": "_ipp._tcp.local",
+///     "port": 631,
+///     "ttl": 120,
+///     "origin": "office-host.local",
+///     "txt": { "path": "/print" },
+///     "subtypes": ["_color"]
+///   }
+/// ]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceDefinition {
+    pub id: String,
+    #[serde(rename = "type", alias = "service_type")]
+    pub service_type: String,
+    pub port: u16,
+    #[serde(default)]
+    pub ttl: Option<u32>,
+    pub origin: String,
+    #[serde(default)]
+    pub txt: BTreeMap<String, String>,
+    #[serde(default)]
+    pub subtypes: Vec<String>,
+    #[serde(default)]
+    pub bind_addr: Option<Ipv4Addr>,
+}
+
+impl ServiceDefinition {
+    /// Checks that the definition describes a service that can be advertised.
+    ///
+    /// # Returns
+    /// * `Ok(ServiceRecord)` - The record to register.
+    /// * `Err(MdnsError)` - If any field is invalid.
+    pub fn into_record(self) -> Result<ServiceRecord, MdnsError> {
+        let instance = ServiceInstanceName::parse(&self.id)
+            .map_err(|e| MdnsError::Generic(format!("Invalid instance name '{}': {}", self.id, e)))?;
+        if instance.service_name() != self.service_type.trim_end_matches('.') {
+            return Err(MdnsError::Generic(format!(
+                "Instance '{}' does not belong to service type '{}'",
+                self.id, self.service_type
+            )));
+        }
+        if self.port == 0 {
+            return Err(MdnsError::Generic(format!("Service '{}' has port 0", self.id)));
+        }
+        if self.origin.is_empty() {
+            return Err(MdnsError::Generic(format!("Service '{}' has no origin host", self.id)));
+        }
+        if let Some(subtype) = self.subtypes.iter().find(|subtype| !subtype.starts_with('_')) {
+            return Err(MdnsError::Generic(format!(
+                "Subtype '{}' of service '{}' must start with '_'",
+                subtype, self.id
+            )));
+        }
+
+        let protocol = Protocol::of_service_type(&self.service_type);
+        Ok(ServiceRecord {
+            id: self.id,
+            service_type: self.service_type,
+            port: self.port,
+            ttl: self.ttl,
+            origin: self.origin,
+            priority: Some(0),
+            weight: Some(0),
+            cache_flush: CacheFlush::default(),
+            txt: self.txt.into_iter().collect(),
+            subtypes: self.subtypes,
+            protocol,
+            bind_addr: self.bind_addr,
+        })
+    }
+}
+
+impl<S: RegistryStore> MdnsService<S> {
+    /// Registers every service declared in a JSON service definition file, then
+    /// announces the registered services.
+    ///
+    /// Each entry is validated and registered independently, so one bad entry does
+    /// not prevent the others from being registered.
+    ///
+    /// # Arguments
+    /// * `path` - Path to a JSON file holding an array of `ServiceDefinition`s.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<(String, Result<(), MdnsError>)>)` - The outcome for each entry, keyed by its id.
+    /// * `Err(MdnsError)` - If the file cannot be read or is not a list of definitions.
+    pub async fn register_from_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<(String, Result<(), MdnsError>)>, MdnsError> {
+        let contents = tokio::fs::read_to_string(path.as_ref()).await?;
+        let definitions: Vec<ServiceDefinition> = serde_json::from_str(&contents).map_err(|e| {
+            MdnsError::Generic(format!(
+                "Failed to parse service file '{}': {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+
+        let mut results = Vec::with_capacity(definitions.len());
+        for definition in definitions {
+            let id = definition.id.clone();
+            let result = match definition.into_record() {
+                Ok(record) => self.add_local_service(record).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = &result {
+                warn!("(CONFIG) Failed to register service '{}': {}", id, err);
+            }
+            results.push((id, result));
+        }
+
+        if results.iter().any(|(_, result)| result.is_ok()) {
+            if let Err(err) = self.advertise_services().await {
+                error!("(CONFIG) Failed to announce loaded services: {}", err);
+            }
+        }
+
+        Ok(results)
+    }
+}
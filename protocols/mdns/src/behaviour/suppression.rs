@@ -0,0 +1,86 @@
+// protocols\mdns\src\behaviour\suppression.rs
+use crate::{DnsPacket, DnsRecord};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Send history of a single multicast record, as tracked for answer suppression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuppressionEntry {
+    /// When the record was last multicast.
+    pub last_sent: Instant,
+    /// How many times the record has been left out of a response since it was last sent.
+    pub suppressed: u64,
+}
+
+/// Tracks when each record was last multicast so repeats can be suppressed.
+#[derive(Debug, Default)]
+pub(crate) struct SuppressionState {
+    entries: Mutex<HashMap<String, SuppressionEntry>>,
+}
+
+impl SuppressionState {
+    /// Records an attempt to multicast `record` and decides whether it may be sent.
+    ///
+    /// # Returns
+    /// * `true` - If the record was not sent within `min_interval`; it is marked as sent now.
+    /// * `false` - If the record was sent too recently; its suppression count is increased.
+    pub(crate) fn try_send(&self, record: &DnsRecord, min_interval: Duration) -> bool {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get_mut(&record_key(record)) {
+            Some(entry) if now.duration_since(entry.last_sent) < min_interval => {
+                entry.suppressed += 1;
+                false
+            }
+            _ => {
+                entries.insert(
+                    record_key(record),
+                    SuppressionEntry {
+                        last_sent: now,
+                        suppressed: 0,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Applies `try_send` to the answers of a round of packets sent together, such as a
+    /// split advertisement. A record repeated in several packets of the round is decided
+    /// once, so a host address shared by many services is not dropped after its first packet.
+    pub(crate) fn retain_round(&self, packets: &mut [DnsPacket], min_interval: Duration) {
+        let mut decided: HashMap<String, bool> = HashMap::new();
+        for packet in packets {
+            packet.answers.retain(|record| {
+                *decided
+                    .entry(record_key(record))
+                    .or_insert_with(|| self.try_send(record, min_interval))
+            });
+        }
+    }
+
+    /// Returns a copy of the send history, keyed by record.
+    pub(crate) fn snapshot(&self) -> HashMap<String, SuppressionEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Forgets all send history, so every record may be sent again immediately.
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Builds the key identifying a record in the suppression state, e.g. `PTR _http._tcp.local -> Web._http._tcp.local`.
+fn record_key(record: &DnsRecord) -> String {
+    match record {
+        DnsRecord::PTR { name, ptr_name, .. } => format!("PTR {} -> {}", name, ptr_name),
+        DnsRecord::SRV { name, .. } => format!("SRV {}", name),
+        DnsRecord::TXT { name, .. } => format!("TXT {}", name),
+        DnsRecord::A { name, ip, .. } => format!("A {} -> {}", name, Ipv4Addr::from(*ip)),
+        DnsRecord::AAAA { name, ip, .. } => format!("AAAA {} -> {}", name, Ipv6Addr::from(*ip)),
+        DnsRecord::NSEC { name, .. } => format!("NSEC {}", name),
+        DnsRecord::OPT { .. } => "OPT".to_string(),
+    }
+}
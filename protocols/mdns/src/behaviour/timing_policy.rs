@@ -0,0 +1,93 @@
+// protocols\mdns\src\behaviour\timing_policy.rs
+use rand::Rng;
+use std::time::Duration;
+
+/// Timing parameters of the mDNS protocol, gathered in one place so they can be tuned
+/// and tested together.
+///
+/// The defaults follow RFC 6762.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimingPolicy {
+    /// Number of probe queries sent before claiming a name (RFC 6762 §8.1: 3).
+    pub probe_count: u32,
+    /// Delay between consecutive probes (RFC 6762 §8.1: 250ms).
+    pub probe_interval: Duration,
+    /// Number of unsolicited announcements sent after claiming a name (RFC 6762 §8.3: at least 2;
+    /// 3 by default so a single lost packet does not delay discovery).
+    pub announce_count: u32,
+    /// Delay between consecutive announcements (RFC 6762 §8.3: 1s).
+    pub announce_interval: Duration,
+    /// Lower bound of the random delay before multicasting a shared-record response (RFC 6762 §6: 20ms).
+    pub response_delay_min: Duration,
+    /// Upper bound of the random delay before multicasting a shared-record response (RFC 6762 §6: 120ms).
+    pub response_delay_max: Duration,
+    /// Minimum time between two multicasts of the same record (RFC 6762 §6: 1s).
+    pub multicast_rate_limit: Duration,
+    /// Interval between checks for discovered services whose target address has expired.
+    pub address_refresh_interval: Duration,
+    /// Interval between sweeps that remove peer records whose TTL has elapsed.
+    pub record_sweep_interval: Duration,
+    /// Interval between checks for a changed local address, after which `run` rejoins the
+    /// multicast group.
+    pub interface_check_interval: Duration,
+    /// How long `MdnsService::resolve` waits for further answers after the last new one
+    /// before returning early.
+    pub resolve_quiet_period: Duration,
+    /// Delay before a continuous query, such as `MdnsService::wait_for_services`, is first
+    /// repeated; it doubles after every repetition (RFC 6762 §5.2: at least 1s).
+    pub requery_interval: Duration,
+    /// Upper bound on the delay between repetitions of a continuous query.
+    pub requery_interval_max: Duration,
+    /// Percentages of a local service's shortest record TTL after which `run` announces
+    /// the service again, so peers' caches never run out; the cycle restarts after the
+    /// last one. Empty to rely on the flat advertise interval alone.
+    pub reannounce_thresholds: Vec<u32>,
+    /// Interval between checks for local services due for re-announcement.
+    pub reannounce_check_interval: Duration,
+    /// How recently another host must have asked the same question for the periodic
+    /// query to skip its next send (RFC 6762 §7.3).
+    pub duplicate_question_window: Duration,
+}
+
+impl TimingPolicy {
+    /// Returns the offset of each probe from the first one, e.g. `[0ms, 250ms, 500ms]`.
+    pub fn probe_schedule(&self) -> Vec<Duration> {
+        (0..self.probe_count).map(|i| self.probe_interval * i).collect()
+    }
+
+    /// Returns the offset of each announcement from the first one, e.g. `[0s, 1s, 2s]`.
+    pub fn announce_schedule(&self) -> Vec<Duration> {
+        (0..self.announce_count).map(|i| self.announce_interval * i).collect()
+    }
+
+    /// Picks a random response delay between `response_delay_min` and `response_delay_max`.
+    pub fn response_delay(&self) -> Duration {
+        if self.response_delay_max <= self.response_delay_min {
+            return self.response_delay_min;
+        }
+        rand::thread_rng().gen_range(self.response_delay_min..=self.response_delay_max)
+    }
+}
+
+impl Default for TimingPolicy {
+    fn default() -> Self {
+        Self {
+            probe_count: 3,
+            probe_interval: Duration::from_millis(250),
+            announce_count: 3,
+            announce_interval: Duration::from_secs(1),
+            response_delay_min: Duration::from_millis(20),
+            response_delay_max: Duration::from_millis(120),
+            multicast_rate_limit: Duration::from_secs(1),
+            address_refresh_interval: Duration::from_secs(5),
+            record_sweep_interval: Duration::from_secs(1),
+            interface_check_interval: Duration::from_secs(5),
+            resolve_quiet_period: Duration::from_millis(500),
+            requery_interval: Duration::from_secs(1),
+            requery_interval_max: Duration::from_secs(60),
+            reannounce_thresholds: vec![80, 85, 90],
+            reannounce_check_interval: Duration::from_secs(1),
+            duplicate_question_window: Duration::from_secs(1),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// protocols\mdns\src\behaviour\transport.rs
+use async_trait::async_trait;
+use socket2::SockRef;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::net::UdpSocket;
+
+/// Abstraction over the datagram socket used by the mDNS service.
+///
+/// The default implementation is a multicast `UdpSocket`, but any type providing
+/// datagram send/receive semantics can be plugged in (e.g. for deterministic tests).
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends a datagram to the given address.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The number of bytes sent.
+    /// * `Err(std::io::Error)` - If the send fails.
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> std::io::Result<usize>;
+
+    /// Sends a multicast datagram out of the interface with the given local address.
+    ///
+    /// The default implementation ignores the interface and behaves like `send_to`.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The number of bytes sent.
+    /// * `Err(std::io::Error)` - If the interface cannot be selected or the send fails.
+    async fn send_to_via(
+        &self,
+        buf: &[u8],
+        target: SocketAddr,
+        _interface: Ipv4Addr,
+    ) -> std::io::Result<usize> {
+        self.send_to(buf, target).await
+    }
+
+    /// Receives a single datagram into the buffer.
+    ///
+    /// # Returns
+    /// * `Ok((usize, SocketAddr))` - The number of bytes read and the sender's address.
+    /// * `Err(std::io::Error)` - If the receive fails.
+    async fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)>;
+
+    /// Joins the IPv4 multicast `group` on the interface with the given local address,
+    /// leaving it there first, to restore a membership lost when the interface went down
+    /// or changed address.
+    ///
+    /// The default implementation does nothing, for transports without group membership.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the group is joined.
+    /// * `Err(std::io::Error)` - If joining fails.
+    fn rejoin_multicast_v4(&self, _group: Ipv4Addr, _interface: Ipv4Addr) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Transport for UdpSocket {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> std::io::Result<usize> {
+        UdpSocket::send_to(self, buf, target).await
+    }
+
+    async fn send_to_via(
+        &self,
+        buf: &[u8],
+        target: SocketAddr,
+        interface: Ipv4Addr,
+    ) -> std::io::Result<usize> {
+        SockRef::from(self).set_multicast_if_v4(&interface)?;
+        UdpSocket::send_to(self, buf, target).await
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+        UdpSocket::recv_from(self, buf).await
+    }
+
+    fn rejoin_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> std::io::Result<()> {
+        // Leaving fails when the membership is already gone, which is the usual case here.
+        let _ = self.leave_multicast_v4(group, interface);
+        self.join_multicast_v4(group, interface)
+    }
+}
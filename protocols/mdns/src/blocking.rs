@@ -0,0 +1,117 @@
+// protocols\mdns\src\blocking.rs
+use crate::behaviour::{MdnsConfig, MdnsError, MdnsService, NodeRecord, ServiceRecord, Transport};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+use tokio_util::sync::CancellationToken;
+use tracing::error;
+
+/// A blocking front end to `MdnsService` for programs that do not run a tokio runtime.
+///
+/// The wrapper owns a runtime of its own and keeps the service listening on it, so
+/// queries for registered services are answered and `resolve` sees its responses. Its
+/// methods block the calling thread and must not be called from async code.
+pub struct BlockingMdnsService {
+    runtime: Runtime,
+    service: Arc<MdnsService>,
+    shutdown: CancellationToken,
+}
+
+impl BlockingMdnsService {
+    /// Creates a blocking mDNS service with the default configuration.
+    pub fn new() -> Result<Self, MdnsError> {
+        Self::with_config(MdnsConfig::default())
+    }
+
+    /// Creates a blocking mDNS service with a custom configuration.
+    pub fn with_config(config: MdnsConfig) -> Result<Self, MdnsError> {
+        let runtime = Self::runtime()?;
+        let service = runtime.block_on(MdnsService::with_config(config))?;
+        Ok(Self::start(runtime, service))
+    }
+
+    /// Creates a blocking mDNS service on top of a custom transport.
+    pub fn with_transport(transport: Arc<dyn Transport>, config: MdnsConfig) -> Result<Self, MdnsError> {
+        let runtime = Self::runtime()?;
+        // The registry spawns its expiry tasks on the current runtime.
+        let service = {
+            let _guard = runtime.enter();
+            MdnsService::with_transport(transport, config)
+        };
+        Ok(Self::start(runtime, service))
+    }
+
+    fn runtime() -> Result<Runtime, MdnsError> {
+        Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("mdns-blocking")
+            .enable_all()
+            .build()
+            .map_err(MdnsError::NetworkError)
+    }
+
+    fn start(runtime: Runtime, service: Arc<MdnsService>) -> Self {
+        let shutdown = CancellationToken::new();
+        let listener = Arc::clone(&service);
+        let cancelled = shutdown.clone();
+        runtime.spawn(async move {
+            tokio::select! {
+                _ = cancelled.cancelled() => {}
+                result = listener.listen() => {
+                    if let Err(err) = result {
+                        error!("(BLOCKING) Listener stopped: {:?}", err);
+                    }
+                }
+            }
+        });
+        Self {
+            runtime,
+            service,
+            shutdown,
+        }
+    }
+
+    /// Registers a local service, like `MdnsService::register_local_service`.
+    pub fn register_local_service(
+        &self,
+        id: String,
+        service_type: String,
+        port: u16,
+        ttl: Option<u32>,
+        origin: String,
+        txt: Option<HashMap<String, String>>,
+    ) -> Result<(), MdnsError> {
+        self.runtime.block_on(
+            self.service
+                .register_local_service(id, service_type, port, ttl, origin, txt),
+        )
+    }
+
+    /// Queries for `service_type` and returns the instances that answered within
+    /// `timeout`, like `MdnsService::resolve`.
+    pub fn resolve(&self, service_type: &str, timeout: Duration) -> Result<Vec<ServiceRecord>, MdnsError> {
+        self.runtime.block_on(self.service.resolve(service_type, timeout))
+    }
+
+    /// Lists the peer hosts with a known IPv4 address.
+    pub fn list_nodes(&self) -> Vec<NodeRecord> {
+        self.runtime.block_on(self.service.registry.list_nodes())
+    }
+
+    /// Returns the wrapped service, for calls this wrapper does not cover.
+    pub fn service(&self) -> &Arc<MdnsService> {
+        &self.service
+    }
+
+    /// Sends a goodbye for every local service and stops listening.
+    pub fn shutdown(self) -> Result<(), MdnsError> {
+        self.runtime.block_on(self.service.shutdown())
+    }
+}
+
+impl Drop for BlockingMdnsService {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
@@ -0,0 +1,88 @@
+// protocols\mdns\src\instance_name.rs
+use crate::name::{escape_label, split_escaped_labels, DnsName};
+use std::fmt;
+
+/// Represents a DNS-SD service instance name split into its three components.
+///
+/// A full instance name such as `My Printer._ipp._tcp.local` is composed of the
+/// human-readable instance (`My Printer`), the service type (`_ipp._tcp`), and the
+/// domain (`local`). The instance portion is a single DNS label and may itself contain
+/// dots, which are escaped as `\.` in the textual form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceInstanceName {
+    pub instance: String,
+    pub service_type: String,
+    pub domain: String,
+}
+
+impl ServiceInstanceName {
+    /// Parses a `ServiceInstanceName` from its textual (presentation) form.
+    ///
+    /// Backslash escapes are honored, so `My\.Printer._ipp._tcp.local` yields the
+    /// instance `My.Printer`. Decimal escapes of the form `\DDD` are also supported.
+    ///
+    /// # Arguments
+    /// * `name` - The full instance name as a string.
+    ///
+    /// # Returns
+    /// * `Ok(ServiceInstanceName)` - If the name contains an instance, service type, and domain.
+    /// * `Err(String)` - If the name is malformed.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let labels = split_escaped_labels(name)?;
+        Self::from_labels(&labels)
+    }
+
+    /// Builds a `ServiceInstanceName` from a parsed `DnsName`.
+    ///
+    /// Labels received over the wire are already unescaped, so dots inside the
+    /// instance label are preserved as-is.
+    pub fn from_dns_name(name: &DnsName) -> Result<Self, String> {
+        Self::from_labels(name.labels())
+    }
+
+    /// Builds a `ServiceInstanceName` from a list of unescaped labels.
+    ///
+    /// The service type is located as the `_service._proto` label pair, where the protocol
+    /// label is `_tcp` or `_udp`. Everything before it is the instance, everything after it
+    /// is the domain.
+    pub fn from_labels(labels: &[String]) -> Result<Self, String> {
+        let proto_index = labels
+            .iter()
+            .enumerate()
+            .skip(2)
+            .find(|(_, label)| {
+                label.eq_ignore_ascii_case("_tcp") || label.eq_ignore_ascii_case("_udp")
+            })
+            .map(|(index, _)| index)
+            .ok_or_else(|| "Missing '_tcp' or '_udp' protocol label".to_string())?;
+
+        let service_label = &labels[proto_index - 1];
+        if !service_label.starts_with('_') {
+            return Err(format!("Service label '{}' must start with '_'", service_label));
+        }
+
+        let domain_labels = &labels[proto_index + 1..];
+        if domain_labels.is_empty() {
+            return Err("Missing domain".to_string());
+        }
+
+        Ok(ServiceInstanceName {
+            instance: labels[..proto_index - 1].join("."),
+            service_type: format!("{}.{}", service_label, labels[proto_index]),
+            domain: domain_labels.join("."),
+        })
+    }
+
+    /// Returns the service type qualified with the domain, e.g. `_ipp._tcp.local`.
+    pub fn service_name(&self) -> String {
+        format!("{}.{}", self.service_type, self.domain)
+    }
+}
+
+impl fmt::Display for ServiceInstanceName {
+    /// Formats the instance name in presentation form, escaping dots and backslashes
+    /// in the instance portion.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", escape_label(&self.instance), self.service_type, self.domain)
+    }
+}
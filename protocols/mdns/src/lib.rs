@@ -4,12 +4,19 @@
 mod record;
 mod packet;
 mod name;
+mod instance_name;
 
-pub use record::DnsRecord;
+pub use record::{DnsRecord, UnsupportedRecordType};
 pub use name::DnsName;
-pub use packet::{DnsPacket,DnsQuestion};
+pub use instance_name::ServiceInstanceName;
+pub use packet::{DnsHeader, DnsPacket, DnsQuestion};
 
 // =================================================
 
 mod behaviour;
-pub use behaviour::*;
\ No newline at end of file
+pub use behaviour::*;
+// Blocking wrapper for callers without a tokio runtime.
+#[cfg(feature = "blocking")]
+pub mod blocking;
+// Token passed to `MdnsService::run` to stop its tasks.
+pub use tokio_util::sync::CancellationToken;
@@ -1,13 +1,22 @@
 // protocols\mdns\src\name.rs
-use std::io::Read;
-use bytes::Buf;
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Marks a length byte as the first half of a compression pointer (RFC 1035 §4.1.4).
+const POINTER_TAG: u8 = 0xC0;
+
+/// Largest message offset a compression pointer can hold (14 bits).
+const MAX_POINTER_OFFSET: usize = 0x3FFF;
 
 /// Represents a DNS name, composed of multiple labels.
 ///
 /// A `DnsName` provides methods for creating, writing, and parsing DNS names,
 /// along with utilities for validation and formatting.
-#[derive(Clone, PartialEq, Debug)]
+///
+/// Names compare and hash case-insensitively over ASCII, as DNS requires (RFC 1035 §2.3.3);
+/// the original spelling is kept for display and on the wire.
+#[derive(Clone, Debug)]
 pub struct DnsName {
     labels: Vec<String>,
 }
@@ -15,18 +24,18 @@ pub struct DnsName {
 impl DnsName {
     /// Creates a new `DnsName` from a string.
     ///
+    /// Backslash escapes are honored, so `My\.Printer._ipp._tcp.local` has the single
+    /// label `My.Printer` in front of the service type.
+    ///
     /// # Arguments
     /// * `name` - The DNS name as a string.
     ///
     /// # Returns
     /// * `Ok(DnsName)` - If the name is valid.
-    /// * `Err(String)` - If any label in the name exceeds 63 characters.
+    /// * `Err(String)` - If any label in the name exceeds 63 characters or an escape is
+    ///   malformed.
     pub fn new(name: &str) -> Result<Self, String> {
-        let labels: Vec<String> = name
-            .split('.')
-            .filter(|label| !label.is_empty())
-            .map(|label| label.to_string())
-            .collect();
+        let labels = split_escaped_labels(name)?;
 
         // Validate label lengths
         for label in &labels {
@@ -35,7 +44,29 @@ impl DnsName {
             }
         }
 
-        Ok(DnsName { labels })
+        let name = DnsName { labels };
+        let wire_len = name.encoded_len();
+        if wire_len > 255 {
+            return Err(format!("Name exceeds 255 bytes ({} bytes encoded)", wire_len));
+        }
+
+        Ok(name)
+    }
+
+    /// Returns the root name, which has no labels and is written as a single zero byte.
+    pub const fn root() -> Self {
+        DnsName { labels: Vec::new() }
+    }
+
+    /// Returns the individual labels that make up the DNS name.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Returns the size of the name in wire format without compression.
+    pub fn encoded_len(&self) -> usize {
+        // Each label costs its length plus a length byte, and the name ends in a zero byte.
+        self.labels.iter().map(|label| label.len() + 1).sum::<usize>() + 1
     }
 
     /// Writes the DNS name into a buffer in DNS wire format.
@@ -50,6 +81,29 @@ impl DnsName {
         buffer.push(0x00); // End of the domain name
     }
 
+    /// Writes the DNS name into a message, replacing its longest suffix already written
+    /// there with a compression pointer.
+    ///
+    /// # Arguments
+    /// * `buffer` - The message being built; names must be written at their final offset.
+    /// * `compressor` - The names written to `buffer` so far.
+    pub(crate) fn write_compressed(&self, buffer: &mut Vec<u8>, compressor: &mut NameCompressor) {
+        for (index, label) in self.labels.iter().enumerate() {
+            let suffix = &self.labels[index..];
+            if let Some(&offset) = compressor.offsets.get(suffix) {
+                let pointer = (POINTER_TAG as u16) << 8 | offset as u16;
+                buffer.extend_from_slice(&pointer.to_be_bytes());
+                return;
+            }
+            if compressor.enabled && buffer.len() <= MAX_POINTER_OFFSET {
+                compressor.offsets.insert(suffix.to_vec(), buffer.len());
+            }
+            buffer.push(label.len() as u8);
+            buffer.extend_from_slice(label.as_bytes());
+        }
+        buffer.push(0x00); // End of the domain name
+    }
+
     /// Parses a `DnsName` from a cursor containing DNS wire format data.
     ///
     /// # Arguments
@@ -58,24 +112,155 @@ impl DnsName {
     /// # Returns
     /// * `Ok(DnsName)` - If parsing succeeds.
     /// * `Err(Box<dyn std::error::Error>)` - If parsing fails.
+    ///
+    /// Compression pointers are followed; the cursor ends up right after the name as it
+    /// appears in place. Each pointer must point before the previous one, so a malformed
+    /// packet cannot send parsing into a loop.
     pub fn parse(cursor: &mut std::io::Cursor<&[u8]>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let data = *cursor.get_ref();
+        let mut pos = cursor.position() as usize;
+        let mut resume_at = None;
+        let mut pointer_limit = pos;
         let mut labels = Vec::new();
         loop {
-            let len = cursor.get_u8();
+            let len = *data.get(pos).ok_or("Name runs past the end of the packet")?;
+            if len & POINTER_TAG == POINTER_TAG {
+                let low = *data.get(pos + 1).ok_or("Truncated compression pointer")?;
+                let target = ((len & !POINTER_TAG) as usize) << 8 | low as usize;
+                if target >= pointer_limit {
+                    return Err("Compression pointer does not point backwards".into());
+                }
+                resume_at.get_or_insert(pos + 2);
+                pointer_limit = target;
+                pos = target;
+                continue;
+            }
+            if len & POINTER_TAG != 0 {
+                return Err(format!("Unsupported label type 0x{:02x}", len).into());
+            }
+            pos += 1;
             if len == 0 {
                 break;
             }
-            let mut label = vec![0; len as usize];
-            cursor.read_exact(&mut label)?;
-            labels.push(String::from_utf8(label)?);
+            let label = data
+                .get(pos..pos + len as usize)
+                .ok_or("Label runs past the end of the packet")?;
+            labels.push(String::from_utf8(label.to_vec())?);
+            pos += len as usize;
         }
+        cursor.set_position(resume_at.unwrap_or(pos) as u64);
         Ok(DnsName { labels })
     }
 }
 
+impl PartialEq for DnsName {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels.len() == other.labels.len()
+            && self
+                .labels
+                .iter()
+                .zip(&other.labels)
+                .all(|(ours, theirs)| ours.eq_ignore_ascii_case(theirs))
+    }
+}
+
+impl Eq for DnsName {}
+
+impl Hash for DnsName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.labels.len());
+        for label in &self.labels {
+            for byte in label.bytes() {
+                state.write_u8(byte.to_ascii_lowercase());
+            }
+            // Terminate each label so ["ab", "c"] and ["a", "bc"] hash apart.
+            state.write_u8(0);
+        }
+    }
+}
+
 impl fmt::Display for DnsName {
-    /// Formats the DNS name as a human-readable string.
+    /// Formats the DNS name as a human-readable string, escaping dots and backslashes
+    /// inside labels so the result parses back into the same name.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.labels.join("."))
+        let labels: Vec<String> = self.labels.iter().map(|label| escape_label(label)).collect();
+        write!(f, "{}", labels.join("."))
+    }
+}
+
+/// Escapes the backslashes and dots of a label for the presentation form of a name.
+pub(crate) fn escape_label(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('.', "\\.")
+}
+
+/// Splits a presentation-format name into unescaped labels. Decimal escapes (`\DDD`)
+/// above 127 are rejected.
+pub(crate) fn split_escaped_labels(name: &str) -> Result<Vec<String>, String> {
+    let mut labels = Vec::new();
+    let mut current = String::new();
+    let mut chars = name.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let next = chars
+                    .next()
+                    .ok_or_else(|| "Trailing escape character".to_string())?;
+                if next.is_ascii_digit() {
+                    let mut digits = String::from(next);
+                    for _ in 0..2 {
+                        match chars.next() {
+                            Some(d) if d.is_ascii_digit() => digits.push(d),
+                            _ => return Err(format!("Invalid decimal escape in '{}'", name)),
+                        }
+                    }
+                    // Labels are strings, so only escapes of single-byte (ASCII) characters
+                    // keep their length on the wire.
+                    let value = digits
+                        .parse::<u8>()
+                        .ok()
+                        .filter(u8::is_ascii)
+                        .ok_or_else(|| format!("Decimal escape '\\{}' is not ASCII", digits))?;
+                    current.push(value as char);
+                } else {
+                    current.push(next);
+                }
+            }
+            '.' => {
+                if !current.is_empty() {
+                    labels.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        labels.push(current);
+    }
+
+    Ok(labels)
+}
+
+/// Remembers where names were written in a message so later names can point back to a
+/// shared suffix instead of repeating it (RFC 1035 §4.1.4).
+#[derive(Debug, Default)]
+pub(crate) struct NameCompressor {
+    enabled: bool,
+    offsets: HashMap<Vec<String>, usize>,
+}
+
+impl NameCompressor {
+    /// Creates a compressor for a message whose first byte is at offset 0.
+    pub(crate) fn new() -> Self {
+        NameCompressor {
+            enabled: true,
+            offsets: HashMap::new(),
+        }
+    }
+
+    /// Creates a compressor that writes every name in full, for buffers that are not a
+    /// complete message.
+    pub(crate) fn disabled() -> Self {
+        NameCompressor::default()
     }
 }
@@ -1,12 +1,78 @@
 // protocols\mdns\src\packet.rs
-use crate::{record::DnsRecord,name::DnsName};
+use crate::{record::{DnsRecord, UnsupportedRecordType},name::{DnsName, NameCompressor}};
+use crate::MdnsError;
 use bytes::Buf;
+use tracing::trace;
+
+/// Size of the fixed DNS message header.
+const HEADER_LEN: usize = 12;
+
+/// Largest encoded size of a DNS name (RFC 1035 §3.1).
+const MAX_NAME_LEN: usize = 255;
+
+/// QR: set in responses, clear in queries.
+const RESPONSE_BIT: u16 = 0x8000;
+/// OPCODE: the kind of query, 0 for a standard query (4 bits).
+const OPCODE_MASK: u16 = 0x7800;
+/// AA: set in responses from the owner of the records.
+const AUTHORITATIVE_BIT: u16 = 0x0400;
+/// TC: set in a query whose known answers continue in the next packet (RFC 6762 §7.2).
+const TRUNCATED_BIT: u16 = 0x0200;
+/// RCODE: the response code, 0 for no error (4 bits).
+const RCODE_MASK: u16 = 0x000F;
+
+/// The id and flags of a DNS message header, with typed access to the flag bits.
+///
+/// The section counts are not kept here; they follow from the sections of the packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DnsHeader {
+    pub id: u16,
+    pub flags: u16,
+}
+
+impl DnsHeader {
+    /// Returns the header of a standard query: every flag clear, as mDNS queries carry.
+    pub const fn query() -> Self {
+        DnsHeader { id: 0, flags: 0 }
+    }
+
+    /// Returns the header of an authoritative response, which every mDNS response is
+    /// (RFC 6762 §18.4).
+    pub const fn response() -> Self {
+        DnsHeader { id: 0, flags: RESPONSE_BIT | AUTHORITATIVE_BIT }
+    }
+
+    /// Returns whether the message is a response rather than a query.
+    pub const fn is_response(&self) -> bool {
+        self.flags & RESPONSE_BIT != 0
+    }
+
+    /// Returns whether the responder claims to own the records it answers with.
+    pub const fn is_authoritative(&self) -> bool {
+        self.flags & AUTHORITATIVE_BIT != 0
+    }
+
+    /// Returns whether the message is truncated.
+    pub const fn is_truncated(&self) -> bool {
+        self.flags & TRUNCATED_BIT != 0
+    }
+
+    /// Returns the opcode, `0` for a standard query.
+    pub const fn opcode(&self) -> u8 {
+        ((self.flags & OPCODE_MASK) >> 11) as u8
+    }
+
+    /// Returns the response code, `0` for no error.
+    pub const fn rcode(&self) -> u8 {
+        (self.flags & RCODE_MASK) as u8
+    }
+}
 
 /// Represents a DNS packet in the mDNS protocol.
 ///
 /// A `DnsPacket` contains the header fields and the various sections of a DNS packet, such as
 /// questions, answers, authorities, and additional records.
-#[derive(Debug,Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DnsPacket {
     pub id: u16,
     pub flags: u16,
@@ -24,7 +90,7 @@ impl DnsPacket {
     pub fn new() -> Self {
         DnsPacket {
             id: 0,
-            flags: 0x8400, // Standard Query Response, Authoritative Answer
+            flags: DnsHeader::response().flags,
             questions: Vec::new(),
             answers: Vec::new(),
             authorities: Vec::new(),
@@ -32,8 +98,38 @@ impl DnsPacket {
         }
     }
 
+    /// Creates an empty packet with the given header.
+    pub fn with_header(header: DnsHeader) -> Self {
+        let mut packet = DnsPacket::new();
+        packet.set_header(header);
+        packet
+    }
+
+    /// Returns the packet's id and flags as a typed header.
+    pub fn header(&self) -> DnsHeader {
+        DnsHeader { id: self.id, flags: self.flags }
+    }
+
+    /// Replaces the packet's id and flags with those of `header`.
+    pub fn set_header(&mut self, header: DnsHeader) {
+        self.id = header.id;
+        self.flags = header.flags;
+    }
+
+    /// Returns whether the packet is a response rather than a query.
+    pub fn is_response(&self) -> bool {
+        self.header().is_response()
+    }
+
     /// Serializes the `DnsPacket` into a byte buffer suitable for transmission.
     ///
+    /// Names repeated across the packet are compressed into pointers to their first
+    /// occurrence (RFC 1035 §4.1.4).
+    ///
+    /// The header counts are taken from the section lengths at the time of the call, so
+    /// sections can be changed freely until then. A section can hold at most 65535
+    /// entries; debug builds assert that the counts written match the sections.
+    ///
     /// # Returns
     /// * `Vec<u8>` - The serialized byte representation of the DNS packet.
     pub fn serialize(&self) -> Vec<u8> {
@@ -41,30 +137,55 @@ impl DnsPacket {
         // Serialize header
         buffer.extend_from_slice(&self.id.to_be_bytes());
         buffer.extend_from_slice(&self.flags.to_be_bytes());
-        buffer.extend_from_slice(&(self.questions.len() as u16).to_be_bytes());
-        buffer.extend_from_slice(&(self.answers.len() as u16).to_be_bytes());
-        buffer.extend_from_slice(&(self.authorities.len() as u16).to_be_bytes());
-        buffer.extend_from_slice(&(self.additionals.len() as u16).to_be_bytes());
+        for count in self.section_counts() {
+            buffer.extend_from_slice(&count.to_be_bytes());
+        }
+
+        let mut compressor = NameCompressor::new();
 
         // Serialize questions
         for question in &self.questions {
-            question.write(&mut buffer);
+            question.write_compressed(&mut buffer, &mut compressor);
         }
 
         // Serialize records
         for record in &self.answers {
-            record.write(&mut buffer);
+            record.write_compressed(&mut buffer, &mut compressor);
         }
         for record in &self.authorities {
-            record.write(&mut buffer);
+            record.write_compressed(&mut buffer, &mut compressor);
         }
         for record in &self.additionals {
-            record.write(&mut buffer);
+            record.write_compressed(&mut buffer, &mut compressor);
         }
 
+        debug_assert_eq!(
+            header_counts(&buffer),
+            [
+                self.questions.len(),
+                self.answers.len(),
+                self.authorities.len(),
+                self.additionals.len(),
+            ],
+            "Header counts must match the serialized sections"
+        );
         buffer
     }
 
+    /// Returns the question, answer, authority, and additional counts for the header.
+    fn section_counts(&self) -> [u16; 4] {
+        [
+            self.questions.len(),
+            self.answers.len(),
+            self.authorities.len(),
+            self.additionals.len(),
+        ]
+        .map(|len| {
+            debug_assert!(len <= usize::from(u16::MAX), "Section of {} entries overflows its count", len);
+            len as u16
+        })
+    }
+
     /// Parses a `DnsPacket` from a byte buffer.
     ///
     /// # Arguments
@@ -73,9 +194,15 @@ impl DnsPacket {
     /// # Returns
     /// * `Ok(DnsPacket)` - If parsing succeeds.
     /// * `Err(Box<dyn std::error::Error>)` - If parsing fails.
+    ///
+    /// Records of types this crate does not model are skipped. Every section must hold
+    /// as many entries as its header count announces, or the packet is rejected.
     pub fn parse(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < HEADER_LEN {
+            return Err(format!("Packet of {} bytes is shorter than a DNS header", data.len()).into());
+        }
         let mut cursor = std::io::Cursor::new(data);
-    
+
         // Parse the header
         let id = cursor.get_u16();
         let flags = cursor.get_u16();
@@ -83,47 +210,19 @@ impl DnsPacket {
         let ancount = cursor.get_u16();
         let nscount = cursor.get_u16();
         let arcount = cursor.get_u16();
-    
+
         let mut questions = Vec::new();
-        for _ in 0..qdcount {
-            if let Ok(question) = DnsQuestion::parse(&mut cursor) {
-                questions.push(question);
-            } else {
-                eprintln!("Failed to parse a question section");
-                break; // Exit the loop gracefully if parsing fails
-            }
-        }
-    
-        let mut answers = Vec::new();
-        for _ in 0..ancount {
-            if let Ok(record) = DnsRecord::parse(&mut cursor) {
-                answers.push(record);
-            } else {
-                eprintln!("Failed to parse an answer section");
-                break;
-            }
+        for index in 0..qdcount {
+            let question = DnsQuestion::parse(&mut cursor).map_err(|err| {
+                format!("Question {} of {}: {}", index + 1, qdcount, err)
+            })?;
+            questions.push(question);
         }
-    
-        let mut authorities = Vec::new();
-        for _ in 0..nscount {
-            if let Ok(record) = DnsRecord::parse(&mut cursor) {
-                authorities.push(record);
-            } else {
-                eprintln!("Failed to parse an authority section");
-                break;
-            }
-        }
-    
-        let mut additionals = Vec::new();
-        for _ in 0..arcount {
-            if let Ok(record) = DnsRecord::parse(&mut cursor) {
-                additionals.push(record);
-            } else {
-                eprintln!("Failed to parse an additional section");
-                break;
-            }
-        }
-    
+
+        let answers = parse_section(&mut cursor, ancount, "answer")?;
+        let authorities = parse_section(&mut cursor, nscount, "authority")?;
+        let additionals = parse_section(&mut cursor, arcount, "additional")?;
+
         Ok(DnsPacket {
             id,
             flags,
@@ -133,19 +232,158 @@ impl DnsPacket {
             additionals,
         })
     }
+
+    /// Checks that the packet is fit to act on: every section fits its 16-bit header
+    /// count, every name other than an OPT owner is non-empty and at most 255 bytes
+    /// encoded, questions ask for class IN (or ANY), and TXT and OPT data is made of
+    /// complete length-prefixed entries.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the packet is well-formed.
+    /// * `Err(MdnsError::PacketError)` - Describing the first problem found.
+    pub fn validate(&self) -> Result<(), MdnsError> {
+        let sections = [
+            ("question", self.questions.len()),
+            ("answer", self.answers.len()),
+            ("authority", self.authorities.len()),
+            ("additional", self.additionals.len()),
+        ];
+        for (section, count) in sections {
+            if count > u16::MAX as usize {
+                return Err(MdnsError::PacketError(format!(
+                    "{} {} entries exceed the header count limit",
+                    count, section
+                )));
+            }
+        }
+
+        for question in &self.questions {
+            validate_name(&question.qname)?;
+            if question.qclass != 1 && question.qclass != 255 {
+                return Err(MdnsError::PacketError(format!(
+                    "Question for {} has unsupported class {}",
+                    question.qname, question.qclass
+                )));
+            }
+        }
+
+        let records = self.answers.iter().chain(&self.authorities).chain(&self.additionals);
+        for record in records {
+            match record {
+                DnsRecord::A { name, .. } | DnsRecord::AAAA { name, .. } => validate_name(name)?,
+                DnsRecord::PTR { name, ptr_name, .. } => {
+                    validate_name(name)?;
+                    validate_name(ptr_name)?;
+                }
+                DnsRecord::SRV { name, target, .. } => {
+                    validate_name(name)?;
+                    validate_name(target)?;
+                }
+                DnsRecord::TXT { name, txt_data, .. } => {
+                    validate_name(name)?;
+                    validate_length_prefixed(txt_data, 1).map_err(|err| {
+                        MdnsError::PacketError(format!("TXT record of {}: {}", name, err))
+                    })?;
+                }
+                DnsRecord::NSEC { name, next_domain, .. } => {
+                    validate_name(name)?;
+                    validate_name(next_domain)?;
+                }
+                DnsRecord::OPT { options, .. } => {
+                    validate_length_prefixed(options, 4)
+                        .map_err(|err| MdnsError::PacketError(format!("OPT record: {}", err)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the four section counts back from a serialized header.
+fn header_counts(buffer: &[u8]) -> [usize; 4] {
+    [4, 6, 8, 10].map(|offset| usize::from(u16::from_be_bytes([buffer[offset], buffer[offset + 1]])))
+}
+
+/// Parses `count` records of one section, skipping records of unsupported types.
+fn parse_section(
+    cursor: &mut std::io::Cursor<&[u8]>,
+    count: u16,
+    section: &str,
+) -> Result<Vec<DnsRecord>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut records = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        match DnsRecord::parse(cursor) {
+            Ok(record) => records.push(record),
+            Err(err) if err.is::<UnsupportedRecordType>() => {
+                trace!("Skipping {} record {}: {}", section, index + 1, err);
+            }
+            Err(err) => {
+                return Err(format!("{} record {} of {}: {}", section, index + 1, count, err).into())
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Checks that a name has at least one label and fits in 255 bytes once encoded.
+fn validate_name(name: &DnsName) -> Result<(), MdnsError> {
+    if name.labels().is_empty() {
+        return Err(MdnsError::PacketError("Empty name".to_string()));
+    }
+    let encoded_len = name.encoded_len();
+    if encoded_len > MAX_NAME_LEN {
+        return Err(MdnsError::PacketError(format!(
+            "Name {} is {} bytes long, over the 255 byte limit",
+            name, encoded_len
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that `data` is a sequence of entries, each a length field of `prefix_len`
+/// bytes (the length in its last two bytes, or its only byte) followed by that many bytes.
+fn validate_length_prefixed(data: &[u8], prefix_len: usize) -> Result<(), String> {
+    let mut rest = data;
+    while !rest.is_empty() {
+        if rest.len() < prefix_len {
+            return Err("truncated entry header".to_string());
+        }
+        let len = match prefix_len {
+            1 => rest[0] as usize,
+            _ => u16::from_be_bytes([rest[prefix_len - 2], rest[prefix_len - 1]]) as usize,
+        };
+        rest = rest
+            .get(prefix_len + len..)
+            .ok_or_else(|| format!("entry of {} bytes runs past the end of the data", len))?;
+    }
+    Ok(())
+}
+
+impl Default for DnsPacket {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Represents a DNS question in the mDNS protocol.
 ///
 /// A `DnsQuestion` consists of a domain name, query type, and query class.
+///
+/// The top bit of the class on the wire is the mDNS "QU" bit (RFC 6762 §5.4): it asks
+/// for the answer to be unicast to the querier. It is exposed as `unicast_response`
+/// and kept out of `qclass`.
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DnsQuestion {
     pub qname: DnsName,
     pub qtype: u16,
     pub qclass: u16,
+    pub unicast_response: bool,
 }
 
+/// The top bit of the question class, requesting a unicast response.
+const UNICAST_RESPONSE_BIT: u16 = 0x8000;
+
 impl DnsQuestion {
     /// Parses a `DnsQuestion` from a cursor containing DNS wire format data.
     ///
@@ -157,14 +395,33 @@ impl DnsQuestion {
     /// * `Err(Box<dyn std::error::Error>)` - If parsing fails.
     pub fn parse(cursor: &mut std::io::Cursor<&[u8]>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let qname = DnsName::parse(cursor)?;
+        if cursor.remaining() < 4 {
+            return Err("Truncated question".into());
+        }
         let qtype = cursor.get_u16();
-        let qclass = cursor.get_u16();
-        Ok(DnsQuestion { qname, qtype, qclass })
+        let class = cursor.get_u16();
+        Ok(DnsQuestion {
+            qname,
+            qtype,
+            qclass: class & !UNICAST_RESPONSE_BIT,
+            unicast_response: class & UNICAST_RESPONSE_BIT != 0,
+        })
     }
 
     pub fn write(&self, buffer: &mut Vec<u8>) {
-        self.qname.write(buffer);
+        self.write_compressed(buffer, &mut NameCompressor::disabled());
+    }
+
+    /// Writes the question into a message, compressing its name against the names
+    /// written before it.
+    pub(crate) fn write_compressed(&self, buffer: &mut Vec<u8>, compressor: &mut NameCompressor) {
+        let class = if self.unicast_response {
+            self.qclass | UNICAST_RESPONSE_BIT
+        } else {
+            self.qclass
+        };
+        self.qname.write_compressed(buffer, compressor);
         buffer.extend_from_slice(&self.qtype.to_be_bytes());
-        buffer.extend_from_slice(&self.qclass.to_be_bytes());
+        buffer.extend_from_slice(&class.to_be_bytes());
     }
 }
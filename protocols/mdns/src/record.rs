@@ -1,26 +1,141 @@
 // protocols\mdns\src\record.rs
 
 // /protocols/mdns/record.rs
-use crate::name::DnsName;
+use crate::name::{DnsName, NameCompressor};
+use std::fmt;
 use std::io::Read;
 use bytes::Buf;
 
+/// Top bit of the record class, signalling a cache-flush (unique) record.
+const CACHE_FLUSH_BIT: u16 = 0x8000;
+
+/// The DNSSEC OK bit within the packed TTL field of an OPT record.
+const DNSSEC_OK_BIT: u32 = 0x8000;
+
+/// Size of the TYPE, CLASS, TTL, and RDLENGTH fields that follow a record's owner name.
+const RECORD_HEADER_LEN: usize = 10;
+
+/// Error returned by `DnsRecord::parse` for a well-formed record of a type this crate does
+/// not model (e.g. HINFO). Its RDATA has been skipped, so parsing can carry on after it.
+#[derive(Debug)]
+pub struct UnsupportedRecordType(pub u16);
+
+impl fmt::Display for UnsupportedRecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unsupported record type {}", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedRecordType {}
+
+/// Largest bitmap length of one window block in an NSEC type bitmap (RFC 4034 §4.1.2).
+const MAX_NSEC_BITMAP_LEN: usize = 32;
+
+/// Owner name of every OPT record.
+static ROOT_NAME: DnsName = DnsName::root();
+
+/// Reserves the RDLENGTH field and returns its offset, for RDATA written in place.
+fn begin_rdata(buffer: &mut Vec<u8>) -> usize {
+    let rdlength_at = buffer.len();
+    buffer.extend_from_slice(&0u16.to_be_bytes());
+    rdlength_at
+}
+
+/// Fills in the RDLENGTH field reserved by `begin_rdata` once the RDATA is written.
+fn end_rdata(buffer: &mut [u8], rdlength_at: usize) {
+    let rdlength = (buffer.len() - rdlength_at - 2) as u16;
+    buffer[rdlength_at..rdlength_at + 2].copy_from_slice(&rdlength.to_be_bytes());
+}
+
+/// Returns the wire class (IN) for a record, with the cache-flush bit set if requested.
+fn record_class(cache_flush: bool) -> u16 {
+    if cache_flush {
+        1 | CACHE_FLUSH_BIT
+    } else {
+        1
+    }
+}
+
+/// Encodes record types as the window blocks of an NSEC type bitmap (RFC 4034 §4.1.2).
+fn encode_type_bitmap(types: &[u16], buffer: &mut Vec<u8>) {
+    let mut types = types.to_vec();
+    types.sort_unstable();
+    types.dedup();
+    let mut index = 0;
+    while index < types.len() {
+        let window = (types[index] >> 8) as u8;
+        let mut bitmap = [0u8; MAX_NSEC_BITMAP_LEN];
+        let mut len = 0;
+        while index < types.len() && (types[index] >> 8) as u8 == window {
+            let low = (types[index] & 0xFF) as usize;
+            bitmap[low / 8] |= 0x80 >> (low % 8);
+            len = low / 8 + 1;
+            index += 1;
+        }
+        buffer.push(window);
+        buffer.push(len as u8);
+        buffer.extend_from_slice(&bitmap[..len]);
+    }
+}
+
+/// Decodes the window blocks of an NSEC type bitmap into the record types it lists.
+fn decode_type_bitmap(mut data: &[u8]) -> Result<Vec<u16>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut types = Vec::new();
+    let mut previous_window = None;
+    while !data.is_empty() {
+        if data.len() < 2 {
+            return Err("Truncated NSEC window block".into());
+        }
+        let (window, len) = (data[0], data[1] as usize);
+        if len == 0 || len > MAX_NSEC_BITMAP_LEN || data.len() < 2 + len {
+            return Err(format!("Invalid NSEC bitmap length {}", len).into());
+        }
+        if previous_window.is_some_and(|previous| window <= previous) {
+            return Err("NSEC window blocks are out of order".into());
+        }
+        for (byte_index, byte) in data[2..2 + len].iter().enumerate() {
+            for bit in 0..8 {
+                if byte & (0x80 >> bit) != 0 {
+                    types.push(u16::from(window) << 8 | (byte_index * 8 + bit) as u16);
+                }
+            }
+        }
+        previous_window = Some(window);
+        data = &data[2 + len..];
+    }
+    Ok(types)
+}
+
 /// Represents DNS resource records (RR) used in the mDNS protocol.
 ///
-/// `DnsRecord` supports multiple record types such as A, PTR, SRV, and TXT.
-#[derive(Debug, Clone)]
+/// `DnsRecord` supports multiple record types such as A, AAAA, PTR, SRV, TXT, and NSEC,
+/// plus the EDNS0 OPT pseudo-record.
+///
+/// Every resource record variant carries a `cache_flush` flag, which maps to the top bit of
+/// the record class on the wire (RFC 6762 §10.2) and tells receivers to replace, rather than
+/// add to, their cached records of the same name and type.
+#[derive(Debug, Clone, PartialEq)]
 pub enum DnsRecord {
     /// A Record - Maps a name to an IPv4 address.
     A {
         name: DnsName,
         ttl: u32,
         ip: [u8; 4],
+        cache_flush: bool,
+    },
+    /// AAAA Record - Maps a name to an IPv6 address.
+    AAAA {
+        name: DnsName,
+        ttl: u32,
+        ip: [u8; 16],
+        cache_flush: bool,
     },
     /// PTR Record - Maps a name to another name.
     PTR {
         name: DnsName,
         ttl: u32,
         ptr_name: DnsName,
+        cache_flush: bool,
     },
     /// SRV Record - Specifies the location of a service.
     SRV {
@@ -30,40 +145,148 @@ pub enum DnsRecord {
         weight: u16,
         port: u16,
         target: DnsName,
+        cache_flush: bool,
     },
     /// TXT Record - Contains text data.
+    ///
+    /// `txt_data` is the raw RDATA: a sequence of length-prefixed character strings,
+    /// conventionally `key=value` pairs (RFC 6763 §6).
     TXT {
         name: DnsName,
         ttl: u32,
         txt_data: Vec<u8>,
+        cache_flush: bool,
+    },
+    /// NSEC Record - Lists the record types that exist for a name, so a querier knows
+    /// the others do not (RFC 6762 §6.1).
+    ///
+    /// `types` holds the record type numbers of the type bitmap, in ascending order when
+    /// parsed. mDNS responders set `next_domain` to the owner name itself.
+    NSEC {
+        name: DnsName,
+        ttl: u32,
+        next_domain: DnsName,
+        types: Vec<u16>,
+        cache_flush: bool,
+    },
+    /// OPT Pseudo-Record (EDNS0) - Advertises the sender's maximum UDP payload size.
+    ///
+    /// Only valid in the additional section. Its owner is always the root name, and the
+    /// class and TTL fields carry the payload size and extended flags (RFC 6891 §6.1).
+    OPT {
+        udp_payload_size: u16,
+        extended_rcode: u8,
+        version: u8,
+        dnssec_ok: bool,
+        options: Vec<u8>, // Raw option TLVs.
     },
     // Additional record types can be added as needed.
 }
 
 impl DnsRecord {
+    /// Returns the owner name of the record.
+    pub fn name(&self) -> &DnsName {
+        match self {
+            DnsRecord::A { name, .. }
+            | DnsRecord::AAAA { name, .. }
+            | DnsRecord::PTR { name, .. }
+            | DnsRecord::SRV { name, .. }
+            | DnsRecord::TXT { name, .. }
+            | DnsRecord::NSEC { name, .. } => name,
+            DnsRecord::OPT { .. } => &ROOT_NAME,
+        }
+    }
+
+    /// Returns the record's TYPE code, e.g. `33` for SRV.
+    pub fn record_type(&self) -> u16 {
+        match self {
+            DnsRecord::A { .. } => 1,
+            DnsRecord::PTR { .. } => 12,
+            DnsRecord::TXT { .. } => 16,
+            DnsRecord::AAAA { .. } => 28,
+            DnsRecord::SRV { .. } => 33,
+            DnsRecord::OPT { .. } => 41,
+            DnsRecord::NSEC { .. } => 47,
+        }
+    }
+
+    /// Returns whether the record carries the cache-flush bit.
+    pub fn cache_flush(&self) -> bool {
+        match self {
+            DnsRecord::A { cache_flush, .. }
+            | DnsRecord::AAAA { cache_flush, .. }
+            | DnsRecord::PTR { cache_flush, .. }
+            | DnsRecord::SRV { cache_flush, .. }
+            | DnsRecord::TXT { cache_flush, .. }
+            | DnsRecord::NSEC { cache_flush, .. } => *cache_flush,
+            DnsRecord::OPT { .. } => false,
+        }
+    }
+
+    /// Sets or clears the cache-flush bit. OPT records have none and are left unchanged.
+    pub fn set_cache_flush(&mut self, flush: bool) {
+        match self {
+            DnsRecord::A { cache_flush, .. }
+            | DnsRecord::AAAA { cache_flush, .. }
+            | DnsRecord::PTR { cache_flush, .. }
+            | DnsRecord::SRV { cache_flush, .. }
+            | DnsRecord::TXT { cache_flush, .. }
+            | DnsRecord::NSEC { cache_flush, .. } => *cache_flush = flush,
+            DnsRecord::OPT { .. } => {}
+        }
+    }
+
+    /// Returns the time-to-live of the record in seconds.
+    ///
+    /// OPT records are never cached and always report `0`.
+    pub fn ttl(&self) -> u32 {
+        match self {
+            DnsRecord::A { ttl, .. }
+            | DnsRecord::AAAA { ttl, .. }
+            | DnsRecord::PTR { ttl, .. }
+            | DnsRecord::SRV { ttl, .. }
+            | DnsRecord::TXT { ttl, .. }
+            | DnsRecord::NSEC { ttl, .. } => *ttl,
+            DnsRecord::OPT { .. } => 0,
+        }
+    }
+
     /// Writes the DNS record to a buffer in DNS wire format.
     ///
     /// # Arguments
     /// * `buffer` - A mutable vector to write the serialized DNS record.
     pub fn write(&self, buffer: &mut Vec<u8>) {
+        self.write_compressed(buffer, &mut NameCompressor::disabled());
+    }
+
+    /// Writes the DNS record into a message, compressing its owner name and the names
+    /// in its RDATA against the names written before it.
+    pub(crate) fn write_compressed(&self, buffer: &mut Vec<u8>, compressor: &mut NameCompressor) {
         match self {
-            DnsRecord::A { name, ttl, ip } => {
-                name.write(buffer);
+            DnsRecord::A { name, ttl, ip, cache_flush } => {
+                name.write_compressed(buffer, compressor);
                 buffer.extend_from_slice(&1u16.to_be_bytes()); // TYPE A
-                buffer.extend_from_slice(&1u16.to_be_bytes()); // CLASS IN
+                buffer.extend_from_slice(&record_class(*cache_flush).to_be_bytes()); // CLASS IN
                 buffer.extend_from_slice(&ttl.to_be_bytes());  // TTL
                 buffer.extend_from_slice(&4u16.to_be_bytes()); // RDLENGTH
                 buffer.extend_from_slice(ip);                 // RDATA (IPv4 address)
             }
-            DnsRecord::PTR { name, ttl, ptr_name } => {
-                name.write(buffer);
+            DnsRecord::AAAA { name, ttl, ip, cache_flush } => {
+                name.write_compressed(buffer, compressor);
+                buffer.extend_from_slice(&28u16.to_be_bytes()); // TYPE AAAA
+                buffer.extend_from_slice(&record_class(*cache_flush).to_be_bytes()); // CLASS IN
+                buffer.extend_from_slice(&ttl.to_be_bytes());   // TTL
+                buffer.extend_from_slice(&16u16.to_be_bytes()); // RDLENGTH
+                buffer.extend_from_slice(ip);                   // RDATA (IPv6 address)
+            }
+            DnsRecord::PTR { name, ttl, ptr_name, cache_flush } => {
+                name.write_compressed(buffer, compressor);
                 buffer.extend_from_slice(&12u16.to_be_bytes()); // TYPE PTR
-                buffer.extend_from_slice(&1u16.to_be_bytes());  // CLASS IN
+                buffer.extend_from_slice(&record_class(*cache_flush).to_be_bytes()); // CLASS IN
                 buffer.extend_from_slice(&ttl.to_be_bytes());   // TTL
-                let mut rdata = Vec::new();
-                ptr_name.write(&mut rdata);
-                buffer.extend_from_slice(&(rdata.len() as u16).to_be_bytes()); // RDLENGTH
-                buffer.extend_from_slice(&rdata);                              // RDATA
+                let rdlength_at = begin_rdata(buffer);
+                ptr_name.write_compressed(buffer, compressor); // RDATA
+                end_rdata(buffer, rdlength_at);
             }
             DnsRecord::SRV {
                 name,
@@ -72,33 +295,59 @@ impl DnsRecord {
                 weight,
                 port,
                 target,
+                cache_flush,
             } => {
-                name.write(buffer);
+                name.write_compressed(buffer, compressor);
                 buffer.extend_from_slice(&33u16.to_be_bytes()); // TYPE SRV
-                buffer.extend_from_slice(&1u16.to_be_bytes());  // CLASS IN
+                buffer.extend_from_slice(&record_class(*cache_flush).to_be_bytes()); // CLASS IN
                 buffer.extend_from_slice(&ttl.to_be_bytes());   // TTL
-                let mut rdata = Vec::new();
-                rdata.extend_from_slice(&priority.to_be_bytes());
-                rdata.extend_from_slice(&weight.to_be_bytes());
-                rdata.extend_from_slice(&port.to_be_bytes());
-                target.write(&mut rdata);
-                buffer.extend_from_slice(&(rdata.len() as u16).to_be_bytes()); // RDLENGTH
-                buffer.extend_from_slice(&rdata);                              // RDATA
+                let rdlength_at = begin_rdata(buffer);
+                buffer.extend_from_slice(&priority.to_be_bytes());
+                buffer.extend_from_slice(&weight.to_be_bytes());
+                buffer.extend_from_slice(&port.to_be_bytes());
+                target.write_compressed(buffer, compressor);
+                end_rdata(buffer, rdlength_at);
             }
-            DnsRecord::TXT { name, ttl, txt_data } => {
-                name.write(buffer);
+            DnsRecord::TXT { name, ttl, txt_data, cache_flush } => {
+                name.write_compressed(buffer, compressor);
                 buffer.extend_from_slice(&16u16.to_be_bytes()); // TYPE TXT
-                buffer.extend_from_slice(&1u16.to_be_bytes());  // CLASS IN
+                buffer.extend_from_slice(&record_class(*cache_flush).to_be_bytes()); // CLASS IN
                 buffer.extend_from_slice(&ttl.to_be_bytes());   // TTL
 
-                let mut rdata = Vec::new();
-                for txt_segment in txt_data.chunks(255) {
-                    rdata.push(txt_segment.len() as u8);
-                    rdata.extend_from_slice(txt_segment);
-                }
+                // An empty TXT record still holds a single empty string (RFC 6763 §6.1).
+                let rdata: &[u8] = if txt_data.is_empty() { &[0] } else { txt_data };
 
                 buffer.extend_from_slice(&(rdata.len() as u16).to_be_bytes()); // RDLENGTH
-                buffer.extend_from_slice(&rdata);                             // RDATA
+                buffer.extend_from_slice(rdata);                              // RDATA
+            }
+            DnsRecord::NSEC { name, ttl, next_domain, types, cache_flush } => {
+                name.write_compressed(buffer, compressor);
+                buffer.extend_from_slice(&47u16.to_be_bytes()); // TYPE NSEC
+                buffer.extend_from_slice(&record_class(*cache_flush).to_be_bytes()); // CLASS IN
+                buffer.extend_from_slice(&ttl.to_be_bytes());   // TTL
+                let rdlength_at = begin_rdata(buffer);
+                // The next domain name is never compressed (RFC 4034 §4.1.1).
+                next_domain.write(buffer);
+                encode_type_bitmap(types, buffer);
+                end_rdata(buffer, rdlength_at);
+            }
+            DnsRecord::OPT {
+                udp_payload_size,
+                extended_rcode,
+                version,
+                dnssec_ok,
+                options,
+            } => {
+                ROOT_NAME.write(buffer);
+                buffer.extend_from_slice(&41u16.to_be_bytes()); // TYPE OPT
+                buffer.extend_from_slice(&udp_payload_size.to_be_bytes()); // CLASS = payload size
+                let mut flags = (*extended_rcode as u32) << 24 | (*version as u32) << 16;
+                if *dnssec_ok {
+                    flags |= DNSSEC_OK_BIT;
+                }
+                buffer.extend_from_slice(&flags.to_be_bytes()); // TTL = extended RCODE and flags
+                buffer.extend_from_slice(&(options.len() as u16).to_be_bytes()); // RDLENGTH
+                buffer.extend_from_slice(options);                              // RDATA
             }
         }
     }
@@ -111,38 +360,110 @@ impl DnsRecord {
     /// # Returns
     /// * `Ok(DnsRecord)` - If parsing succeeds.
     /// * `Err(Box<dyn std::error::Error>)` - If parsing fails.
+    /// * `Err(UnsupportedRecordType)` - If the record is of an unknown type; its RDATA is
+    ///   skipped.
+    ///
+    /// Truncated records and RDATA that does not span exactly RDLENGTH bytes are errors.
     pub fn parse(cursor: &mut std::io::Cursor<&[u8]>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let name = DnsName::parse(cursor)?;
+        if cursor.remaining() < RECORD_HEADER_LEN {
+            return Err("Truncated record header".into());
+        }
         let rtype = cursor.get_u16();
-        let _rclass = cursor.get_u16();
+        let rclass = cursor.get_u16();
+        let cache_flush = rclass & CACHE_FLUSH_BIT != 0;
         let ttl = cursor.get_u32();
         let rdlength = cursor.get_u16();
+        if cursor.remaining() < rdlength as usize {
+            return Err("Record data runs past the end of the packet".into());
+        }
+
+        let rdata_end = cursor.position() + u64::from(rdlength);
+        let record = Self::parse_rdata(cursor, name, rtype, rclass, cache_flush, ttl, rdlength)?;
+        if cursor.position() != rdata_end {
+            return Err(format!(
+                "Record data of type {} does not match its length of {} bytes",
+                rtype, rdlength
+            )
+            .into());
+        }
+        Ok(record)
+    }
 
+    /// Parses the RDATA of a record whose header has been read.
+    fn parse_rdata(
+        cursor: &mut std::io::Cursor<&[u8]>,
+        name: DnsName,
+        rtype: u16,
+        rclass: u16,
+        cache_flush: bool,
+        ttl: u32,
+        rdlength: u16,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         match rtype {
             1 => { // A Record
                 let mut ip = [0u8; 4];
                 cursor.read_exact(&mut ip)?;
-                Ok(DnsRecord::A { name, ttl, ip })
+                Ok(DnsRecord::A { name, ttl, ip, cache_flush })
+            }
+            28 => { // AAAA Record
+                let mut ip = [0u8; 16];
+                cursor.read_exact(&mut ip)?;
+                Ok(DnsRecord::AAAA { name, ttl, ip, cache_flush })
             }
             12 => { // PTR Record
                 let ptr_name = DnsName::parse(cursor)?;
-                Ok(DnsRecord::PTR { name, ttl, ptr_name })
+                Ok(DnsRecord::PTR { name, ttl, ptr_name, cache_flush })
             }
             33 => { // SRV Record
+                if rdlength < 7 {
+                    return Err("SRV record data is too short".into());
+                }
                 let priority = cursor.get_u16();
                 let weight = cursor.get_u16();
                 let port = cursor.get_u16();
                 let target = DnsName::parse(cursor)?;
-                Ok(DnsRecord::SRV { name, ttl, priority, weight, port, target })
+                Ok(DnsRecord::SRV { name, ttl, priority, weight, port, target, cache_flush })
             }
             16 => { // TXT Record
                 let mut txt_data = vec![0; rdlength as usize];
                 cursor.read_exact(&mut txt_data)?;
-                Ok(DnsRecord::TXT { name, ttl, txt_data })
+                // A lone empty string is how an empty TXT record is written; undo that.
+                if txt_data == [0] {
+                    txt_data.clear();
+                }
+                Ok(DnsRecord::TXT { name, ttl, txt_data, cache_flush })
+            }
+            47 => { // NSEC Record
+                let rdata_start = cursor.position();
+                let next_domain = DnsName::parse(cursor)?;
+                let name_len = (cursor.position() - rdata_start) as usize;
+                let bitmap_len = (rdlength as usize)
+                    .checked_sub(name_len)
+                    .ok_or("NSEC next domain name runs past the record data")?;
+                let mut bitmap = vec![0; bitmap_len];
+                cursor.read_exact(&mut bitmap)?;
+                let types = decode_type_bitmap(&bitmap)?;
+                Ok(DnsRecord::NSEC { name, ttl, next_domain, types, cache_flush })
+            }
+            41 => { // OPT Pseudo-Record
+                if name != ROOT_NAME {
+                    cursor.advance(rdlength as usize);
+                    return Err("OPT record owner must be the root name".into());
+                }
+                let mut options = vec![0; rdlength as usize];
+                cursor.read_exact(&mut options)?;
+                Ok(DnsRecord::OPT {
+                    udp_payload_size: rclass,
+                    extended_rcode: (ttl >> 24) as u8,
+                    version: (ttl >> 16) as u8,
+                    dnssec_ok: ttl & DNSSEC_OK_BIT != 0,
+                    options,
+                })
             }
             _ => {
                 cursor.advance(rdlength as usize);
-                Err("Unknown record type".into())
+                Err(Box::new(UnsupportedRecordType(rtype)))
             }
         }
     }
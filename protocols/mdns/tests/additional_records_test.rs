@@ -0,0 +1,73 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsHeader, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::collections::HashMap;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    /// Asks a responder for `_http._tcp.local` and returns its reply.
+    async fn browse_reply() -> DnsPacket {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let responder = MdnsService::with_transport(transport.clone(), config);
+        responder
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                Some(HashMap::from([("path".to_string(), "/".to_string())])),
+            )
+            .await
+            .unwrap();
+
+        let mut query = DnsPacket::with_header(DnsHeader::query());
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        responder.process_query(&query, &peer).await;
+        transport.sent_packets().remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_browse_reply_puts_srv_txt_and_address_in_additionals() {
+        let reply = browse_reply().await;
+
+        let types = |records: &[DnsRecord]| records.iter().map(|r| r.record_type()).collect::<Vec<_>>();
+        assert_eq!(types(&reply.answers), vec![12]);
+        assert_eq!(types(&reply.additionals), vec![33, 16, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_additional_records_are_cached_like_answers() {
+        let reply = browse_reply().await;
+        let browser =
+            MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+
+        let src: SocketAddr = "192.168.1.10:5353".parse().unwrap();
+        browser.process_response(&reply, &src).await;
+
+        let discovered = browser
+            .registry
+            .get_discovered_service("Web._http._tcp.local")
+            .await
+            .expect("SRV from the additional section should be cached");
+        assert_eq!(discovered.port, 8080);
+        let node = browser.registry.get_node("web-host.local").await.unwrap();
+        assert_eq!(node.ip_address, "192.168.1.10");
+        let txt = browser.registry.get_txt("Web._http._tcp.local").await.unwrap();
+        assert_eq!(txt.entries, vec!["path=/".to_string()]);
+    }
+}
@@ -0,0 +1,50 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_expired_a_record_triggers_address_query() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::SRV {
+            name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 631,
+            target: DnsName::new("printer-host.local").unwrap(),
+            cache_flush: false,
+        });
+        response.answers.push(DnsRecord::A {
+            name: DnsName::new("printer-host.local").unwrap(),
+            ttl: 1,
+            ip: [192, 168, 1, 50],
+            cache_flush: false,
+        });
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+        service.process_response(&response, &src).await;
+
+        // While the A record is fresh there is nothing to refresh.
+        assert_eq!(service.refresh_expired_addresses().await.unwrap(), 0);
+        assert!(transport.sent().is_empty());
+
+        // Only the A record expires; the SRV record remains cached.
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(service.registry.list_discovered_services().await.len(), 1);
+
+        assert_eq!(service.refresh_expired_addresses().await.unwrap(), 1);
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].questions.len(), 1);
+        assert_eq!(sent[0].questions[0].qtype, 1, "An A query should be issued");
+        assert_eq!(sent[0].questions[0].qname.to_string(), "printer-host.local");
+    }
+}
@@ -0,0 +1,125 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{AdvertisePolicy, DnsRecord, MdnsConfig, MdnsService, ServiceRecord};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    const WIRED: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 10);
+    const WIRELESS: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 5);
+
+    /// Hides the printer from the wireless network and advertises the web server there
+    /// on another port.
+    struct GuestNetwork;
+
+    impl AdvertisePolicy for GuestNetwork {
+        fn adjust(&self, service: &ServiceRecord, interface: Ipv4Addr) -> Option<ServiceRecord> {
+            if interface != WIRELESS {
+                return Some(service.clone());
+            }
+            if service.id.starts_with("Printer") {
+                return None;
+            }
+            let mut adjusted = service.clone();
+            adjusted.port = 9090;
+            Some(adjusted)
+        }
+    }
+
+    async fn service_on_two_interfaces(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(WIRED),
+            interfaces: vec![WIRED, WIRELESS],
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        for (id, service_type, port) in [
+            ("Web._http._tcp.local", "_http._tcp.local", 8080),
+            ("Printer._ipp._tcp.local", "_ipp._tcp.local", 631),
+        ] {
+            service
+                .register_local_service(
+                    id.to_string(),
+                    service_type.to_string(),
+                    port,
+                    Some(120),
+                    "host.local".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        service
+    }
+
+    /// Returns the `(instance, port)` of every SRV record in `records`, sorted.
+    fn srv_ports(records: &[DnsRecord]) -> Vec<(String, u16)> {
+        let mut ports: Vec<(String, u16)> = records
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::SRV { name, port, .. } => Some((name.to_string(), *port)),
+                _ => None,
+            })
+            .collect();
+        ports.sort();
+        ports
+    }
+
+    #[tokio::test]
+    async fn test_policy_shapes_advertisements_per_interface() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_on_two_interfaces(transport.clone()).await;
+        service.set_advertise_policy(Arc::new(GuestNetwork));
+
+        service.advertise_services().await.unwrap();
+
+        let interfaces: Vec<Ipv4Addr> = transport.interface_sends().iter().map(|(i, _)| *i).collect();
+        assert_eq!(interfaces, vec![WIRED, WIRELESS]);
+        let packets = transport.sent_packets();
+        assert_eq!(
+            srv_ports(&packets[0].answers),
+            vec![
+                ("Printer._ipp._tcp.local".to_string(), 631),
+                ("Web._http._tcp.local".to_string(), 8080),
+            ]
+        );
+        assert_eq!(
+            srv_ports(&packets[1].answers),
+            vec![("Web._http._tcp.local".to_string(), 9090)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_policy_sends_the_same_packet_everywhere() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_on_two_interfaces(transport.clone()).await;
+
+        service.advertise_services().await.unwrap();
+
+        let packets = transport.sent_packets();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0], packets[1]);
+        assert_eq!(srv_ports(&packets[0].answers).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_policy_applies_to_announcements() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_on_two_interfaces(transport.clone()).await;
+        service.set_advertise_policy(Arc::new(GuestNetwork));
+
+        service
+            .announce_service("Printer._ipp._tcp.local")
+            .await
+            .unwrap();
+
+        // The printer is announced on the wired interface only.
+        assert!(!transport.interface_sends().is_empty());
+        assert!(transport
+            .interface_sends()
+            .iter()
+            .all(|(interface, _)| *interface == WIRED));
+    }
+}
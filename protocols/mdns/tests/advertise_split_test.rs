@@ -0,0 +1,56 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::collections::HashMap;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_large_advertisement_is_split_under_the_packet_limit() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        for i in 0..50 {
+            let txt = HashMap::from([("path".to_string(), format!("/service/number/{}", i))]);
+            service
+                .register_local_service(
+                    format!("Service {}._http._tcp.local", i),
+                    "_http._tcp.local".to_string(),
+                    8000 + i,
+                    Some(120),
+                    format!("host-{}.local", i % 5),
+                    Some(txt),
+                )
+                .await
+                .unwrap();
+        }
+
+        service.advertise_services().await.unwrap();
+
+        let sent = transport.sent();
+        assert!(sent.len() > 1, "50 services should not fit in one packet");
+        let mut srv_count = 0;
+        for (bytes, _) in &sent {
+            assert!(bytes.len() <= 1472, "packet of {} bytes exceeds the limit", bytes.len());
+            let packet = DnsPacket::parse(bytes).unwrap();
+            assert_eq!(packet.flags, 0x8400);
+            // Every service in a packet comes with its PTR, TXT, and host address.
+            for record in &packet.answers {
+                if let DnsRecord::SRV { name, target, .. } = record {
+                    srv_count += 1;
+                    let has = |pred: &dyn Fn(&DnsRecord) -> bool| packet.answers.iter().any(pred);
+                    assert!(has(&|r| matches!(r, DnsRecord::PTR { ptr_name, .. } if ptr_name == name)));
+                    assert!(has(&|r| matches!(r, DnsRecord::TXT { name: txt_name, .. } if txt_name == name)));
+                    assert!(has(&|r| matches!(r, DnsRecord::A { name: host, .. } if host == target)));
+                }
+            }
+        }
+        assert_eq!(srv_count, 50);
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn a_record_ips(packet: &DnsPacket) -> Vec<[u8; 4]> {
+        packet
+            .answers
+            .iter()
+            .chain(&packet.additionals)
+            .filter_map(|record| match record {
+                DnsRecord::A { ip, .. } => Some(*ip),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_advertised_address_overrides_detected_address() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(203, 0, 113, 7)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        assert_eq!(a_record_ips(&packet), vec![[203, 0, 113, 7]]);
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &src).await;
+
+        let reply = transport.sent_packets().remove(0);
+        assert_eq!(a_record_ips(&reply), vec![[203, 0, 113, 7]]);
+    }
+}
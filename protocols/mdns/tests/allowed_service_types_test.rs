@@ -0,0 +1,91 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsHeader, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    /// A responder allowed to answer for `_http._tcp.local` only, with an SSH service
+    /// registered alongside it.
+    async fn service(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            allowed_service_types: Some(vec!["_http._tcp.local".to_string()]),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        for (id, service_type, host) in [
+            ("Web._http._tcp.local", "_http._tcp.local", "web-host.local"),
+            ("Shell._ssh._tcp.local", "_ssh._tcp.local", "ssh-host.local"),
+        ] {
+            service
+                .register_local_service(
+                    id.to_string(),
+                    service_type.to_string(),
+                    8080,
+                    Some(120),
+                    host.to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        service
+    }
+
+    /// Sends a unicast query for `qname` and returns the records of the response, if any.
+    async fn ask(qname: &str, qtype: u16) -> Option<Vec<DnsRecord>> {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone()).await;
+        let mut query = DnsPacket::with_header(DnsHeader::query());
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new(qname).unwrap(),
+            qtype,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &peer).await;
+
+        match transport.sent_packets().as_slice() {
+            [] => None,
+            [response] => Some(response.answers.clone()),
+            more => panic!("Expected at most one response, got {}", more.len()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_allowed_type_is_answered() {
+        let answers = ask("_http._tcp.local", 12).await.expect("An allowed type is answered");
+        assert!(answers.iter().any(|record| matches!(
+            record,
+            DnsRecord::PTR { ptr_name, .. } if ptr_name.to_string() == "Web._http._tcp.local"
+        )));
+        assert!(ask("Web._http._tcp.local", 33).await.is_some());
+        assert!(ask("web-host.local", 1).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_other_types_their_instances_and_hosts_are_not_answered() {
+        assert!(ask("_ssh._tcp.local", 12).await.is_none());
+        assert!(ask("Shell._ssh._tcp.local", 33).await.is_none());
+        assert!(ask("Shell._ssh._tcp.local", 255).await.is_none());
+        assert!(ask("ssh-host.local", 1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_enumeration_lists_only_allowed_types() {
+        let answers = ask("_services._dns-sd._udp.local", 12).await.unwrap();
+        let types: Vec<String> = answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::PTR { ptr_name, .. } => Some(ptr_name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(types, vec!["_http._tcp.local".to_string()]);
+    }
+}
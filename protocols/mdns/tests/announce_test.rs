@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{CancellationToken, DnsRecord, MdnsConfig, MdnsService, TimingPolicy};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn config() -> MdnsConfig {
+        MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            timing: TimingPolicy {
+                announce_interval: Duration::from_millis(50),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        }
+    }
+
+    async fn register(service: &MdnsService, id: &str) {
+        service
+            .register_local_service(
+                id.to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_registering_while_running_announces_in_a_burst() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let service = MdnsService::with_transport(transport.clone(), config());
+
+        let shutdown = CancellationToken::new();
+        Arc::clone(&service)
+            .run("_http._tcp.local".to_string(), 60, 60, shutdown.clone())
+            .await;
+        register(&service, "Web._http._tcp.local").await;
+        sleep(Duration::from_millis(300)).await;
+        shutdown.cancel();
+
+        let announcements: Vec<_> = transport
+            .sent_packets()
+            .into_iter()
+            .filter(|packet| {
+                packet.answers.iter().any(|r| {
+                    matches!(r, DnsRecord::SRV { name, ttl, .. }
+                        if name.to_string() == "Web._http._tcp.local" && *ttl > 0)
+                })
+            })
+            .collect();
+        assert_eq!(announcements.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_announce_service_stops_when_service_is_removed() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), config());
+        register(&service, "Web._http._tcp.local").await;
+
+        let announcing = Arc::clone(&service);
+        let burst =
+            tokio::spawn(async move { announcing.announce_service("Web._http._tcp.local").await });
+        sleep(Duration::from_millis(20)).await;
+        service.registry.remove_service("Web._http._tcp.local").await.unwrap();
+
+        assert!(burst.await.unwrap().is_ok());
+        assert_eq!(transport.sent_packets().len(), 1);
+        assert!(service.announce_service("Web._http._tcp.local").await.is_err());
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsHeader, DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService};
+    use std::collections::HashMap;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    async fn service(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "host.local".to_string(),
+                Some(HashMap::from([("path".to_string(), "/".to_string())])),
+            )
+            .await
+            .unwrap();
+        service
+    }
+
+    /// Sends a unicast query for `qname` and returns the types of the answers and of the
+    /// additional records, in order.
+    async fn ask(qname: &str, qtype: u16) -> (Vec<u16>, Vec<u16>) {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone()).await;
+        let mut query = DnsPacket::with_header(DnsHeader::query());
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new(qname).unwrap(),
+            qtype,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &peer).await;
+
+        let types = |records: &[mdns::DnsRecord]| records.iter().map(|r| r.record_type()).collect();
+        match transport.sent_packets().as_slice() {
+            [] => (Vec::new(), Vec::new()),
+            [response] => (types(&response.answers), types(&response.additionals)),
+            more => panic!("Expected one response, got {}", more.len()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_srv_and_txt_queries_are_answered_directly() {
+        assert_eq!(ask("Web._http._tcp.local", 33).await, (vec![33], vec![1]));
+        assert_eq!(ask("web._HTTP._tcp.local", 16).await, (vec![16], vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_any_query_for_an_instance_returns_srv_and_txt_with_addresses() {
+        assert_eq!(ask("Web._http._tcp.local", 255).await, (vec![33, 16], vec![1]));
+    }
+
+    #[tokio::test]
+    async fn test_any_query_for_a_host_or_type_returns_its_records() {
+        assert_eq!(ask("host.local", 255).await.0, vec![1]);
+        assert_eq!(ask("_http._tcp.local", 255).await, (vec![12], vec![33, 16, 1]));
+    }
+
+    #[tokio::test]
+    async fn test_queries_for_unknown_names_go_unanswered() {
+        assert_eq!(ask("Other._http._tcp.local", 255).await, (vec![], vec![]));
+        assert_eq!(ask("Other._http._tcp.local", 33).await, (vec![], vec![]));
+    }
+}
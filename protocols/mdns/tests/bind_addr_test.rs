@@ -0,0 +1,160 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{
+        DiscoveryEvent, DnsHeader, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig,
+        MdnsService, ServiceRecord,
+    };
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    const LAN: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 10);
+    const MANAGEMENT: Ipv4Addr = Ipv4Addr::new(10, 8, 0, 2);
+
+    async fn service_with_admin(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(LAN),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .register_service(
+                ServiceRecord::builder("Web._http._tcp.local", "_http._tcp.local", 8080)
+                    .origin("web-host.local")
+                    .ttl(120)
+                    .build(),
+            )
+            .await
+            .unwrap();
+        service
+            .register_service(
+                ServiceRecord::builder("Admin._http._tcp.local", "_http._tcp.local", 9000)
+                    .origin("admin-host.local")
+                    .ttl(120)
+                    .bind_addr(MANAGEMENT)
+                    .build(),
+            )
+            .await
+            .unwrap();
+        service
+    }
+
+    /// Returns the `(host, address)` of every A record in `packet`, sorted.
+    fn addresses(packet: &DnsPacket) -> Vec<(String, Ipv4Addr)> {
+        let mut addresses: Vec<(String, Ipv4Addr)> = packet
+            .answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::A { name, ip, .. } => Some((name.to_string(), Ipv4Addr::from(*ip))),
+                _ => None,
+            })
+            .collect();
+        addresses.sort();
+        addresses
+    }
+
+    #[tokio::test]
+    async fn test_bound_service_advertises_only_its_address() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_with_admin(transport).await;
+
+        let packet = service.create_advertise_packet().await.unwrap();
+
+        assert_eq!(
+            addresses(&packet),
+            vec![
+                ("admin-host.local".to_string(), MANAGEMENT),
+                ("web-host.local".to_string(), LAN),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reverse_lookup_of_bound_address_names_only_its_host() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_with_admin(transport.clone()).await;
+
+        let mut query = DnsPacket::with_header(DnsHeader::query());
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("2.0.8.10.in-addr.arpa").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let peer: SocketAddr = "10.8.0.5:5353".parse().unwrap();
+        service.process_query(&query, &peer).await;
+
+        let reply = &transport.sent_packets()[0];
+        let hosts: Vec<String> = reply
+            .answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::PTR { ptr_name, .. } => Some(ptr_name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hosts, vec!["admin-host.local"]);
+    }
+
+    #[tokio::test]
+    async fn test_echo_of_bound_announcement_is_not_a_conflict() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_with_admin(transport).await;
+        let mut events = service.subscribe();
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+        let src = SocketAddr::new(MANAGEMENT.into(), 5353);
+        service.process_response(&parsed, &src).await;
+
+        while let Ok(event) = events.try_recv() {
+            assert!(
+                !matches!(event, DiscoveryEvent::Conflict { .. }),
+                "Our own bound address was reported as a conflict: {:?}",
+                event
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_services_sharing_a_host_each_advertise_their_bind_addr() {
+        const STORAGE: Ipv4Addr = Ipv4Addr::new(10, 9, 0, 2);
+        for order in [[MANAGEMENT, STORAGE], [STORAGE, MANAGEMENT]] {
+            let config = MdnsConfig {
+                advertised_address: Some(LAN),
+                keep_service_origins: true,
+                ..MdnsConfig::default()
+            };
+            let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+            for (index, bind_addr) in order.into_iter().enumerate() {
+                service
+                    .register_service(
+                        ServiceRecord::builder(
+                            &format!("Admin {}._http._tcp.local", index),
+                            "_http._tcp.local",
+                            9000,
+                        )
+                        .origin("admin-host.local")
+                        .ttl(120)
+                        .bind_addr(bind_addr)
+                        .build(),
+                    )
+                    .await
+                    .unwrap();
+            }
+
+            let packet = service.create_advertise_packet().await.unwrap();
+
+            assert_eq!(
+                addresses(&packet),
+                vec![
+                    ("admin-host.local".to_string(), MANAGEMENT),
+                    ("admin-host.local".to_string(), STORAGE),
+                ]
+            );
+        }
+    }
+}
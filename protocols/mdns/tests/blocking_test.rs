@@ -0,0 +1,45 @@
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use mdns::blocking::BlockingMdnsService;
+    use mdns::{ChannelBus, MdnsConfig};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_blocking_querier_resolves_blocking_responder() {
+        let bus = ChannelBus::new();
+        let responder = BlockingMdnsService::with_transport(
+            Arc::new(bus.join("192.168.1.10:5353".parse().unwrap())),
+            MdnsConfig {
+                advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+                ..MdnsConfig::default()
+            },
+        )
+        .unwrap();
+        let querier = BlockingMdnsService::with_transport(
+            Arc::new(bus.join("192.168.1.20:5353".parse().unwrap())),
+            MdnsConfig::default(),
+        )
+        .unwrap();
+        responder
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .unwrap();
+
+        let found = querier.resolve("_http._tcp.local", Duration::from_secs(2)).unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].port, 8080);
+        let nodes = querier.list_nodes();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].ip_address, "192.168.1.10");
+        responder.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{
+        CacheFlush, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService,
+        Protocol, ServiceRecord,
+    };
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    fn discovered(id: &str, ttl: u32) -> ServiceRecord {
+        ServiceRecord {
+            id: id.to_string(),
+            service_type: "_http._tcp.local".to_string(),
+            port: 8080,
+            ttl: Some(ttl),
+            origin: "peer.local".to_string(),
+            priority: Some(0),
+            weight: Some(0),
+            cache_flush: CacheFlush::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: Some(Protocol::Tcp),
+            bind_addr: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_near_expiry_cached_records_are_excluded() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            answer_from_cache: true,
+            min_cache_answer_ttl: 10,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .registry
+            .add_discovered_service(discovered("Fresh._http._tcp.local", 120))
+            .await
+            .unwrap();
+        service
+            .registry
+            .add_discovered_service(discovered("Stale._http._tcp.local", 2))
+            .await
+            .unwrap();
+
+        let mut query = DnsPacket::new();
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &src).await;
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1, "The fresh cached service should be answered");
+        let srv_names: Vec<String> = sent[0]
+            .additionals
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::SRV { name, ttl, .. } => {
+                    assert!(*ttl <= 120, "Cached answers carry the remaining TTL");
+                    Some(name.to_string())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(srv_names, vec!["Fresh._http._tcp.local".to_string()]);
+    }
+}
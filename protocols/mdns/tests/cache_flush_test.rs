@@ -0,0 +1,174 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsHeader, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn a_record(host: &str, ip: [u8; 4]) -> DnsRecord {
+        DnsRecord::A {
+            name: DnsName::new(host).unwrap(),
+            ttl: 120,
+            ip,
+            cache_flush: true,
+        }
+    }
+
+    fn srv_record(target: &str, cache_flush: bool) -> DnsRecord {
+        DnsRecord::SRV {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 9000,
+            target: DnsName::new(target).unwrap(),
+            cache_flush,
+        }
+    }
+
+    fn response(answers: Vec<DnsRecord>) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers = answers;
+        packet
+    }
+
+    fn peer() -> SocketAddr {
+        "192.168.1.30:5353".parse().unwrap()
+    }
+
+    #[test]
+    fn test_cache_flush_bit_survives_parsing() {
+        let packet = response(vec![a_record("peer.local", [192, 168, 1, 30])]);
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+        assert!(parsed.answers[0].cache_flush());
+    }
+
+    #[tokio::test]
+    async fn test_flushed_address_replaces_the_cached_one() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+
+        service.process_response(&response(vec![a_record("peer.local", [192, 168, 1, 30])]), &peer()).await;
+        service.process_response(&response(vec![a_record("peer.local", [192, 168, 1, 31])]), &peer()).await;
+
+        let nodes = service.registry.list_nodes().await;
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].ip_address, "192.168.1.31");
+    }
+
+    #[tokio::test]
+    async fn test_flushed_srv_purges_the_previous_target_host() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        service
+            .process_response(
+                &response(vec![srv_record("old.local", true), a_record("old.local", [192, 168, 1, 30])]),
+                &peer(),
+            )
+            .await;
+
+        service
+            .process_response(
+                &response(vec![srv_record("new.local", true), a_record("new.local", [192, 168, 1, 31])]),
+                &peer(),
+            )
+            .await;
+
+        assert!(service.registry.get_node("old.local").await.is_none());
+        assert!(service.registry.get_node("new.local").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_srv_without_flush_leaves_the_previous_target_cached() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        service
+            .process_response(
+                &response(vec![srv_record("old.local", false), a_record("old.local", [192, 168, 1, 30])]),
+                &peer(),
+            )
+            .await;
+
+        service
+            .process_response(&response(vec![srv_record("new.local", false)]), &peer())
+            .await;
+
+        assert!(service.registry.get_node("old.local").await.is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_flushes_leave_a_single_node() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+
+        for round in 0..50u8 {
+            // The same host announced twice at once, in different letter case.
+            let tasks: Vec<_> = [("Peer.local", 30), ("peer.local", 31)]
+                .into_iter()
+                .map(|(host, last_octet)| {
+                    let service = Arc::clone(&service);
+                    tokio::spawn(async move {
+                        let record = a_record(host, [192, 168, round, last_octet]);
+                        service.process_response(&response(vec![record]), &peer()).await;
+                    })
+                })
+                .collect();
+            for task in tasks {
+                task.await.unwrap();
+            }
+
+            let nodes = service.registry.list_nodes().await;
+            assert_eq!(nodes.len(), 1, "Round {}: flushes interleaved: {:?}", round, nodes);
+            let octets: Vec<u8> = nodes[0].ip_address.split('.').map(|o| o.parse().unwrap()).collect();
+            assert_eq!(octets[2], round, "Round {}: a stale address survived", round);
+        }
+    }
+
+    /// Asks a fresh responder for `_http._tcp.local` PTRs from `src` and returns the
+    /// answers and additional records of its reply.
+    async fn browse_response(src: &str) -> Vec<DnsRecord> {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                Some([("path".to_string(), "/".to_string())].into()),
+            )
+            .await
+            .unwrap();
+        let mut query = DnsPacket::with_header(DnsHeader::query());
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: true,
+        });
+        service.process_query(&query, &src.parse().unwrap()).await;
+
+        let response = transport.sent_packets().pop().expect("The browse is answered");
+        response.answers.into_iter().chain(response.additionals).collect()
+    }
+
+    #[tokio::test]
+    async fn test_browse_response_flushes_unique_records_only() {
+        let records = browse_response("192.168.1.20:5353").await;
+        assert_eq!(records.len(), 4, "PTR, SRV, TXT, and A: {:?}", records);
+        for record in records {
+            let unique = !matches!(record, DnsRecord::PTR { .. });
+            assert_eq!(record.cache_flush(), unique, "{:?}", record);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_legacy_unicast_response_sets_no_cache_flush_bit() {
+        let records = browse_response("192.168.1.20:40000").await;
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|record| !record.cache_flush()), "{:?}", records);
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{CapturingTransport, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    async fn service(capture: &CapturingTransport) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(capture.clone()), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_advertisement_is_captured_with_its_records() {
+        let capture = CapturingTransport::new();
+        let service = service(&capture).await;
+
+        service.advertise_services().await.unwrap();
+
+        let sent = capture.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1, "224.0.0.251:5353".parse::<SocketAddr>().unwrap());
+        let packets = capture.sent_packets().unwrap();
+        let mut records: Vec<String> = packets[0]
+            .answers
+            .iter()
+            .map(|record| match record {
+                DnsRecord::PTR { name, ptr_name, .. } => format!("PTR {} -> {}", name, ptr_name),
+                DnsRecord::SRV { name, target, port, .. } => {
+                    format!("SRV {} -> {}:{}", name, target, port)
+                }
+                DnsRecord::A { name, ip, .. } => format!("A {} -> {}", name, Ipv4Addr::from(*ip)),
+                other => panic!("Unexpected record {:?}", other),
+            })
+            .collect();
+        records.sort();
+        assert_eq!(
+            records,
+            vec![
+                "A web-host.local -> 192.168.1.10",
+                "PTR _http._tcp.local -> Web._http._tcp.local",
+                "SRV Web._http._tcp.local -> web-host.local:8080",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_the_log() {
+        let capture = CapturingTransport::new();
+        let log = capture.log();
+        let service = service(&capture).await;
+
+        service.advertise_services().await.unwrap();
+        assert_eq!(log.lock().unwrap().len(), 1);
+
+        capture.clear();
+        service.shutdown().await.unwrap();
+        let goodbye = &capture.sent_packets().unwrap()[0];
+        assert!(goodbye.answers.iter().all(|record| record.ttl() == 0));
+    }
+}
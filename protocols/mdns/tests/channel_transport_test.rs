@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{ChannelBus, MdnsConfig, MdnsService, Transport};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_multicast_reaches_every_other_member() {
+        let bus = ChannelBus::new();
+        let a = bus.join("192.168.1.10:5353".parse().unwrap());
+        let b = bus.join("192.168.1.20:5353".parse().unwrap());
+        let c = bus.join("192.168.1.30:5353".parse().unwrap());
+
+        a.send_to(b"hello", "224.0.0.251:5353".parse().unwrap()).await.unwrap();
+        b.send_to(b"direct", c.address()).await.unwrap();
+
+        let mut buf = [0; 16];
+        assert_eq!(b.recv_from(&mut buf).await.unwrap(), (5, a.address()));
+        assert_eq!(&buf[..5], b"hello");
+        assert_eq!(c.recv_from(&mut buf).await.unwrap(), (5, a.address()));
+        assert_eq!(c.recv_from(&mut buf).await.unwrap(), (6, b.address()));
+        assert_eq!(&buf[..6], b"direct");
+    }
+
+    #[tokio::test]
+    async fn test_querier_discovers_responder_over_the_bus() {
+        let bus = ChannelBus::new();
+        let responder_addr: SocketAddr = "192.168.1.10:5353".parse().unwrap();
+        let responder = MdnsService::with_transport(
+            Arc::new(bus.join(responder_addr)),
+            MdnsConfig {
+                advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+                keep_service_origins: true,
+                ..MdnsConfig::default()
+            },
+        );
+        let querier = MdnsService::with_transport(
+            Arc::new(bus.join("192.168.1.20:5353".parse().unwrap())),
+            MdnsConfig::default(),
+        );
+        responder
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let listeners: Vec<_> = [&responder, &querier]
+            .into_iter()
+            .map(|service| {
+                let service = Arc::clone(service);
+                tokio::spawn(async move { service.listen().await })
+            })
+            .collect();
+
+        let found = querier
+            .resolve("_http._tcp.local", Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "MyService._http._tcp.local");
+        assert_eq!(found[0].port, 8080);
+        let host = querier.registry.get_node("MyHost.local").await.unwrap();
+        assert_eq!(host.ip_address, "192.168.1.10");
+
+        for listener in listeners {
+            listener.abort();
+        }
+    }
+}
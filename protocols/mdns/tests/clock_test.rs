@@ -0,0 +1,157 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockClock, MockTransport};
+    use mdns::{
+        DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService, NodeRecord, Protocol, ServiceRecord,
+    };
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+    use tokio::time::Duration;
+
+    fn service_with_clock() -> (Arc<MockTransport>, Arc<MockClock>, Arc<MdnsService>) {
+        let transport = Arc::new(MockTransport::new());
+        let clock = Arc::new(MockClock::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport_and_clock(transport.clone(), config, clock.clone());
+        (transport, clock, service)
+    }
+
+    /// Lets spawned tasks run until they block on the mock clock again.
+    async fn settle() {
+        for _ in 0..50 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_expire_only_when_the_clock_advances() {
+        let (_, clock, service) = service_with_clock();
+        service
+            .registry
+            .add_node(NodeRecord {
+                id: "peer.local".to_string(),
+                ip_address: "192.168.1.30".to_string(),
+                ttl: Some(5),
+                source: None,
+                last_seen: None,
+                scope_id: None,
+            })
+            .await
+            .unwrap();
+        service
+            .registry
+            .add_discovered_service(ServiceRecord {
+                id: "Peer._http._tcp.local".to_string(),
+                service_type: "_http._tcp.local".to_string(),
+                port: 80,
+                ttl: Some(10),
+                origin: "peer.local".to_string(),
+                priority: None,
+                weight: None,
+                cache_flush: Default::default(),
+                txt: Vec::new(),
+                subtypes: Vec::new(),
+                protocol: Some(Protocol::Tcp),
+                bind_addr: None,
+            })
+            .await
+            .unwrap();
+
+        assert!(service.registry.sweep_expired().await.is_empty());
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(service.registry.sweep_expired().await, vec!["peer.local".to_string()]);
+        assert_eq!(
+            service.registry.discovered_remaining_ttl("Peer._http._tcp.local"),
+            Some(5)
+        );
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(
+            service.registry.sweep_expired().await,
+            vec!["Peer._http._tcp.local".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wall_clock_time_alone_expires_nothing() {
+        let (_, clock, service) = service_with_clock();
+        service
+            .registry
+            .add_node(NodeRecord {
+                id: "peer.local".to_string(),
+                ip_address: "192.168.1.30".to_string(),
+                ttl: Some(1),
+                source: None,
+                last_seen: None,
+                scope_id: None,
+            })
+            .await
+            .unwrap();
+        let mut txt = DnsPacket::new();
+        txt.answers.push(DnsRecord::TXT {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 1,
+            txt_data: b"\x06path=/".to_vec(),
+            cache_flush: true,
+        });
+        let src = "192.168.1.30:5353".parse().unwrap();
+        service.process_response(&txt, &src).await;
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(service.registry.get_node("peer.local").await.is_some());
+        assert!(service.registry.get_txt("Peer._http._tcp.local").await.is_some());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(service.registry.get_node("peer.local").await.is_none());
+        assert!(service.registry.get_txt("Peer._http._tcp.local").await.is_none());
+        assert_eq!(service.registry.sweep_expired().await, vec!["peer.local".to_string()]);
+        assert_eq!(service.registry.node_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_announcement_burst_follows_the_clock() {
+        let (transport, clock, service) = service_with_clock();
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let announcer = Arc::clone(&service);
+        let burst = tokio::spawn(async move { announcer.announce_service("Web._http._tcp.local").await });
+        let announcements = || {
+            transport
+                .sent_packets()
+                .iter()
+                .filter(|packet| packet.answers.iter().any(|r| matches!(r, DnsRecord::SRV { .. })))
+                .count()
+        };
+
+        settle().await;
+        assert_eq!(announcements(), 1, "The first announcement goes out at once");
+
+        clock.advance(Duration::from_millis(999));
+        settle().await;
+        assert_eq!(announcements(), 1, "The second announcement waits a full second");
+
+        clock.advance(Duration::from_millis(1));
+        settle().await;
+        assert_eq!(announcements(), 2);
+
+        clock.advance(Duration::from_secs(1));
+        burst.await.unwrap().unwrap();
+        assert_eq!(announcements(), 3);
+    }
+}
@@ -0,0 +1,160 @@
+// protocols\mdns\tests\common\mod.rs
+#![allow(dead_code)]
+use async_trait::async_trait;
+use mdns::{Clock, DnsPacket, Transport};
+use std::collections::VecDeque;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// A scripted datagram, or the kind of error to fail `recv_from` with.
+type Inbound = Result<(Vec<u8>, SocketAddr), std::io::ErrorKind>;
+
+/// In-memory transport that replays scripted inbound datagrams and records every send.
+///
+/// Once the inbound script is exhausted, `recv_from` fails with `ConnectionAborted`,
+/// which ends the service's `listen` loop, unless the transport is held open.
+#[derive(Default)]
+pub struct MockTransport {
+    inbound: Mutex<VecDeque<Inbound>>,
+    sent: Mutex<Vec<(Vec<u8>, SocketAddr)>>,
+    hold_open: AtomicBool,
+    interface_sends: Mutex<Vec<(Ipv4Addr, SocketAddr)>>,
+    rejoins: Mutex<Vec<(Ipv4Addr, Ipv4Addr)>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a datagram to be returned by a later `recv_from`.
+    pub fn push_inbound(&self, data: Vec<u8>, src: SocketAddr) {
+        self.inbound.lock().unwrap().push_back(Ok((data, src)));
+    }
+
+    /// Queues an error of `kind` to be returned by a later `recv_from`.
+    pub fn push_inbound_error(&self, kind: std::io::ErrorKind) {
+        self.inbound.lock().unwrap().push_back(Err(kind));
+    }
+
+    /// Keeps `recv_from` waiting for more scripted datagrams instead of failing once the
+    /// script is exhausted.
+    pub fn hold_open(&self) {
+        self.hold_open.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns every `(bytes, destination)` pair sent so far.
+    pub fn sent(&self) -> Vec<(Vec<u8>, SocketAddr)> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Returns the `(interface, destination)` of every send made through `send_to_via`.
+    pub fn interface_sends(&self) -> Vec<(Ipv4Addr, SocketAddr)> {
+        self.interface_sends.lock().unwrap().clone()
+    }
+
+    /// Returns the `(group, interface)` of every `rejoin_multicast_v4` call.
+    pub fn rejoins(&self) -> Vec<(Ipv4Addr, Ipv4Addr)> {
+        self.rejoins.lock().unwrap().clone()
+    }
+
+    /// Returns every sent datagram parsed back into a `DnsPacket`.
+    pub fn sent_packets(&self) -> Vec<DnsPacket> {
+        self.sent()
+            .iter()
+            .map(|(bytes, _)| DnsPacket::parse(bytes).expect("sent packet should parse"))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> std::io::Result<usize> {
+        self.sent.lock().unwrap().push((buf.to_vec(), target));
+        Ok(buf.len())
+    }
+
+    async fn send_to_via(
+        &self,
+        buf: &[u8],
+        target: SocketAddr,
+        interface: Ipv4Addr,
+    ) -> std::io::Result<usize> {
+        self.interface_sends.lock().unwrap().push((interface, target));
+        self.send_to(buf, target).await
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+        loop {
+            let next = self.inbound.lock().unwrap().pop_front();
+            match next {
+                Some(Err(kind)) => return Err(std::io::Error::new(kind, "scripted receive error")),
+                Some(Ok((data, src))) => {
+                    // Like a UDP socket, silently drop whatever does not fit.
+                    let len = data.len().min(buf.len());
+                    buf[..len].copy_from_slice(&data[..len]);
+                    return Ok((len, src));
+                }
+                None if self.hold_open.load(Ordering::SeqCst) => {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                None => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        "mock transport exhausted",
+                    ))
+                }
+            }
+        }
+    }
+
+    fn rejoin_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> std::io::Result<()> {
+        self.rejoins.lock().unwrap().push((group, interface));
+        Ok(())
+    }
+}
+
+/// Clock that stands still until a test advances it.
+pub struct MockClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+    advanced: Notify,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+            advanced: Notify::new(),
+        }
+    }
+
+    /// Moves the clock forward, waking every sleeper whose deadline has been reached.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+        self.advanced.notify_waiters();
+    }
+}
+
+#[async_trait]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let deadline = self.now() + duration;
+        loop {
+            let advanced = self.advanced.notified();
+            if self.now() >= deadline {
+                return;
+            }
+            advanced.await;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use futures::StreamExt;
+    use mdns::{DiscoveryEvent, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService, NodeRecord};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn a_record(ip: [u8; 4], ttl: u32) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("peer.local").unwrap(),
+            ttl,
+            ip,
+            cache_flush: false,
+        });
+        packet
+    }
+
+    fn node(ip: &str) -> NodeRecord {
+        NodeRecord {
+            id: "peer.local".to_string(),
+            ip_address: ip.to_string(),
+            ttl: Some(120),
+            source: Some("192.168.1.30:5353".parse().unwrap()),
+            last_seen: None,
+            scope_id: None,
+        }
+    }
+
+    /// Clears the `last_seen` stamp of a node event, which follows the tokio clock.
+    fn unstamped(event: DiscoveryEvent) -> DiscoveryEvent {
+        match event {
+            DiscoveryEvent::ServiceDiscovered(node) => {
+                DiscoveryEvent::ServiceDiscovered(NodeRecord { last_seen: None, ..node })
+            }
+            DiscoveryEvent::ServiceUpdated(node) => {
+                DiscoveryEvent::ServiceUpdated(NodeRecord { last_seen: None, ..node })
+            }
+            other => other,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_see_discovery_update_and_expiry() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+        let mut events = service.subscribe();
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+
+        service.process_response(&a_record([192, 168, 1, 30], 120), &src).await;
+        // A plain refresh of the same address is not an update.
+        service.process_response(&a_record([192, 168, 1, 30], 120), &src).await;
+        service.process_response(&a_record([192, 168, 1, 31], 120), &src).await;
+        service.process_response(&a_record([192, 168, 1, 31], 0), &src).await;
+
+        assert_eq!(
+            unstamped(events.try_recv().unwrap()),
+            DiscoveryEvent::ServiceDiscovered(node("192.168.1.30"))
+        );
+        assert_eq!(
+            unstamped(events.try_recv().unwrap()),
+            DiscoveryEvent::ServiceUpdated(node("192.168.1.31"))
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DiscoveryEvent::ServiceExpired("peer.local".to_string())
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discovery_stream_yields_events() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+        let stream = service.discovery_stream();
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+
+        service.process_response(&a_record([192, 168, 1, 30], 120), &src).await;
+        service.process_response(&a_record([192, 168, 1, 31], 120), &src).await;
+
+        let events: Vec<DiscoveryEvent> =
+            tokio::time::timeout(Duration::from_secs(1), stream.take(2).map(unstamped).collect())
+                .await
+                .expect("Both events should arrive in time");
+        assert_eq!(
+            events,
+            vec![
+                DiscoveryEvent::ServiceDiscovered(node("192.168.1.30")),
+                DiscoveryEvent::ServiceUpdated(node("192.168.1.31")),
+            ]
+        );
+    }
+}
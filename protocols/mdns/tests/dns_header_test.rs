@@ -0,0 +1,94 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsHeader, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    #[test]
+    fn test_header_constructors_and_accessors() {
+        let query = DnsHeader::query();
+        assert!(!query.is_response());
+        assert!(!query.is_authoritative());
+        assert_eq!((query.opcode(), query.rcode()), (0, 0));
+
+        let response = DnsHeader::response();
+        assert!(response.is_response());
+        assert!(response.is_authoritative());
+        assert_eq!(response.flags, 0x8400);
+
+        // QR, opcode 2 (status), TC, and rcode 3 (name error).
+        let header = DnsHeader { id: 7, flags: 0x8000 | 2 << 11 | 0x0200 | 3 };
+        assert!(header.is_response());
+        assert!(header.is_truncated());
+        assert!(!header.is_authoritative());
+        assert_eq!(header.opcode(), 2);
+        assert_eq!(header.rcode(), 3);
+    }
+
+    #[test]
+    fn test_packet_header_survives_parsing() {
+        let mut packet = DnsPacket::with_header(DnsHeader::query());
+        packet.set_header(DnsHeader { id: 0x1234, ..packet.header() });
+
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+        assert_eq!(parsed.header(), DnsHeader { id: 0x1234, flags: 0 });
+        assert!(!parsed.is_response());
+        assert!(DnsPacket::new().is_response(), "New packets are responses");
+    }
+
+    #[test]
+    fn test_header_counts_follow_sections_pushed_after_construction() {
+        let mut packet = DnsPacket::with_header(DnsHeader::response());
+        assert_eq!(&packet.serialize()[4..12], &[0; 8]);
+
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("host.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 10],
+            cache_flush: true,
+        });
+        let bytes = packet.serialize();
+        assert_eq!(&bytes[6..8], &[0, 1], "ANCOUNT reflects the pushed answer");
+
+        let parsed = DnsPacket::parse(&bytes).unwrap();
+        assert_eq!(parsed.answers, packet.answers);
+        assert!(parsed.questions.is_empty() && parsed.additionals.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_packets_with_a_nonzero_opcode_are_ignored() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+        let listener = {
+            let service = Arc::clone(&service);
+            tokio::spawn(async move { service.listen().await })
+        };
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+        let response = |host: &str, opcode: u16| {
+            let mut packet = DnsPacket::with_header(DnsHeader {
+                id: 0,
+                flags: DnsHeader::response().flags | opcode << 11,
+            });
+            packet.answers.push(DnsRecord::A {
+                name: DnsName::new(host).unwrap(),
+                ttl: 120,
+                ip: [192, 168, 1, 30],
+                cache_flush: false,
+            });
+            packet
+        };
+
+        transport.push_inbound(response("update.local", 5).serialize(), src);
+        transport.push_inbound(response("peer.local", 0).serialize(), src);
+        sleep(Duration::from_millis(100)).await;
+        listener.abort();
+
+        assert!(service.registry.get_node("update.local").await.is_none());
+        assert!(service.registry.get_node("peer.local").await.is_some());
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsPacket, DnsRecord, MdnsConfig, MdnsService, Protocol, ServiceInstanceName};
+    use std::collections::HashMap;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    fn service() -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            hostname: Some("office-host.local".to_string()),
+            ..MdnsConfig::default()
+        };
+        MdnsService::with_transport(Arc::new(MockTransport::new()), config)
+    }
+
+    #[tokio::test]
+    async fn test_register_dns_sd_composes_the_names() {
+        let service = service();
+        let id = service
+            .register_dns_sd(
+                "Office Printer",
+                "ipp",
+                Protocol::Tcp,
+                631,
+                Some(HashMap::from([("rp".to_string(), "ipp/print".to_string())])),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(id, "Office Printer._ipp._tcp.local");
+        let registered = service.registry.get_service(&id).await.unwrap();
+        assert_eq!(registered.service_type, "_ipp._tcp.local");
+        assert_eq!(registered.origin, "office-host.local");
+        assert_eq!(registered.protocol, Some(Protocol::Tcp));
+        assert_eq!(registered.port, 631);
+        assert_eq!(registered.txt, vec![("rp".to_string(), "ipp/print".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_dots_in_the_instance_stay_in_one_label() {
+        let service = service();
+        let id = service
+            .register_dns_sd("Floor 2.East", "_http", Protocol::Tcp, 80, None)
+            .await
+            .unwrap();
+        assert_eq!(id, r"Floor 2\.East._http._tcp.local");
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+        let instance = parsed
+            .answers
+            .iter()
+            .find_map(|record| match record {
+                DnsRecord::PTR { ptr_name, .. } => Some(ptr_name.clone()),
+                _ => None,
+            })
+            .expect("The service is advertised with a PTR");
+        assert_eq!(instance.labels()[0], "Floor 2.East");
+        assert_eq!(instance.to_string(), id);
+        let name = ServiceInstanceName::from_dns_name(&instance).unwrap();
+        assert_eq!(name.instance, "Floor 2.East");
+        assert_eq!(name.service_name(), "_http._tcp.local");
+    }
+
+    #[tokio::test]
+    async fn test_register_dns_sd_rejects_bad_instances() {
+        let service = service();
+        assert!(service.register_dns_sd("", "http", Protocol::Tcp, 80, None).await.is_err());
+        let too_long = "x".repeat(64);
+        assert!(service.register_dns_sd(&too_long, "http", Protocol::Udp, 80, None).await.is_err());
+        assert!(service.registry.list_services().await.is_empty());
+    }
+}
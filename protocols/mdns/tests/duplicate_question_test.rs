@@ -0,0 +1,78 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{
+        DnsHeader, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService, TimingPolicy,
+    };
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::sync::watch;
+    use tokio::time::{sleep, Duration};
+    use tokio_util::sync::CancellationToken;
+
+    fn browse_query() -> DnsPacket {
+        let mut query = DnsPacket::with_header(DnsHeader::query());
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        query
+    }
+
+    /// Runs the periodic query every 300ms for 450ms, with `observed` arriving from
+    /// `src` after 100ms, and returns the number of queries sent.
+    async fn queries_sent(observed: DnsPacket, src: &str) -> usize {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            timing: TimingPolicy {
+                requery_interval_max: Duration::from_millis(300),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        let (_interval_tx, interval_rx) = watch::channel(Duration::from_millis(300));
+        let shutdown = CancellationToken::new();
+
+        let task = {
+            let service = service.clone();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move { service.periodic_query("_http._tcp.local", interval_rx, shutdown).await })
+        };
+        sleep(Duration::from_millis(100)).await;
+        service.process_query(&observed, &src.parse::<SocketAddr>().unwrap()).await;
+        sleep(Duration::from_millis(350)).await;
+        shutdown.cancel();
+        task.await.unwrap().unwrap();
+
+        transport.sent_packets().iter().filter(|packet| !packet.is_response()).count()
+    }
+
+    #[tokio::test]
+    async fn test_peer_query_suppresses_the_next_send() {
+        assert_eq!(queries_sent(browse_query(), "192.168.1.20:5353").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_own_query_echo_does_not_suppress() {
+        assert_eq!(queries_sent(browse_query(), "192.168.1.10:5353").await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_peer_query_with_known_answers_does_not_suppress() {
+        // Our query lists no known answers, so the peer's query may miss some of ours.
+        let mut observed = browse_query();
+        observed.answers.push(DnsRecord::PTR {
+            name: DnsName::new("_http._tcp.local").unwrap(),
+            ttl: 120,
+            ptr_name: DnsName::new("Web._http._tcp.local").unwrap(),
+            cache_flush: false,
+        });
+        assert_eq!(queries_sent(observed, "192.168.1.20:5353").await, 2);
+    }
+}
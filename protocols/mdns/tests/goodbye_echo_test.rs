@@ -0,0 +1,69 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn announcement(ttl: u32) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::PTR {
+            name: DnsName::new("_http._tcp.local").unwrap(),
+            ttl,
+            ptr_name: DnsName::new("MyService._http._tcp.local").unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new("MyService._http._tcp.local").unwrap(),
+            ttl,
+            priority: 0,
+            weight: 0,
+            port: 8080,
+            target: DnsName::new("MyHost.local").unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("MyHost.local").unwrap(),
+            ttl,
+            ip: [192, 168, 1, 10],
+            cache_flush: false,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_own_goodbye_echo_is_ignored() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let own_addr: SocketAddr = "192.168.1.10:5353".parse().unwrap();
+
+        // Our regular announcement echoes back first, then our goodbye.
+        service.process_response(&announcement(120), &own_addr).await;
+        service.process_response(&announcement(0), &own_addr).await;
+
+        assert!(
+            service.registry.get_service("MyService._http._tcp.local").await.is_some(),
+            "Local service must survive its own goodbye echo"
+        );
+        let node = service.registry.get_node("MyHost.local").await;
+        assert_eq!(node.map(|n| n.ttl), Some(Some(120)), "Goodbye echo must not overwrite our node");
+    }
+}
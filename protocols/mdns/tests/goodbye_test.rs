@@ -0,0 +1,187 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{CancellationToken, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    async fn service_with(transport: Arc<MockTransport>, ids: &[&str]) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        for id in ids {
+            service
+                .register_local_service(
+                    id.to_string(),
+                    "_http._tcp.local".to_string(),
+                    8080,
+                    Some(120),
+                    "MyHost.local".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        service
+    }
+
+    #[tokio::test]
+    async fn test_deregister_sends_goodbye_and_removes_service() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_with(transport.clone(), &["Web._http._tcp.local"]).await;
+
+        service.deregister_local_service("Web._http._tcp.local").await.unwrap();
+
+        assert!(service.registry.get_service("Web._http._tcp.local").await.is_none());
+        let packets = transport.sent_packets();
+        assert_eq!(packets.len(), 1);
+        let goodbye = &packets[0];
+        assert!(goodbye.answers.iter().any(|r| matches!(r, DnsRecord::PTR { .. })));
+        assert!(goodbye.answers.iter().any(|r| matches!(r, DnsRecord::SRV { .. })));
+        assert!(goodbye.answers.iter().any(|r| matches!(r, DnsRecord::A { .. })));
+        assert!(goodbye.answers.iter().all(|r| r.ttl() == 0));
+
+        assert!(service.deregister_local_service("Web._http._tcp.local").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deregister_says_goodbye_for_that_service_only() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_with(transport.clone(), &[]).await;
+        for (id, host) in [
+            ("Web._http._tcp.local", "web-host.local"),
+            ("Api._http._tcp.local", "api-host.local"),
+            ("Admin._http._tcp.local", "admin-host.local"),
+        ] {
+            service
+                .register_local_service(
+                    id.to_string(),
+                    "_http._tcp.local".to_string(),
+                    8080,
+                    Some(120),
+                    host.to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        service.deregister_local_service("Api._http._tcp.local").await.unwrap();
+
+        let goodbye = &transport.sent_packets()[0];
+        let records: Vec<(u16, String)> = goodbye
+            .answers
+            .iter()
+            .map(|r| (r.record_type(), r.name().to_string()))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                (12, "_http._tcp.local".to_string()),
+                (33, "Api._http._tcp.local".to_string()),
+                (1, "api-host.local".to_string()),
+            ]
+        );
+        assert!(goodbye.answers.iter().all(|r| r.ttl() == 0));
+        let mut remaining: Vec<String> =
+            service.registry.list_services().await.into_iter().map(|s| s.id).collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["Admin._http._tcp.local", "Web._http._tcp.local"]);
+    }
+
+    #[tokio::test]
+    async fn test_deregister_keeps_addresses_of_a_shared_host() {
+        let transport = Arc::new(MockTransport::new());
+        let service =
+            service_with(transport.clone(), &["One._http._tcp.local", "Two._http._tcp.local"]).await;
+
+        service.deregister_local_service("One._http._tcp.local").await.unwrap();
+
+        // Two._http._tcp.local still lives on MyHost.local, so its address stays valid.
+        let goodbye = &transport.sent_packets()[0];
+        assert!(goodbye.answers.iter().any(|r| matches!(r, DnsRecord::SRV { .. })));
+        assert!(!goodbye.answers.iter().any(|r| matches!(r, DnsRecord::A { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_says_goodbye_for_every_service() {
+        let transport = Arc::new(MockTransport::new());
+        let service =
+            service_with(transport.clone(), &["One._http._tcp.local", "Two._http._tcp.local"]).await;
+
+        service.shutdown().await.unwrap();
+
+        assert!(service.registry.list_services().await.is_empty());
+        let goodbye = &transport.sent_packets()[0];
+        let srv_count = goodbye
+            .answers
+            .iter()
+            .filter(|r| matches!(r, DnsRecord::SRV { ttl: 0, .. }))
+            .count();
+        assert_eq!(srv_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_peer_goodbye_removes_discovered_records() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_with(transport, &[]).await;
+        let peer: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+
+        let records = |ttl| {
+            let mut packet = DnsPacket::new();
+            packet.answers.push(DnsRecord::SRV {
+                name: DnsName::new("Peer._http._tcp.local").unwrap(),
+                ttl,
+                priority: 0,
+                weight: 0,
+                port: 9000,
+                target: DnsName::new("peer.local").unwrap(),
+                cache_flush: false,
+            });
+            packet.answers.push(DnsRecord::A {
+                name: DnsName::new("peer.local").unwrap(),
+                ttl,
+                ip: [192, 168, 1, 30],
+                cache_flush: false,
+            });
+            packet
+        };
+
+        service.process_response(&records(120), &peer).await;
+        assert!(service.registry.get_discovered_service("Peer._http._tcp.local").await.is_some());
+        assert!(service.registry.get_node("peer.local").await.is_some());
+
+        service.process_response(&records(0), &peer).await;
+        assert!(service.registry.get_discovered_service("Peer._http._tcp.local").await.is_none());
+        assert!(service.registry.get_node("peer.local").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_run_stops_tasks_and_says_goodbye() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let service = service_with(transport.clone(), &["Web._http._tcp.local"]).await;
+
+        let shutdown = CancellationToken::new();
+        Arc::clone(&service)
+            .run("_http._tcp.local".to_string(), 60, 60, shutdown.clone())
+            .await;
+        sleep(Duration::from_millis(100)).await;
+
+        shutdown.cancel();
+        sleep(Duration::from_millis(100)).await;
+
+        assert!(service.registry.list_services().await.is_empty());
+        let goodbye = transport.sent_packets().pop().unwrap();
+        assert!(!goodbye.answers.is_empty());
+        assert!(goodbye.answers.iter().all(|r| r.ttl() == 0));
+        // Every task has exited and released its handle on the service.
+        assert_eq!(Arc::strong_count(&service), 1);
+    }
+}
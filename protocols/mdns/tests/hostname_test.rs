@@ -0,0 +1,105 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsRecord, MdnsConfig, MdnsService, ServiceRecord};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_configured_hostname_is_the_single_host_of_local_services() {
+        let config = MdnsConfig {
+            hostname: Some("box.local".to_string()),
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        for (id, service_type, origin) in [
+            ("Web._http._tcp.local", "_http._tcp.local", "web-host.local"),
+            ("Shell._ssh._tcp.local", "_ssh._tcp.local", "ssh-host.local"),
+        ] {
+            service
+                .register_local_service(id.to_string(), service_type.to_string(), 8080, None, origin.to_string(), None)
+                .await
+                .unwrap();
+        }
+
+        let answers = service.create_advertise_packet().await.unwrap().answers;
+
+        let targets: Vec<String> = answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::SRV { target, .. } => Some(target.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(targets, vec!["box.local", "box.local"]);
+        let hosts: Vec<String> = answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::A { name, .. } => Some(name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hosts, vec!["box.local"], "One A record for the one host");
+        assert_eq!(service.hostname(), "box.local");
+    }
+
+    #[tokio::test]
+    async fn test_services_share_the_system_hostname_by_default() {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        for (id, service_type, origin) in [
+            ("Web._http._tcp.local", "_http._tcp.local", "a.local"),
+            ("Shell._ssh._tcp.local", "_ssh._tcp.local", "b.local"),
+        ] {
+            service
+                .register_local_service(id.to_string(), service_type.to_string(), 8080, None, origin.to_string(), None)
+                .await
+                .unwrap();
+        }
+
+        let answers = service.create_advertise_packet().await.unwrap().answers;
+
+        let hosts: Vec<String> = answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::A { name, .. } => Some(name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hosts, vec![service.hostname().to_string()], "One A record for the one host");
+    }
+
+    #[tokio::test]
+    async fn test_service_without_origin_gets_the_system_hostname() {
+        let config = MdnsConfig {
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        assert!(service.hostname().ends_with(".local"), "{}", service.hostname());
+
+        service
+            .register_service(ServiceRecord::builder("Web._http._tcp.local", "_http._tcp.local", 8080).build())
+            .await
+            .unwrap();
+        service
+            .register_service(
+                ServiceRecord::builder("Files._smb._tcp.local", "_smb._tcp.local", 445)
+                    .origin("nas.local")
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        let web = service.registry.get_service("Web._http._tcp.local").await.unwrap();
+        assert_eq!(web.origin, service.hostname());
+        let files = service.registry.get_service("Files._smb._tcp.local").await.unwrap();
+        assert_eq!(files.origin, "nas.local", "An explicit origin is kept when asked to");
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{DnsName, ServiceInstanceName};
+
+    #[test]
+    fn test_parse_instance_name() {
+        let parsed = ServiceInstanceName::parse("My Printer._ipp._tcp.local").unwrap();
+
+        assert_eq!(parsed.instance, "My Printer");
+        assert_eq!(parsed.service_type, "_ipp._tcp");
+        assert_eq!(parsed.domain, "local");
+        assert_eq!(parsed.service_name(), "_ipp._tcp.local");
+    }
+
+    #[test]
+    fn test_parse_instance_name_with_escaped_dot() {
+        let parsed = ServiceInstanceName::parse(r"Office\.Floor2 Printer._ipp._tcp.local.").unwrap();
+
+        assert_eq!(parsed.instance, "Office.Floor2 Printer");
+        assert_eq!(parsed.service_type, "_ipp._tcp");
+        assert_eq!(parsed.domain, "local");
+        assert_eq!(parsed.to_string(), r"Office\.Floor2 Printer._ipp._tcp.local");
+    }
+
+    #[test]
+    fn test_parse_rejects_name_without_protocol() {
+        assert!(ServiceInstanceName::parse("MyService._http.local").is_err());
+        assert!(ServiceInstanceName::parse("_http._tcp.local").is_err());
+    }
+
+    #[test]
+    fn test_from_dns_name() {
+        let name = DnsName::new("MyService._http._tcp.local.").unwrap();
+        let parsed = ServiceInstanceName::from_dns_name(&name).unwrap();
+
+        assert_eq!(parsed.instance, "MyService");
+        assert_eq!(parsed.service_name(), "_http._tcp.local");
+    }
+
+    #[test]
+    fn test_dns_name_honors_escapes_and_round_trips() {
+        let name = DnsName::new(r"Office\.Floor2 Printer._ipp._tcp.local").unwrap();
+
+        assert_eq!(name.labels()[0], "Office.Floor2 Printer");
+        assert_eq!(name.labels().len(), 4);
+        assert_eq!(name.to_string(), r"Office\.Floor2 Printer._ipp._tcp.local");
+        assert_eq!(DnsName::new(&name.to_string()).unwrap(), name);
+    }
+
+    #[test]
+    fn test_decimal_escapes_must_be_ascii() {
+        let name = DnsName::new(r"A\066C._http._tcp.local").unwrap();
+        assert_eq!(name.labels()[0], "ABC");
+
+        assert!(DnsName::new(r"caf\233._http._tcp.local").is_err());
+        assert!(DnsName::new(r"\200._http._tcp.local").is_err());
+        assert!(DnsName::new(r"\256._http._tcp.local").is_err());
+    }
+}
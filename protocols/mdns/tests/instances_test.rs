@@ -0,0 +1,113 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    fn response_with(record: DnsRecord) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(record);
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_instance_assembled_from_several_packets() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+
+        let packets = vec![
+            response_with(DnsRecord::PTR {
+                name: DnsName::new("_ipp._tcp.local").unwrap(),
+                ttl: 120,
+                ptr_name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+                cache_flush: false,
+            }),
+            response_with(DnsRecord::SRV {
+                name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+                ttl: 120,
+                priority: 1,
+                weight: 5,
+                port: 631,
+                target: DnsName::new("printer-host.local").unwrap(),
+                cache_flush: false,
+            }),
+            response_with(DnsRecord::TXT {
+                name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+                ttl: 120,
+                txt_data: b"\x0bpath=/print\x09color=yes".to_vec(),
+                cache_flush: false,
+            }),
+            response_with(DnsRecord::A {
+                name: DnsName::new("printer-host.local").unwrap(),
+                ttl: 120,
+                ip: [192, 168, 1, 50],
+                cache_flush: false,
+            }),
+        ];
+        for packet in &packets {
+            service.process_response(packet, &src).await;
+        }
+
+        let instances = service.registry.instances().await;
+        assert_eq!(instances.len(), 1, "All records should collapse into one instance");
+
+        let printer = &instances["Printer._ipp._tcp.local"];
+        assert_eq!(printer.service_type, "_ipp._tcp.local");
+        assert_eq!(printer.host, "printer-host.local");
+        assert_eq!(printer.port, 631);
+        assert_eq!(printer.priority, 1);
+        assert_eq!(printer.weight, 5);
+        assert_eq!(printer.txt, vec!["path=/print".to_string(), "color=yes".to_string()]);
+        assert_eq!(printer.addresses, vec!["192.168.1.50".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_single_response_yields_discovered_service_record() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+
+        // A typical answer to a browse: the SRV record may precede the PTR record.
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::SRV {
+            name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 1,
+            weight: 5,
+            port: 631,
+            target: DnsName::new("printer-host.local").unwrap(),
+            cache_flush: false,
+        });
+        response.answers.push(DnsRecord::PTR {
+            name: DnsName::new("_ipp._tcp.local").unwrap(),
+            ttl: 120,
+            ptr_name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+            cache_flush: false,
+        });
+        response.answers.push(DnsRecord::A {
+            name: DnsName::new("printer-host.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 50],
+            cache_flush: false,
+        });
+        service.process_response(&response, &src).await;
+
+        let printer = service
+            .registry
+            .get_discovered_service("Printer._ipp._tcp.local")
+            .await
+            .expect("The SRV record should produce a discovered service");
+        assert_eq!(printer.service_type, "_ipp._tcp.local");
+        assert_eq!(printer.origin, "printer-host.local");
+        assert_eq!(printer.port, 631);
+        assert_eq!(printer.priority, Some(1));
+        assert_eq!(printer.weight, Some(5));
+
+        // The A record is still kept as a node for reverse lookups.
+        let node = service.registry.get_node("printer-host.local").await.unwrap();
+        assert_eq!(node.ip_address, "192.168.1.50");
+    }
+}
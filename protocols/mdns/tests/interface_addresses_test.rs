@@ -0,0 +1,63 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsRecord, MdnsConfig, MdnsService};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    async fn advertised_a_records(config: MdnsConfig) -> Vec<Ipv4Addr> {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        packet
+            .answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::A { ip, .. } => Some(Ipv4Addr::from(*ip)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_one_a_record_per_configured_interface() {
+        let config = MdnsConfig {
+            interfaces: vec![Ipv4Addr::new(192, 168, 1, 10), Ipv4Addr::new(10, 0, 0, 10)],
+            ..MdnsConfig::default()
+        };
+        assert_eq!(
+            advertised_a_records(config).await,
+            vec![Ipv4Addr::new(192, 168, 1, 10), Ipv4Addr::new(10, 0, 0, 10)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_single_interface_restricts_fan_out() {
+        let config = MdnsConfig {
+            interface: Some(Ipv4Addr::new(10, 0, 0, 10)),
+            interfaces: vec![Ipv4Addr::new(192, 168, 1, 10), Ipv4Addr::new(10, 0, 0, 10)],
+            ..MdnsConfig::default()
+        };
+        assert_eq!(advertised_a_records(config).await, vec![Ipv4Addr::new(10, 0, 0, 10)]);
+    }
+
+    #[tokio::test]
+    async fn test_enumerated_interfaces_exclude_loopback() {
+        let addresses = advertised_a_records(MdnsConfig::default()).await;
+        assert!(addresses.iter().all(|ip| !ip.is_loopback()), "Got {:?}", addresses);
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{MdnsConfig, MdnsError, MdnsService};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_overlong_service_id_is_rejected_cleanly() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+
+        // Every label fits in 63 bytes, but the whole name is far beyond 255.
+        let suffix = "._http._tcp.local";
+        let labels = vec!["x".repeat(60); 5].join(".");
+        let id = format!("{}{}", &labels[..300 - suffix.len()], suffix);
+        assert_eq!(id.len(), 300);
+
+        let result = service
+            .register_local_service(
+                id,
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await;
+
+        assert!(matches!(result, Err(MdnsError::InvalidName(_))), "Got {:?}", result);
+        assert!(service.registry.list_services().await.is_empty());
+        let packet = service.create_advertise_packet().await.unwrap();
+        assert!(packet.answers.is_empty(), "Nothing should be advertised");
+    }
+
+    #[tokio::test]
+    async fn test_overlong_label_in_origin_is_rejected() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+
+        let result = service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                format!("{}.local", "h".repeat(64)),
+                None,
+            )
+            .await;
+
+        assert!(matches!(result, Err(MdnsError::InvalidName(_))), "Got {:?}", result);
+    }
+}
@@ -0,0 +1,46 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_response_from_ipv4_mapped_source_is_processed() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::A {
+            name: DnsName::new("peer-host.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        });
+        let src: SocketAddr = "[::ffff:192.168.1.30]:5353".parse().unwrap();
+        service.process_response(&response, &src).await;
+
+        let node = service.registry.get_node("peer-host.local").await;
+        assert_eq!(node.map(|n| n.ip_address), Some("192.168.1.30".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_response_from_native_ipv6_source_is_ignored() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::A {
+            name: DnsName::new("peer-host.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        });
+        let src: SocketAddr = "[fe80::1]:5353".parse().unwrap();
+        service.process_response(&response, &src).await;
+
+        assert!(service.registry.get_node("peer-host.local").await.is_none());
+    }
+}
@@ -0,0 +1,211 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn dual_stack_config() -> MdnsConfig {
+        MdnsConfig {
+            ipv6: true,
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            advertised_address_v6: Some("fe80::10".parse().unwrap()),
+            ..MdnsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_aaaa_record_roundtrip() {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::AAAA {
+            name: DnsName::new("host.local").unwrap(),
+            ttl: 120,
+            ip: "fe80::10".parse::<Ipv6Addr>().unwrap().octets(),
+            cache_flush: true,
+        });
+
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+        match &parsed.answers[0] {
+            DnsRecord::AAAA { name, ttl, ip, cache_flush } => {
+                assert_eq!(name.to_string(), "host.local");
+                assert_eq!(*ttl, 120);
+                assert_eq!(Ipv6Addr::from(*ip), "fe80::10".parse::<Ipv6Addr>().unwrap());
+                assert!(*cache_flush);
+            }
+            other => panic!("Expected an AAAA record, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dual_stack_advertises_on_both_families_with_aaaa() {
+        let v4 = Arc::new(MockTransport::new());
+        let v6 = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transports(v4.clone(), v6.clone(), dual_stack_config());
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        service.advertise_services().await.unwrap();
+
+        let v4_sent = v4.sent();
+        let v6_sent = v6.sent();
+        assert_eq!(v4_sent.len(), 1);
+        assert_eq!(v6_sent.len(), 1);
+        assert_eq!(v4_sent[0].1, "224.0.0.251:5353".parse::<SocketAddr>().unwrap());
+        assert_eq!(v6_sent[0].1, "[ff02::fb]:5353".parse::<SocketAddr>().unwrap());
+
+        let packet = &v6.sent_packets()[0];
+        assert!(packet.answers.iter().any(|r| matches!(r, DnsRecord::A { .. })));
+        assert!(packet.answers.iter().any(|r| matches!(
+            r,
+            DnsRecord::AAAA { ip, .. } if Ipv6Addr::from(*ip) == "fe80::10".parse::<Ipv6Addr>().unwrap()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_ipv4_only_service_emits_no_aaaa() {
+        let v4 = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            advertised_address_v6: Some("fe80::10".parse().unwrap()),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(v4.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        assert!(!packet.answers.iter().any(|r| matches!(r, DnsRecord::AAAA { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_listen_reads_from_both_sockets() {
+        let v4 = Arc::new(MockTransport::new());
+        let v6 = Arc::new(MockTransport::new());
+        v4.hold_open();
+        v6.hold_open();
+        let service = MdnsService::with_transports(v4.clone(), v6.clone(), dual_stack_config());
+
+        let mut from_v4 = DnsPacket::new();
+        from_v4.answers.push(DnsRecord::A {
+            name: DnsName::new("peer4.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        });
+        let mut from_v6 = DnsPacket::new();
+        from_v6.answers.push(DnsRecord::AAAA {
+            name: DnsName::new("peer6.local").unwrap(),
+            ttl: 120,
+            ip: "fe80::30".parse::<Ipv6Addr>().unwrap().octets(),
+            cache_flush: false,
+        });
+        v4.push_inbound(from_v4.serialize(), "192.168.1.30:5353".parse().unwrap());
+        v6.push_inbound(from_v6.serialize(), "[fe80::30]:5353".parse().unwrap());
+
+        let listener = {
+            let service = Arc::clone(&service);
+            tokio::spawn(async move { service.listen().await })
+        };
+        sleep(Duration::from_millis(200)).await;
+        listener.abort();
+
+        assert!(service.registry.get_node("peer4.local").await.is_some());
+        let node_v6 = service.registry.get_node_v6("peer6.local").await;
+        assert_eq!(node_v6.map(|n| n.ip_address), Some("fe80::30".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_auto_detected_aaaa_skips_link_local_by_default() {
+        let v4 = Arc::new(MockTransport::new());
+        let v6 = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address_v6: None,
+            ..dual_stack_config()
+        };
+        let service = MdnsService::with_transports(v4, v6, config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        assert!(!packet.answers.iter().any(|r| matches!(
+            r,
+            DnsRecord::AAAA { ip, .. } if Ipv6Addr::from(*ip).is_unicast_link_local()
+        )));
+    }
+
+    fn aaaa_response(ip: &str) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::AAAA {
+            name: DnsName::new("peer6.local").unwrap(),
+            ttl: 120,
+            ip: ip.parse::<Ipv6Addr>().unwrap().octets(),
+            cache_flush: true,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_link_local_aaaa_keeps_the_receiving_scope() {
+        let service = MdnsService::with_transports(
+            Arc::new(MockTransport::new()),
+            Arc::new(MockTransport::new()),
+            dual_stack_config(),
+        );
+        let src = SocketAddr::V6(SocketAddrV6::new("fe80::30".parse().unwrap(), 5353, 0, 3));
+
+        service.process_response(&aaaa_response("fe80::30"), &src).await;
+
+        let node = service.registry.get_node_v6("peer6.local").await.unwrap();
+        assert_eq!(node.scope_id, Some(3));
+        assert_eq!(
+            node.socket_addr(8080),
+            Some(SocketAddr::V6(SocketAddrV6::new("fe80::30".parse().unwrap(), 8080, 0, 3)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_global_aaaa_has_no_scope() {
+        let service = MdnsService::with_transports(
+            Arc::new(MockTransport::new()),
+            Arc::new(MockTransport::new()),
+            dual_stack_config(),
+        );
+        let src = SocketAddr::V6(SocketAddrV6::new("fe80::30".parse().unwrap(), 5353, 0, 3));
+
+        service.process_response(&aaaa_response("2001:db8::30"), &src).await;
+
+        let node = service.registry.get_node_v6("peer6.local").await.unwrap();
+        assert_eq!(node.scope_id, None);
+        assert_eq!(node.socket_addr(80), Some("[2001:db8::30]:80".parse().unwrap()));
+    }
+}
@@ -0,0 +1,100 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn query_with_known_answer(known: &[(&str, u32)]) -> DnsPacket {
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        for (instance, ttl) in known {
+            query.answers.push(DnsRecord::PTR {
+                name: DnsName::new("_http._tcp.local").unwrap(),
+                ttl: *ttl,
+                ptr_name: DnsName::new(instance).unwrap(),
+                cache_flush: false,
+            });
+        }
+        query
+    }
+
+    async fn responder(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        for id in ["One._http._tcp.local", "Two._http._tcp.local"] {
+            service
+                .register_local_service(
+                    id.to_string(),
+                    "_http._tcp.local".to_string(),
+                    8080,
+                    Some(120),
+                    "MyHost.local".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        service
+    }
+
+    fn answered_instances(packet: &DnsPacket) -> Vec<String> {
+        let mut instances: Vec<String> = packet
+            .answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::PTR { ptr_name, .. } => Some(ptr_name.to_string()),
+                _ => None,
+            })
+            .collect();
+        instances.sort();
+        instances
+    }
+
+    #[tokio::test]
+    async fn test_known_answers_with_fresh_ttl_are_suppressed() {
+        let transport = Arc::new(MockTransport::new());
+        let service = responder(transport.clone()).await;
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        // "One" is known with 60s of 120s left (exactly half), "Two" only with 59s.
+        let query = query_with_known_answer(&[
+            ("One._http._tcp.local", 60),
+            ("Two._http._tcp.local", 59),
+        ]);
+        service.process_query(&query, &src).await;
+
+        let reply = &transport.sent_packets()[0];
+        assert_eq!(answered_instances(reply), vec!["Two._http._tcp.local"]);
+        assert!(!reply.answers.iter().any(|record| matches!(
+            record,
+            DnsRecord::SRV { name, .. } if name.to_string() == "One._http._tcp.local"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_no_reply_when_every_answer_is_known() {
+        let transport = Arc::new(MockTransport::new());
+        let service = responder(transport.clone()).await;
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        let query = query_with_known_answer(&[
+            ("One._http._tcp.local", 120),
+            ("Two._http._tcp.local", 120),
+        ]);
+        service.process_query(&query, &src).await;
+
+        assert!(transport.sent().is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    fn ptr_query(service_type: &str) -> DnsPacket {
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new(service_type).unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        query
+    }
+
+    #[tokio::test]
+    async fn test_legacy_unicast_reply_uses_configured_ttl_cap() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            legacy_unicast_ttl_cap: 30,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let src: SocketAddr = "192.168.1.20:34567".parse().unwrap();
+        service.process_query(&ptr_query("_http._tcp.local"), &src).await;
+
+        let sent = transport.sent();
+        assert_eq!(sent.len(), 1, "Exactly one reply should be sent");
+        assert_eq!(sent[0].1, src, "Legacy unicast reply should go straight to the querier");
+
+        let reply = DnsPacket::parse(&sent[0].0).unwrap();
+        assert!(!reply.answers.is_empty());
+        assert!(reply.answers.iter().all(|record| record.ttl() == 30));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_unicast_reply_echoes_query_id_and_question() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut query = ptr_query("_http._tcp.local");
+        query.id = 0x1234;
+        let src: SocketAddr = "192.168.1.20:34567".parse().unwrap();
+        service.process_query(&query, &src).await;
+
+        let sent = transport.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1, src);
+        let reply = DnsPacket::parse(&sent[0].0).unwrap();
+        assert_eq!(reply.id, 0x1234, "Legacy reply should echo the query id");
+        assert_eq!(reply.questions.len(), 1);
+        assert_eq!(reply.questions[0].qname.to_string(), "_http._tcp.local");
+        assert!(reply.answers.iter().all(|record| record.ttl() <= 10));
+    }
+
+    #[tokio::test]
+    async fn test_multicast_reply_keeps_full_ttl() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&ptr_query("_http._tcp.local"), &src).await;
+
+        let reply = transport.sent_packets().remove(0);
+        assert_eq!(reply.id, 0);
+        assert!(reply.questions.is_empty());
+        assert!(reply.answers.iter().all(|record| record.ttl() == 120));
+    }
+}
@@ -0,0 +1,40 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{timeout, Duration};
+
+    #[tokio::test]
+    async fn test_flood_drops_and_counts_without_stalling_recv() {
+        let transport = Arc::new(MockTransport::new());
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let bytes = query.serialize();
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        for _ in 0..2000 {
+            transport.push_inbound(bytes.clone(), src);
+        }
+
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        // The receive loop must drain the whole flood and hit the end of the script promptly,
+        // rather than blocking on the processing of earlier packets.
+        let result = timeout(Duration::from_secs(2), service.listen()).await;
+        assert!(result.is_ok(), "recv loop stalled behind packet processing");
+        assert!(result.unwrap().is_err(), "listen should end when the transport is exhausted");
+
+        let dropped = service.dropped_packets();
+        assert!(dropped > 0, "overflowing packets should be counted");
+        assert!(dropped < 2000, "packets that fit in the queue should not be dropped");
+    }
+}
@@ -0,0 +1,62 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsError, MdnsService};
+    use std::io::ErrorKind;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{timeout, Duration};
+
+    fn address_response() -> Vec<u8> {
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::A {
+            name: DnsName::new("peer.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 20],
+            cache_flush: false,
+        });
+        response.serialize()
+    }
+
+    #[tokio::test]
+    async fn test_listen_keeps_receiving_after_transient_errors() {
+        let transport = Arc::new(MockTransport::new());
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        transport.push_inbound_error(ErrorKind::ConnectionRefused);
+        transport.push_inbound_error(ErrorKind::Interrupted);
+        transport.push_inbound_error(ErrorKind::ConnectionReset);
+        transport.push_inbound(address_response(), src);
+        transport.hold_open();
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        let listener = {
+            let service = service.clone();
+            tokio::spawn(async move { service.listen().await })
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(!listener.is_finished(), "transient errors must not end listen");
+        let node = service.registry.get_node("peer.local").await;
+        assert!(node.is_some(), "the packet after the errors should be processed");
+        listener.abort();
+    }
+
+    #[tokio::test]
+    async fn test_listen_returns_on_fatal_error() {
+        let transport = Arc::new(MockTransport::new());
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        transport.push_inbound_error(ErrorKind::NotConnected);
+        transport.push_inbound(address_response(), src);
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        let result = timeout(Duration::from_secs(2), service.listen()).await.unwrap();
+
+        match result {
+            Err(MdnsError::NetworkError(err)) => assert_eq!(err.kind(), ErrorKind::NotConnected),
+            other => panic!("Expected the fatal error, got {:?}", other),
+        }
+        assert!(service.registry.get_node("peer.local").await.is_none());
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+    use std::sync::Arc;
+    use tokio::net::UdpSocket;
+    use tokio::time::{sleep, timeout, Duration, Instant};
+
+    /// Port used by this harness so it never talks to a real mDNS responder on 5353.
+    const TEST_PORT: u16 = 53530;
+
+    /// Returns true if a datagram multicast to 224.0.0.251 on `port` loops back to this host.
+    async fn multicast_loopback_available(port: u16) -> bool {
+        let probe = async {
+            let socket = socket2::Socket::new(
+                socket2::Domain::IPV4,
+                socket2::Type::DGRAM,
+                Some(socket2::Protocol::UDP),
+            )?;
+            socket.set_reuse_address(true)?;
+            #[cfg(unix)]
+            socket.set_reuse_port(true)?;
+            socket.set_nonblocking(true)?;
+            socket.bind(&SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port)).into())?;
+            let socket = UdpSocket::from_std(socket.into())?;
+            socket.join_multicast_v4(Ipv4Addr::new(224, 0, 0, 251), Ipv4Addr::UNSPECIFIED)?;
+
+            let marker = b"nautilus-mdns-probe";
+            socket
+                .send_to(marker, SocketAddrV4::new(Ipv4Addr::new(224, 0, 0, 251), port))
+                .await?;
+            let mut buf = [0u8; 64];
+            loop {
+                let (len, _) = socket.recv_from(&mut buf).await?;
+                if &buf[..len] == marker {
+                    return Ok::<bool, std::io::Error>(true);
+                }
+            }
+        };
+        matches!(timeout(Duration::from_millis(500), probe).await, Ok(Ok(true)))
+    }
+
+    #[tokio::test]
+    async fn test_two_services_discover_each_other_over_multicast() {
+        if !multicast_loopback_available(TEST_PORT).await {
+            eprintln!("Skipping: multicast loopback is unavailable in this environment");
+            return;
+        }
+
+        let advertiser = MdnsService::with_config(MdnsConfig {
+            port: TEST_PORT,
+            advertised_address: Some(Ipv4Addr::LOCALHOST),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        })
+        .await
+        .expect("advertiser socket setup");
+        let browser = MdnsService::with_config(MdnsConfig {
+            port: TEST_PORT,
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        })
+        .await
+        .expect("browser socket setup");
+
+        advertiser
+            .register_local_service(
+                "Harness._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "harness-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let listener = {
+            let browser = Arc::clone(&browser);
+            tokio::spawn(async move { browser.listen().await })
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut discovered = false;
+        while Instant::now() < deadline {
+            advertiser.advertise_services().await.unwrap();
+            sleep(Duration::from_millis(200)).await;
+            if browser
+                .registry
+                .list_discovered_services()
+                .await
+                .iter()
+                .any(|service| service.id == "Harness._http._tcp.local")
+            {
+                discovered = true;
+                break;
+            }
+        }
+        listener.abort();
+
+        assert!(discovered, "browser should discover the advertised service");
+        let node = browser.registry.get_node("harness-host.local").await;
+        assert_eq!(node.map(|n| n.ip_address), Some("127.0.0.1".to_string()));
+    }
+}
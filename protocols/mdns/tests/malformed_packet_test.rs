@@ -0,0 +1,182 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn a_record(host: &str) -> DnsRecord {
+        DnsRecord::A {
+            name: DnsName::new(host).unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        }
+    }
+
+    fn valid_packet() -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 9000,
+            target: DnsName::new("peer.local").unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::TXT {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            txt_data: b"\x07path=/a".to_vec(),
+            cache_flush: false,
+        });
+        packet.answers.push(a_record("peer.local"));
+        packet
+    }
+
+    #[test]
+    fn test_every_truncation_is_rejected_without_panicking() {
+        let bytes = valid_packet().serialize();
+        assert!(DnsPacket::parse(&bytes).is_ok());
+        for len in 0..bytes.len() {
+            assert!(DnsPacket::parse(&bytes[..len]).is_err(), "prefix of {} bytes parsed", len);
+        }
+    }
+
+    #[test]
+    fn test_random_and_mutated_inputs_never_panic() {
+        let mut rng = StdRng::seed_from_u64(0x6d646e73);
+        let valid = valid_packet().serialize();
+        for _ in 0..5000 {
+            let mut bytes = if rng.gen_bool(0.5) {
+                let len = rng.gen_range(0..64);
+                (0..len).map(|_| rng.gen()).collect::<Vec<u8>>()
+            } else {
+                valid.clone()
+            };
+            for _ in 0..rng.gen_range(1..4) {
+                if !bytes.is_empty() {
+                    let index = rng.gen_range(0..bytes.len());
+                    bytes[index] = rng.gen();
+                }
+            }
+            if let Ok(packet) = DnsPacket::parse(&bytes) {
+                let _ = packet.validate();
+            }
+        }
+    }
+
+    #[test]
+    fn test_header_counts_must_match_the_sections() {
+        let mut bytes = valid_packet().serialize();
+        bytes[7] = 4; // ANCOUNT claims one answer more than present.
+        assert!(DnsPacket::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_rdata_must_match_its_length() {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(a_record("peer.local"));
+        let mut bytes = packet.serialize();
+        // Claim five bytes of RDATA for the four-byte address and append one.
+        let rdlength_at = bytes.len() - 6;
+        bytes[rdlength_at + 1] = 5;
+        bytes.push(0);
+        assert!(DnsPacket::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_record_types_are_skipped() {
+        // A response header announcing two answers.
+        let mut bytes = vec![0, 0, 0x84, 0x00, 0, 0, 0, 2, 0, 0, 0, 0];
+        // An HINFO record (type 13) for peer.local with three bytes of RDATA.
+        DnsName::new("peer.local").unwrap().write(&mut bytes);
+        bytes.extend_from_slice(&[0, 13, 0, 1, 0, 0, 0, 120, 0, 3, 1, b'x', 0]);
+        a_record("peer.local").write(&mut bytes);
+
+        let packet = DnsPacket::parse(&bytes).unwrap();
+        assert_eq!(packet.answers.len(), 1);
+        assert!(matches!(packet.answers[0], DnsRecord::A { .. }));
+    }
+
+    #[test]
+    fn test_validate_rejects_garbage_contents() {
+        assert!(valid_packet().validate().is_ok());
+
+        let mut empty_name = DnsPacket::new();
+        empty_name.answers.push(a_record(""));
+        assert!(empty_name.validate().is_err());
+
+        let mut bad_txt = DnsPacket::new();
+        bad_txt.answers.push(DnsRecord::TXT {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            txt_data: b"\x09short".to_vec(),
+            cache_flush: false,
+        });
+        assert!(bad_txt.validate().is_err());
+
+        let mut bad_class = DnsPacket::new();
+        bad_class.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 3,
+            unicast_response: false,
+        });
+        assert!(bad_class.validate().is_err());
+
+        let mut long_name = DnsPacket::new();
+        let labels: Vec<String> = (0..5).map(|_| "a".repeat(60)).collect();
+        let parsed = {
+            let mut bytes = Vec::new();
+            for label in &labels {
+                bytes.push(label.len() as u8);
+                bytes.extend_from_slice(label.as_bytes());
+            }
+            bytes.push(0);
+            DnsName::parse(&mut std::io::Cursor::new(&bytes[..])).unwrap()
+        };
+        long_name.answers.push(DnsRecord::A {
+            name: parsed,
+            ttl: 120,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        });
+        assert!(long_name.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_listen_drops_invalid_packets() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+        let listener = {
+            let service = Arc::clone(&service);
+            tokio::spawn(async move { service.listen().await })
+        };
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+
+        let mut invalid = valid_packet();
+        invalid.answers.push(DnsRecord::TXT {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            txt_data: b"\x09short".to_vec(),
+            cache_flush: false,
+        });
+        transport.push_inbound(invalid.serialize(), src);
+        sleep(Duration::from_millis(100)).await;
+        assert!(service.registry.get_node("peer.local").await.is_none());
+
+        transport.push_inbound(valid_packet().serialize(), src);
+        sleep(Duration::from_millis(100)).await;
+        assert!(service.registry.get_node("peer.local").await.is_some());
+
+        listener.abort();
+    }
+}
@@ -0,0 +1,52 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{MdnsConfig, MdnsService};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_registration_beyond_limit_fails() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            max_local_services: Some(2),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+
+        let mut results = Vec::new();
+        for name in ["One", "Two", "Three"] {
+            results.push(
+                service
+                    .register_local_service(
+                        format!("{}._http._tcp.local", name),
+                        "_http._tcp.local".to_string(),
+                        8080,
+                        Some(120),
+                        "host.local".to_string(),
+                        None,
+                    )
+                    .await,
+            );
+        }
+
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(results[2].is_err(), "The third registration should exceed the limit");
+        assert_eq!(service.registry.list_services().await.len(), 2);
+
+        // Re-registering an existing service replaces it rather than adding one.
+        assert!(service
+            .register_local_service(
+                "One._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                9090,
+                Some(120),
+                "host.local".to_string(),
+                None,
+            )
+            .await
+            .is_ok());
+    }
+}
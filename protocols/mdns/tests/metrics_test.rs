@@ -0,0 +1,74 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{
+        CancellationToken, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsMetrics,
+        MdnsService, RunMode,
+    };
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    #[tokio::test]
+    async fn test_metrics_count_traffic_and_known_nodes() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            run_mode: RunMode::ResponderOnly,
+            registry_print_interval: None,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        assert_eq!(service.metrics(), MdnsMetrics::default());
+
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let src: SocketAddr = "192.168.1.20:34567".parse().unwrap();
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::A {
+            name: DnsName::new("peer.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        });
+        transport.push_inbound(query.serialize(), src);
+        transport.push_inbound(response.serialize(), "192.168.1.30:5353".parse().unwrap());
+        transport.push_inbound(vec![0x00, 0x01, 0x02], src);
+
+        let shutdown = CancellationToken::new();
+        Arc::clone(&service)
+            .run("_http._tcp.local".to_string(), 60, 60, shutdown.clone())
+            .await;
+        sleep(Duration::from_millis(200)).await;
+
+        let metrics = service.metrics();
+        shutdown.cancel();
+        assert_eq!(metrics.packets_received, 3);
+        assert_eq!(metrics.parse_failures, 1);
+        assert_eq!(metrics.queries_answered, 1);
+        assert_eq!(metrics.nodes_known, 1);
+        assert_eq!(metrics.packets_sent as usize, transport.sent().len());
+        assert!(metrics.packets_sent >= 2, "The announcement and the answer were sent");
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_instances_sharing_origin_get_one_a_record() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        for name in ["Alpha", "Beta", "Gamma"] {
+            service
+                .register_local_service(
+                    format!("{}._http._tcp.local", name),
+                    "_http._tcp.local".to_string(),
+                    8080,
+                    Some(120),
+                    "SharedHost.local".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &src).await;
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1, "All instances should share a single response");
+        let reply = &sent[0];
+        let count =
+            |records: &[DnsRecord], f: fn(&DnsRecord) -> bool| records.iter().filter(|r| f(r)).count();
+        assert_eq!(reply.answers.len(), 3, "Only the PTR records answer the question");
+        assert_eq!(count(&reply.answers, |r| matches!(r, DnsRecord::PTR { .. })), 3);
+        assert_eq!(count(&reply.additionals, |r| matches!(r, DnsRecord::SRV { .. })), 3);
+        assert_eq!(count(&reply.additionals, |r| matches!(r, DnsRecord::A { .. })), 1);
+    }
+}
@@ -0,0 +1,36 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsPacket, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_default_config_uses_standard_group_and_port() {
+        let config = MdnsConfig::default();
+        assert_eq!(config.multicast_addr, Ipv4Addr::new(224, 0, 0, 251));
+        assert_eq!(config.port, 5353);
+        assert_eq!(config.interface, None);
+        assert_eq!(config.multicast_ttl, 255);
+        assert!(config.multicast_loop);
+    }
+
+    #[tokio::test]
+    async fn test_packets_go_to_configured_group_and_port() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            multicast_addr: Ipv4Addr::new(239, 255, 0, 1),
+            port: 15353,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+
+        service.send_packet(&DnsPacket::new()).await.unwrap();
+
+        let sent = transport.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1, "239.255.0.1:15353".parse::<SocketAddr>().unwrap());
+    }
+}
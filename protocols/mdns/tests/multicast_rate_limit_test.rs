@@ -0,0 +1,113 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService, TimingPolicy};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    async fn web_service(transport: Arc<MockTransport>, timing: TimingPolicy) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            timing,
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_repeated_advertisements_within_a_second_are_coalesced() {
+        let transport = Arc::new(MockTransport::new());
+        let service = web_service(transport.clone(), TimingPolicy::default()).await;
+
+        service.advertise_services().await.unwrap();
+        service.advertise_services().await.unwrap();
+        assert_eq!(transport.sent().len(), 1, "The second advertisement should be coalesced");
+
+        let state = service.suppression_state();
+        assert_eq!(state["SRV Web._http._tcp.local"].suppressed, 1);
+        assert!(state.contains_key("A web-host.local -> 192.168.1.10"));
+    }
+
+    #[tokio::test]
+    async fn test_advertisement_is_sent_again_after_rate_limit() {
+        let transport = Arc::new(MockTransport::new());
+        let timing = TimingPolicy {
+            multicast_rate_limit: Duration::from_millis(50),
+            ..TimingPolicy::default()
+        };
+        let service = web_service(transport.clone(), timing).await;
+
+        service.advertise_services().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        service.advertise_services().await.unwrap();
+        assert_eq!(transport.sent().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_multicast_query_response_is_delayed() {
+        let transport = Arc::new(MockTransport::new());
+        let timing = TimingPolicy {
+            response_delay_min: Duration::from_millis(60),
+            response_delay_max: Duration::from_millis(80),
+            ..TimingPolicy::default()
+        };
+        let service = web_service(transport.clone(), timing).await;
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        let started = Instant::now();
+        service.process_query(&query, &src).await;
+        assert!(started.elapsed() >= Duration::from_millis(60));
+        assert_eq!(transport.sent().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unicast_query_response_is_not_delayed() {
+        let transport = Arc::new(MockTransport::new());
+        let timing = TimingPolicy {
+            response_delay_min: Duration::from_secs(5),
+            response_delay_max: Duration::from_secs(5),
+            ..TimingPolicy::default()
+        };
+        let service = web_service(transport.clone(), timing).await;
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        let started = Instant::now();
+        service.process_query(&query, &src).await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(transport.sent().len(), 1);
+    }
+}
@@ -0,0 +1,68 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn hash_of(name: &DnsName) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_names_compare_and_hash_ignoring_case() {
+        let lower = DnsName::new("_http._tcp.local").unwrap();
+        let mixed = DnsName::new("_HTTP._Tcp.LOCAL").unwrap();
+
+        assert_eq!(lower, mixed);
+        assert_eq!(hash_of(&lower), hash_of(&mixed));
+        assert_eq!(mixed.to_string(), "_HTTP._Tcp.LOCAL", "Spelling should be kept for display");
+        assert_ne!(DnsName::new("ab.c").unwrap(), DnsName::new("a.bc").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_mixed_case_query_matches_lowercase_service() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_HTTP._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &peer).await;
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1, "The mixed-case query should be answered");
+        let ptr = sent[0].answers.iter().find_map(|record| match record {
+            DnsRecord::PTR { ptr_name, .. } => Some(ptr_name.to_string()),
+            _ => None,
+        });
+        assert_eq!(ptr.as_deref(), Some("web._http._tcp.local"));
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord};
+
+    fn name(name: &str) -> DnsName {
+        DnsName::new(name).unwrap()
+    }
+
+    fn advertisement() -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        for instance in ["One", "Two", "Three"] {
+            let instance = name(&format!("{}._http._tcp.local", instance));
+            packet.answers.push(DnsRecord::PTR {
+                name: name("_http._tcp.local"),
+                ttl: 120,
+                ptr_name: instance.clone(),
+                cache_flush: false,
+            });
+            packet.answers.push(DnsRecord::SRV {
+                name: instance,
+                ttl: 120,
+                priority: 0,
+                weight: 0,
+                port: 8080,
+                target: name("MyHost.local"),
+                cache_flush: true,
+            });
+        }
+        packet.answers.push(DnsRecord::A {
+            name: name("MyHost.local"),
+            ttl: 120,
+            ip: [192, 168, 1, 10],
+            cache_flush: true,
+        });
+        packet
+    }
+
+    #[test]
+    fn test_shared_names_are_compressed_and_round_trip() {
+        let packet = advertisement();
+        let mut naive = vec![0u8; 12];
+        for record in &packet.answers {
+            record.write(&mut naive);
+        }
+
+        let bytes = packet.serialize();
+        assert!(
+            bytes.len() < naive.len(),
+            "compressed {} bytes, naive {} bytes",
+            bytes.len(),
+            naive.len()
+        );
+
+        let parsed = DnsPacket::parse(&bytes).unwrap();
+        assert_eq!(parsed.answers.len(), packet.answers.len());
+        for (parsed, original) in parsed.answers.iter().zip(&packet.answers) {
+            assert_eq!(parsed.name(), original.name());
+            assert_eq!(parsed.ttl(), original.ttl());
+            assert_eq!(parsed.cache_flush(), original.cache_flush());
+        }
+        assert!(matches!(
+            &parsed.answers[5],
+            DnsRecord::SRV { name: instance, target, port: 8080, .. }
+                if instance.to_string() == "Three._http._tcp.local"
+                    && target.to_string() == "MyHost.local"
+        ));
+        assert!(matches!(
+            &parsed.answers[2],
+            DnsRecord::PTR { ptr_name, .. } if ptr_name.to_string() == "Two._http._tcp.local"
+        ));
+    }
+
+    #[test]
+    fn test_question_name_is_reused_by_answers() {
+        let mut packet = DnsPacket::new();
+        packet.questions.push(DnsQuestion {
+            qname: name("_http._tcp.local"),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        packet.answers.push(DnsRecord::PTR {
+            name: name("_http._tcp.local"),
+            ttl: 120,
+            ptr_name: name("Web._http._tcp.local"),
+            cache_flush: false,
+        });
+
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+        assert_eq!(parsed.questions[0].qname.to_string(), "_http._tcp.local");
+        assert!(matches!(
+            &parsed.answers[0],
+            DnsRecord::PTR { name, ptr_name, .. }
+                if name.to_string() == "_http._tcp.local"
+                    && ptr_name.to_string() == "Web._http._tcp.local"
+        ));
+    }
+
+    #[test]
+    fn test_pointer_loop_is_rejected() {
+        // One question whose name is a pointer to itself.
+        let mut bytes = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        bytes.extend_from_slice(&[0xC0, 12, 0, 12, 0, 1]);
+
+        assert!(DnsPacket::parse(&bytes).is_err());
+    }
+}
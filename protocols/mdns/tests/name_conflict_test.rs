@@ -0,0 +1,108 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DiscoveryEvent, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    async fn service() -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        service
+    }
+
+    fn a_response(ip: [u8; 4]) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("myhost.local").unwrap(),
+            ttl: 120,
+            ip,
+            cache_flush: true,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_foreign_address_for_our_host_is_a_conflict() {
+        let service = service().await;
+        let mut events = service.subscribe();
+        let src: SocketAddr = "192.168.1.66:5353".parse().unwrap();
+
+        service.process_response(&a_response([192, 168, 1, 66]), &src).await;
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DiscoveryEvent::Conflict {
+                name: "myhost.local".to_string(),
+                ours: "192.168.1.10".to_string(),
+                theirs: "192.168.1.66".to_string(),
+            }
+        );
+        assert!(
+            service.registry.get_node("myhost.local").await.is_none(),
+            "The foreign record must not be stored"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_echo_of_our_own_address_is_not_a_conflict() {
+        let service = service().await;
+        let mut events = service.subscribe();
+        let src: SocketAddr = "192.168.1.10:5353".parse().unwrap();
+
+        service.process_response(&a_response([192, 168, 1, 10]), &src).await;
+
+        assert!(matches!(events.try_recv(), Ok(DiscoveryEvent::ServiceDiscovered(_))));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_foreign_srv_for_our_instance_is_a_conflict() {
+        let service = service().await;
+        let mut events = service.subscribe();
+        let src: SocketAddr = "192.168.1.66:5353".parse().unwrap();
+
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new("MyService._http._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 9090,
+            target: DnsName::new("OtherHost.local").unwrap(),
+            cache_flush: true,
+        });
+        service.process_response(&packet, &src).await;
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DiscoveryEvent::Conflict {
+                name: "MyService._http._tcp.local".to_string(),
+                ours: "MyHost.local:8080".to_string(),
+                theirs: "OtherHost.local:9090".to_string(),
+            }
+        );
+        assert!(service
+            .registry
+            .get_discovered_service("MyService._http._tcp.local")
+            .await
+            .is_none());
+    }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockClock, MockTransport};
+    use mdns::{Clock, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::time::Duration;
+
+    fn service_with_clock() -> (Arc<MockClock>, Arc<MdnsService>) {
+        let clock = Arc::new(MockClock::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service =
+            MdnsService::with_transport_and_clock(Arc::new(MockTransport::new()), config, clock.clone());
+        (clock, service)
+    }
+
+    fn address_records(ttl: u32) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("peer.local").unwrap(),
+            ttl,
+            ip: [192, 168, 1, 30],
+            cache_flush: true,
+        });
+        packet.answers.push(DnsRecord::AAAA {
+            name: DnsName::new("peer.local").unwrap(),
+            ttl,
+            ip: "fe80::30".parse::<std::net::Ipv6Addr>().unwrap().octets(),
+            cache_flush: true,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_nodes_record_their_source_and_last_seen() {
+        let (clock, service) = service_with_clock();
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+
+        service.process_response(&address_records(120), &src).await;
+
+        let nodes = service.registry.list_nodes().await;
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].source, Some(src));
+        assert_eq!(nodes[0].last_seen, Some(clock.now()));
+        let node_v6 = service.registry.get_node_v6("peer.local").await.unwrap();
+        assert_eq!(node_v6.source, Some(src));
+        assert_eq!(node_v6.last_seen, Some(clock.now()));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_moves_last_seen_and_delays_expiry() {
+        let (clock, service) = service_with_clock();
+        let first: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+        let second: SocketAddr = "192.168.1.31:5353".parse().unwrap();
+
+        service.process_response(&address_records(10), &first).await;
+        clock.advance(Duration::from_secs(8));
+        service.process_response(&address_records(10), &second).await;
+
+        let node = service.registry.get_node("peer.local").await.unwrap();
+        assert_eq!(node.source, Some(second));
+        assert_eq!(node.last_seen, Some(clock.now()));
+
+        // Ten seconds after the first sighting the refreshed node is still alive.
+        clock.advance(Duration::from_secs(2));
+        assert!(service.registry.is_alive(&node));
+        assert!(service.registry.sweep_expired().await.is_empty());
+
+        clock.advance(Duration::from_secs(8));
+        assert!(!service.registry.is_alive(&node));
+        assert_eq!(
+            service.registry.sweep_expired().await,
+            vec!["peer.local".to_string(), "peer.local".to_string()]
+        );
+        assert!(service.registry.list_nodes().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ipv4_mapped_source_is_stored_as_ipv4() {
+        let (_clock, service) = service_with_clock();
+        let mapped: SocketAddr = "[::ffff:192.168.1.30]:5353".parse().unwrap();
+
+        service.process_response(&address_records(120), &mapped).await;
+
+        let node = service.registry.get_node("peer.local").await.unwrap();
+        assert_eq!(node.source, Some("192.168.1.30:5353".parse().unwrap()));
+    }
+}
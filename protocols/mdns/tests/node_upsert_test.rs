@@ -0,0 +1,36 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    fn a_response(ttl: u32) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("peer.local").unwrap(),
+            ttl,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_repeated_a_records_update_a_single_node() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+
+        for ttl in [60, 90, 120] {
+            service.process_response(&a_response(ttl), &src).await;
+        }
+
+        let nodes = service.registry.list_nodes().await;
+        assert_eq!(nodes.len(), 1, "Re-announcements should not duplicate the node");
+        assert_eq!(nodes[0].id, "peer.local");
+        assert_eq!(nodes[0].ip_address, "192.168.1.30");
+        assert_eq!(nodes[0].ttl, Some(120), "The latest TTL should win");
+    }
+}
@@ -0,0 +1,97 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    fn nsec(name: &str, types: Vec<u16>) -> DnsRecord {
+        DnsRecord::NSEC {
+            name: DnsName::new(name).unwrap(),
+            ttl: 120,
+            next_domain: DnsName::new(name).unwrap(),
+            types,
+            cache_flush: true,
+        }
+    }
+
+    #[test]
+    fn test_nsec_round_trips() {
+        let mut packet = DnsPacket::new();
+        packet.additionals.push(nsec("Printer._ipp._tcp.local", vec![16, 33, 47, 1]));
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+
+        match &parsed.additionals[0] {
+            DnsRecord::NSEC { name, next_domain, types, cache_flush, .. } => {
+                assert_eq!(name.to_string(), "Printer._ipp._tcp.local");
+                assert_eq!(next_domain.to_string(), "Printer._ipp._tcp.local");
+                assert_eq!(types, &vec![1, 16, 33, 47]);
+                assert!(cache_flush);
+            }
+            other => panic!("Expected an NSEC record, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nsec_with_empty_window_is_rejected() {
+        let mut packet = DnsPacket::new();
+        packet.additionals.push(nsec("peer.local", vec![1]));
+        let mut bytes = packet.serialize();
+        // Shrink the bitmap window to zero bytes; RFC 4034 requires 1 to 32.
+        let len = bytes.len();
+        bytes[len - 2] = 0;
+        bytes.pop();
+        bytes[len - 16] -= 1;
+        assert!(DnsPacket::parse(&bytes).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_denied_address_type_is_not_queried() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::SRV {
+            name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 631,
+            target: DnsName::new("printer-host.local").unwrap(),
+            cache_flush: false,
+        });
+        // The host only has an AAAA record.
+        response.additionals.push(nsec("printer-host.local", vec![28, 47]));
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+        service.process_response(&response, &src).await;
+
+        assert_eq!(service.registry.record_types("printer-host.local"), Some(vec![28, 47]));
+        assert!(service.registry.denies_type("printer-host.local", 1));
+        assert!(!service.registry.denies_type("printer-host.local", 28));
+
+        assert_eq!(service.refresh_expired_addresses().await.unwrap(), 0);
+        assert!(transport.sent().is_empty(), "No A query should go to a host that denies A");
+    }
+
+    #[tokio::test]
+    async fn test_goodbye_nsec_forgets_record_types() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+
+        let mut response = DnsPacket::new();
+        response.additionals.push(nsec("peer.local", vec![1]));
+        service.process_response(&response, &src).await;
+        assert!(service.registry.denies_type("peer.local", 28));
+
+        let mut goodbye = DnsPacket::new();
+        let mut record = nsec("peer.local", vec![1]);
+        if let DnsRecord::NSEC { ttl, .. } = &mut record {
+            *ttl = 0;
+        }
+        goodbye.additionals.push(record);
+        service.process_response(&goodbye, &src).await;
+        assert_eq!(service.registry.record_types("peer.local"), None);
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService, ResponderMode};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_one_shot_resolver_never_answers_queries() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            mode: ResponderMode::OneShot,
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut query = DnsPacket::new();
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &src).await;
+
+        assert!(transport.sent().is_empty(), "A resolver must not respond to queries");
+    }
+}
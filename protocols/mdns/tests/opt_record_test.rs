@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    /// A response as sent by an EDNS0-aware responder: PTR, SRV, and A answers, with
+    /// compressed names, and an OPT record advertising a 1440-byte payload.
+    const CAPTURED_RESPONSE: &[u8] = &[
+        0x00, 0x00, 0x84, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01,
+        // PTR _http._tcp.local -> Web._http._tcp.local
+        0x05, b'_', b'h', b't', b't', b'p', 0x04, b'_', b't', b'c', b'p',
+        0x05, b'l', b'o', b'c', b'a', b'l', 0x00,
+        0x00, 0x0c, 0x00, 0x01, 0x00, 0x00, 0x11, 0x94, 0x00, 0x06,
+        0x03, b'W', b'e', b'b', 0xc0, 0x0c,
+        // SRV Web._http._tcp.local -> host.local:8080
+        0xc0, 0x28, 0x00, 0x21, 0x80, 0x01, 0x00, 0x00, 0x00, 0x78, 0x00, 0x0d,
+        0x00, 0x00, 0x00, 0x00, 0x1f, 0x90, 0x04, b'h', b'o', b's', b't', 0xc0, 0x17,
+        // A host.local -> 192.168.1.30
+        0xc0, 0x40, 0x00, 0x01, 0x80, 0x01, 0x00, 0x00, 0x00, 0x78, 0x00, 0x04,
+        0xc0, 0xa8, 0x01, 0x1e,
+        // OPT, UDP payload size 1440
+        0x00, 0x00, 0x29, 0x05, 0xa0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_opt_record_roundtrip() {
+        let mut packet = DnsPacket::new();
+        packet.flags = 0x0000;
+        packet.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        packet.additionals.push(DnsRecord::OPT {
+            udp_payload_size: 4096,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: true,
+            options: vec![0x00, 0x04, 0x00, 0x02, 0xab, 0xcd],
+        });
+
+        let parsed = DnsPacket::parse(&packet.serialize()).unwrap();
+        assert_eq!(parsed.questions.len(), 1);
+        assert_eq!(parsed.additionals.len(), 1);
+        match &parsed.additionals[0] {
+            DnsRecord::OPT {
+                udp_payload_size,
+                extended_rcode,
+                version,
+                dnssec_ok,
+                options,
+            } => {
+                assert_eq!(*udp_payload_size, 4096);
+                assert_eq!(*extended_rcode, 0);
+                assert_eq!(*version, 0);
+                assert!(*dnssec_ok);
+                assert_eq!(options, &vec![0x00, 0x04, 0x00, 0x02, 0xab, 0xcd]);
+            }
+            other => panic!("Expected an OPT record, got {:?}", other),
+        }
+        assert_eq!(parsed.additionals[0].name(), &DnsName::root());
+    }
+
+    #[test]
+    fn test_captured_response_with_opt_parses_its_answers() {
+        let parsed = DnsPacket::parse(CAPTURED_RESPONSE).unwrap();
+
+        assert_eq!(parsed.answers.len(), 3);
+        match &parsed.answers[1] {
+            DnsRecord::SRV { name, port, target, .. } => {
+                assert_eq!(name.to_string(), "Web._http._tcp.local");
+                assert_eq!(*port, 8080);
+                assert_eq!(target.to_string(), "host.local");
+            }
+            other => panic!("Expected an SRV record, got {:?}", other),
+        }
+        assert!(matches!(
+            parsed.additionals.as_slice(),
+            [DnsRecord::OPT { udp_payload_size: 1440, .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_response_with_opt_is_processed() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        let packet = DnsPacket::parse(CAPTURED_RESPONSE).unwrap();
+        let peer: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+
+        service.process_response(&packet, &peer).await;
+
+        let discovered = service.registry.list_discovered_services().await;
+        assert_eq!(discovered.len(), 1);
+        assert_eq!(discovered[0].id, "Web._http._tcp.local");
+        let node = service.registry.get_node("host.local").await.unwrap();
+        assert_eq!(node.ip_address, "192.168.1.30");
+        assert!(service.registry.get_node("").await.is_none(), "OPT must not be cached");
+    }
+}
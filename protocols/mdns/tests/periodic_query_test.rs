@@ -0,0 +1,83 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{MdnsConfig, MdnsError, MdnsService, TimingPolicy};
+    use std::sync::Arc;
+    use tokio::sync::watch;
+    use tokio::time::{sleep, Duration};
+    use tokio_util::sync::CancellationToken;
+
+    fn service(transport: Arc<MockTransport>, cap: Duration) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                requery_interval_max: cap,
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        MdnsService::with_transport(transport, config)
+    }
+
+    #[tokio::test]
+    async fn test_query_interval_doubles_up_to_the_cap() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone(), Duration::from_millis(200));
+        let (_interval_tx, interval_rx) = watch::channel(Duration::from_millis(50));
+        let shutdown = CancellationToken::new();
+
+        let task = {
+            let service = service.clone();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move { service.periodic_query("_http._tcp.local", interval_rx, shutdown).await })
+        };
+        // Queries at 0, 50, 150, 350, and 550ms.
+        sleep(Duration::from_millis(650)).await;
+        shutdown.cancel();
+        task.await.unwrap().unwrap();
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 5, "Backoff should space out queries");
+        assert!(sent.iter().all(|query| query.questions[0].qname.to_string() == "_http._tcp.local"));
+    }
+
+    #[tokio::test]
+    async fn test_new_interval_queries_at_once_and_restarts_backoff() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone(), Duration::from_millis(40));
+        let (interval_tx, interval_rx) = watch::channel(Duration::from_secs(10));
+        let shutdown = CancellationToken::new();
+
+        let task = {
+            let service = service.clone();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move { service.periodic_query("_http._tcp.local", interval_rx, shutdown).await })
+        };
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(transport.sent_packets().len(), 1, "Only the initial query before the change");
+
+        interval_tx.send(Duration::from_millis(40)).unwrap();
+        sleep(Duration::from_millis(250)).await;
+        shutdown.cancel();
+        task.await.unwrap().unwrap();
+
+        let sent = transport.sent_packets().len();
+        assert!(sent >= 5, "The faster interval should take effect at once, sent {}", sent);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_service_type_is_reported() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone(), Duration::from_secs(60));
+        let (_interval_tx, interval_rx) = watch::channel(Duration::from_secs(1));
+
+        let label = "a".repeat(64);
+        let result = service
+            .periodic_query(&format!("{}.local", label), interval_rx, CancellationToken::new())
+            .await;
+
+        assert!(matches!(result, Err(MdnsError::InvalidName(_))), "{:?}", result);
+        assert!(transport.sent_packets().is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService, TimingPolicy};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn service(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            timing: TimingPolicy {
+                probe_interval: Duration::from_millis(30),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        MdnsService::with_transport(transport, config)
+    }
+
+    async fn register_probed(service: Arc<MdnsService>) -> String {
+        service
+            .register_local_service_probed(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap()
+    }
+
+    fn probes(transport: &MockTransport) -> Vec<DnsPacket> {
+        transport
+            .sent_packets()
+            .into_iter()
+            .filter(|packet| packet.flags & 0x8000 == 0)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_unclaimed_name_is_registered_after_three_probes() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone());
+
+        let name = register_probed(Arc::clone(&service)).await;
+
+        assert_eq!(name, "Web._http._tcp.local");
+        assert!(service.registry.get_service(&name).await.is_some());
+        let probes = probes(&transport);
+        assert_eq!(probes.len(), 3);
+        let question = &probes[0].questions[0];
+        assert_eq!(question.qname.to_string(), "Web._http._tcp.local");
+        assert_eq!(question.qtype, 255);
+        assert!(question.unicast_response);
+    }
+
+    #[tokio::test]
+    async fn test_claimed_name_is_renamed() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone());
+
+        let registering = tokio::spawn(register_probed(Arc::clone(&service)));
+        sleep(Duration::from_millis(10)).await;
+        let mut claim = DnsPacket::new();
+        claim.answers.push(DnsRecord::SRV {
+            name: DnsName::new("Web._http._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 9000,
+            target: DnsName::new("peer.local").unwrap(),
+            cache_flush: true,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_response(&claim, &peer).await;
+
+        let name = registering.await.unwrap();
+        assert_eq!(name, "Web (2)._http._tcp.local");
+        assert!(service.registry.get_service("Web._http._tcp.local").await.is_none());
+        assert!(service.registry.get_service(&name).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_probe_for_registered_name_is_answered() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone());
+        register_probed(Arc::clone(&service)).await;
+        let sent_before = transport.sent_packets().len();
+
+        let mut probe = DnsPacket::new();
+        probe.flags = 0x0000;
+        probe.questions.push(DnsQuestion {
+            qname: DnsName::new("web._http._tcp.local").unwrap(),
+            qtype: 255,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&probe, &peer).await;
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), sent_before + 1);
+        assert!(sent[sent_before].answers.iter().any(|r| matches!(r, DnsRecord::SRV { port: 8080, .. })));
+    }
+}
@@ -0,0 +1,101 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockClock, MockTransport};
+    use mdns::{MdnsConfig, MdnsService};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+    use tokio::time::Duration;
+
+    async fn register(service: &MdnsService, id: &str, ttl: u32) {
+        service
+            .register_local_service(
+                id.to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(ttl),
+                "host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_services_are_reannounced_at_their_own_ttl_thresholds() {
+        let transport = Arc::new(MockTransport::new());
+        let clock = Arc::new(MockClock::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport_and_clock(transport.clone(), config, clock.clone());
+        register(&service, "Short._http._tcp.local", 100).await;
+        register(&service, "Long._http._tcp.local", 1000).await;
+        assert_eq!(service.reannounce_expiring().await.unwrap(), 0, "Cycles start now");
+
+        // 80, 85, and 90 seconds are the thresholds of the 100-second service only.
+        let mut now = 0;
+        for (elapsed, expected) in [(79, 0), (80, 1), (84, 0), (85, 1), (90, 1), (91, 0)] {
+            clock.advance(Duration::from_secs(elapsed - now));
+            now = elapsed;
+            assert_eq!(
+                service.reannounce_expiring().await.unwrap(),
+                expected,
+                "At {} seconds",
+                elapsed
+            );
+        }
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 3);
+        assert!(sent.iter().all(|packet| packet
+            .answers
+            .iter()
+            .all(|record| record.name().to_string() != "Long._http._tcp.local")));
+
+        // The cycle restarted at 90 seconds, so the next threshold is 90 + 80.
+        clock.advance(Duration::from_secs(78));
+        assert_eq!(service.reannounce_expiring().await.unwrap(), 0);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(service.reannounce_expiring().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_skipped_thresholds_send_a_single_announcement() {
+        let transport = Arc::new(MockTransport::new());
+        let clock = Arc::new(MockClock::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport_and_clock(transport.clone(), config, clock.clone());
+        register(&service, "Short._http._tcp.local", 10).await;
+        service.reannounce_expiring().await.unwrap();
+
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(service.reannounce_expiring().await.unwrap(), 1);
+        assert_eq!(transport.sent_packets().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_service_is_still_advertised_after_a_full_ttl() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        register(&service, "Short._http._tcp.local", 1).await;
+        service.reannounce_expiring().await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+
+        assert_eq!(service.registry.list_services().await.len(), 1);
+        assert_eq!(service.reannounce_expiring().await.unwrap(), 1);
+        assert!(transport.sent_packets()[0]
+            .answers
+            .iter()
+            .any(|record| record.name().to_string() == "Short._http._tcp.local"));
+    }
+}
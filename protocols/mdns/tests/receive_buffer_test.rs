@@ -0,0 +1,72 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    /// A response with a TXT record of about 5000 bytes, followed by an A record.
+    fn large_response() -> DnsPacket {
+        let mut txt_data = Vec::new();
+        for i in 0..20 {
+            let entry = format!("key{:02}={}", i, "v".repeat(240));
+            txt_data.push(entry.len() as u8);
+            txt_data.extend_from_slice(entry.as_bytes());
+        }
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::TXT {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            txt_data,
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("peer.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 30],
+            cache_flush: false,
+        });
+        packet
+    }
+
+    async fn receive(config: MdnsConfig, data: Vec<u8>) -> Arc<MdnsService> {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let service = MdnsService::with_transport(transport.clone(), config);
+        let listener = {
+            let service = Arc::clone(&service);
+            tokio::spawn(async move { service.listen().await })
+        };
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+        transport.push_inbound(data, src);
+        sleep(Duration::from_millis(100)).await;
+        listener.abort();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_packets_larger_than_4096_bytes_are_received_whole() {
+        let data = large_response().serialize();
+        assert!(data.len() > 4096 && data.len() <= 9000);
+
+        let service = receive(MdnsConfig::default(), data).await;
+        assert!(service.registry.get_node("peer.local").await.is_some());
+        assert_eq!(service.metrics().parse_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_packets_over_the_buffer_size_are_truncated_and_dropped() {
+        let data = large_response().serialize();
+        let config = MdnsConfig {
+            recv_buffer_size: 4096,
+            ..MdnsConfig::default()
+        };
+
+        let service = receive(config, data).await;
+        assert!(service.registry.get_node("peer.local").await.is_none());
+        assert_eq!(service.metrics().parse_failures, 1);
+    }
+}
@@ -0,0 +1,86 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::collections::HashMap;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    fn ttl_config() -> MdnsConfig {
+        MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            default_ttl: 4500,
+            address_ttl: Some(120),
+            ..MdnsConfig::default()
+        }
+    }
+
+    async fn register(service: &MdnsService, ttl: Option<u32>) {
+        let txt = HashMap::from([("path".to_string(), "/".to_string())]);
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                ttl,
+                "MyHost.local".to_string(),
+                Some(txt),
+            )
+            .await
+            .unwrap();
+    }
+
+    fn ttl_of(packet: &DnsPacket, kind: &str) -> u32 {
+        packet
+            .answers
+            .iter()
+            .find_map(|record| match (kind, record) {
+                ("PTR", DnsRecord::PTR { ttl, .. })
+                | ("SRV", DnsRecord::SRV { ttl, .. })
+                | ("TXT", DnsRecord::TXT { ttl, .. })
+                | ("A", DnsRecord::A { ttl, .. }) => Some(*ttl),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("No {} record advertised", kind))
+    }
+
+    #[tokio::test]
+    async fn test_config_ttls_apply_per_record_type() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), ttl_config());
+        register(&service, None).await;
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        assert_eq!(ttl_of(&packet, "PTR"), 4500);
+        assert_eq!(ttl_of(&packet, "SRV"), 4500);
+        assert_eq!(ttl_of(&packet, "TXT"), 4500);
+        assert_eq!(ttl_of(&packet, "A"), 120, "The address override should win");
+    }
+
+    #[tokio::test]
+    async fn test_service_ttl_overrides_config() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), ttl_config());
+        register(&service, Some(60)).await;
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        for kind in ["PTR", "SRV", "TXT", "A"] {
+            assert_eq!(ttl_of(&packet, kind), 60, "{} should carry the service's TTL", kind);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_config_keeps_120_second_ttl() {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        register(&service, None).await;
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        for kind in ["PTR", "SRV", "TXT", "A"] {
+            assert_eq!(ttl_of(&packet, kind), 120);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+mod common;
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsRecord, MdnsConfig, MdnsService};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    const SERVICE_FILE: &str = r#"[
+        {
+            "id": "Printer._ipp._tcp.local",
+            "type": "_ipp._tcp.local",
+            "port": 631,
+            "ttl": 120,
+            "origin": "office-host.local",
+            "txt": { "path": "/print" },
+            "subtypes": ["_color"]
+        },
+        {
+            "id": "Files._http._tcp.local",
+            "type": "_http._tcp.local",
+            "port": 8080,
+            "origin": "office-host.local"
+        },
+        {
+            "id": "Broken._http._tcp.local",
+            "type": "_http._tcp.local",
+            "port": 0,
+            "origin": "office-host.local"
+        }
+    ]"#;
+
+    #[tokio::test]
+    async fn test_register_from_file_registers_and_announces() {
+        let path = std::env::temp_dir().join(format!("mdns-services-{}.json", std::process::id()));
+        std::fs::write(&path, SERVICE_FILE).unwrap();
+
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        let results = service.register_from_file(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let failed: Vec<&str> = results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(id, _)| id.as_str())
+            .collect();
+        assert_eq!(failed, vec!["Broken._http._tcp.local"]);
+
+        let printer = service.registry.get_service("Printer._ipp._tcp.local").await.unwrap();
+        assert_eq!(printer.port, 631);
+        assert_eq!(printer.txt, vec![("path".to_string(), "/print".to_string())]);
+        assert_eq!(printer.subtypes, vec!["_color".to_string()]);
+        assert!(service.registry.get_service("Files._http._tcp.local").await.is_some());
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1, "Loaded services should be announced once");
+        let announced: Vec<String> = sent[0]
+            .answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::SRV { name, .. } => Some(name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(announced.len(), 2);
+        assert!(announced.contains(&"Printer._ipp._tcp.local".to_string()));
+        assert!(announced.contains(&"Files._http._tcp.local".to_string()));
+    }
+}
@@ -0,0 +1,113 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{CancellationToken, DnsRecord, MdnsConfig, MdnsService, ServiceRecord, TimingPolicy};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn record(id: &str, port: u16) -> ServiceRecord {
+        ServiceRecord::builder(id, "_http._tcp.local", port)
+            .origin("web-host.local")
+            .ttl(120)
+            .build()
+    }
+
+    fn config() -> MdnsConfig {
+        MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            registry_print_interval: None,
+            timing: TimingPolicy {
+                announce_interval: Duration::from_millis(50),
+                ..TimingPolicy::default()
+            },
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_announced_in_one_burst() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let service = MdnsService::with_transport(transport.clone(), config());
+
+        let shutdown = CancellationToken::new();
+        Arc::clone(&service)
+            .run("_http._tcp.local".to_string(), 60, 60, shutdown.clone())
+            .await;
+        service
+            .register_local_services(vec![
+                record("One._http._tcp.local", 8001),
+                record("Two._http._tcp.local", 8002),
+                record("Three._http._tcp.local", 8003),
+            ])
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(300)).await;
+        shutdown.cancel();
+
+        let announcements: Vec<_> = transport
+            .sent_packets()
+            .into_iter()
+            .filter(|packet| packet.flags & 0x8000 != 0)
+            .filter(|packet| packet.answers.iter().any(|r| r.ttl() > 0))
+            .collect();
+        assert_eq!(announcements.len(), 3, "One burst of three announcements");
+        for packet in &announcements {
+            let srv_count = packet
+                .answers
+                .iter()
+                .filter(|record| matches!(record, DnsRecord::SRV { .. }))
+                .count();
+            assert_eq!(srv_count, 3, "Every announcement carries all three services");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_record_rejects_the_whole_batch() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config());
+        let mut invalid = record("Two._http._tcp.local", 8002);
+        invalid.origin = format!("{}.local", "h".repeat(64));
+
+        let result = service
+            .register_local_services(vec![record("One._http._tcp.local", 8001), invalid])
+            .await;
+        assert!(result.is_err());
+        assert!(service.registry.list_services().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_over_the_limit_registers_nothing() {
+        let config = MdnsConfig {
+            max_local_services: Some(2),
+            ..config()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        service.register_service(record("One._http._tcp.local", 8001)).await.unwrap();
+
+        let result = service
+            .register_local_services(vec![
+                // Replacing a registered service does not count against the limit.
+                record("One._http._tcp.local", 9001),
+                record("Two._http._tcp.local", 8002),
+                record("Three._http._tcp.local", 8003),
+            ])
+            .await;
+        assert!(result.is_err());
+        let services = service.registry.list_services().await;
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].port, 8001);
+
+        service
+            .register_local_services(vec![
+                record("One._http._tcp.local", 9001),
+                record("Two._http._tcp.local", 8002),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(service.registry.list_services().await.len(), 2);
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn browse_query() -> Vec<u8> {
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        query.serialize()
+    }
+
+    #[tokio::test]
+    async fn test_locked_registry_drops_packet_after_timeout() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            registry_lock_timeout: Duration::from_millis(50),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        let listener = {
+            let service = Arc::clone(&service);
+            tokio::spawn(async move { service.listen().await })
+        };
+
+        // While the registry is held, the query is given up on once the timeout elapses.
+        let guard = service.registry.lock_for_update().await;
+        transport.push_inbound(browse_query(), src);
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(service.contended_packets(), 1, "processing should not block on the lock");
+        assert!(transport.sent().is_empty());
+        drop(guard);
+
+        // Once released, packets are processed again.
+        transport.push_inbound(browse_query(), src);
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(transport.sent().len(), 1);
+        assert_eq!(service.contended_packets(), 1);
+
+        listener.abort();
+    }
+}
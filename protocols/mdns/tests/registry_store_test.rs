@@ -0,0 +1,137 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use async_trait::async_trait;
+    use mdns::{
+        CacheFlush, MdnsConfig, MdnsRegistry, MdnsService, Protocol, Record, Registry, RegistryError,
+        RegistryStore, ServiceRecord, TokioClock,
+    };
+    use std::any::Any;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    type Rows<R> = Arc<Mutex<HashMap<String, R>>>;
+
+    /// A store whose tables outlive the registries using them, standing in for a
+    /// durable backend.
+    #[derive(Clone, Default)]
+    struct SharedStore {
+        tables: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+    }
+
+    struct SharedTable<R> {
+        rows: Rows<R>,
+    }
+
+    impl RegistryStore for SharedStore {
+        type Table<R: Record + 'static> = SharedTable<R>;
+
+        fn open<R: Record + 'static>(&self, name: &str, _capacity: usize) -> SharedTable<R> {
+            let mut tables = self.tables.lock().unwrap();
+            let table = tables
+                .entry(name.to_string())
+                .or_insert_with(|| Arc::new(Rows::<R>::default()));
+            let rows = table.downcast_ref::<Rows<R>>().expect("table opened with another type");
+            SharedTable { rows: rows.clone() }
+        }
+    }
+
+    #[async_trait]
+    impl<R: Record + 'static> Registry<R> for SharedTable<R> {
+        async fn add(&self, record: R) -> Result<(), RegistryError> {
+            self.rows.lock().unwrap().insert(record.identifier(), record);
+            Ok(())
+        }
+
+        async fn get(&self, identifier: &str) -> Option<R> {
+            self.rows.lock().unwrap().get(identifier).cloned()
+        }
+
+        async fn list(&self) -> Vec<R> {
+            self.rows.lock().unwrap().values().cloned().collect()
+        }
+
+        async fn remove(&self, identifier: &str) -> Result<(), RegistryError> {
+            self.rows
+                .lock()
+                .unwrap()
+                .remove(identifier)
+                .map(|_| ())
+                .ok_or_else(|| RegistryError::RecordNotFound(identifier.to_string()))
+        }
+
+        async fn set_capacity(&self, _capacity: usize) {}
+
+        async fn get_capacity(&self) -> usize {
+            usize::MAX
+        }
+    }
+
+    fn service_on(store: SharedStore) -> Arc<MdnsService<SharedStore>> {
+        let registry = MdnsRegistry::with_store(store, Arc::new(TokioClock));
+        MdnsService::with_transport_and_registry(
+            Arc::new(MockTransport::new()),
+            MdnsConfig::default(),
+            registry,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_registry_opens_a_table_per_record_kind() {
+        let store = SharedStore::default();
+        let _service = service_on(store.clone());
+
+        let mut names: Vec<String> = store.tables.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["discovered_services", "nodes", "nodes_v6", "services", "txt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_records_survive_a_restart_with_the_same_store() {
+        let store = SharedStore::default();
+        let first = service_on(store.clone());
+        first
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        first
+            .registry
+            .add_discovered_service(ServiceRecord {
+                id: "Printer._ipp._tcp.local".to_string(),
+                service_type: "_ipp._tcp.local".to_string(),
+                port: 631,
+                ttl: Some(120),
+                origin: "printer.local".to_string(),
+                priority: Some(0),
+                weight: Some(0),
+                cache_flush: CacheFlush::default(),
+                txt: Vec::new(),
+                subtypes: Vec::new(),
+                protocol: Some(Protocol::Tcp),
+                bind_addr: None,
+            })
+            .await
+            .unwrap();
+        drop(first);
+
+        let second = service_on(store);
+
+        let local = second.registry.get_service("Web._http._tcp.local").await.unwrap();
+        assert_eq!(local.port, 8080);
+        let discovered = second.registry.list_discovered_services().await;
+        assert_eq!(discovered.len(), 1);
+        assert_eq!(discovered[0].id, "Printer._ipp._tcp.local");
+    }
+}
@@ -0,0 +1,30 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{MdnsConfig, MdnsService};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_rejoin_joins_the_group_on_each_configured_interface() {
+        let transport = Arc::new(MockTransport::new());
+        let interfaces = vec![Ipv4Addr::new(192, 0, 2, 1), Ipv4Addr::new(192, 0, 2, 2)];
+        let config = MdnsConfig {
+            interfaces: interfaces.clone(),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        assert!(service.joined_interfaces().is_empty());
+
+        service.rejoin_multicast().await.unwrap();
+
+        let group = Ipv4Addr::new(224, 0, 0, 251);
+        assert_eq!(
+            transport.rejoins(),
+            vec![(group, interfaces[0]), (group, interfaces[1])]
+        );
+        assert_eq!(service.joined_interfaces(), interfaces);
+    }
+}
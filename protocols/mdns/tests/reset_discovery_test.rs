@@ -0,0 +1,96 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsRegistry, MdnsService, NodeRecord};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    fn peer_response() -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::PTR {
+            name: DnsName::new("_ipp._tcp.local").unwrap(),
+            ttl: 120,
+            ptr_name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new("Printer._ipp._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 631,
+            target: DnsName::new("printer-host.local").unwrap(),
+            cache_flush: true,
+        });
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("printer-host.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 40],
+            cache_flush: true,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_reset_discovery_keeps_local_services() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let peer: SocketAddr = "192.168.1.40:5353".parse().unwrap();
+        service.process_response(&peer_response(), &peer).await;
+        assert_eq!(service.registry.list_discovered_services().await.len(), 1);
+        assert_eq!(service.registry.list_nodes().await.len(), 1);
+
+        service.reset_discovery().await;
+
+        assert!(service.registry.list_discovered_services().await.is_empty());
+        assert!(service.registry.list_nodes().await.is_empty());
+        assert_eq!(service.registry.node_count(), 0);
+        let local = service.registry.list_services().await;
+        assert_eq!(local.len(), 1, "Local services should survive a reset");
+        assert_eq!(local[0].id, "Web._http._tcp.local");
+
+        // Peers are learned afresh afterwards.
+        service.process_response(&peer_response(), &peer).await;
+        assert_eq!(service.registry.list_discovered_services().await.len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_clear_nodes_alongside_sweep_leaves_nothing_behind() {
+        let registry = Arc::new(MdnsRegistry::new());
+        for round in 0..20 {
+            for index in 0..10 {
+                registry
+                    .add_node(NodeRecord {
+                        id: format!("host{}-{}.local", round, index),
+                        ip_address: format!("192.168.2.{}", index),
+                        ttl: Some(0),
+                        source: None,
+                        last_seen: None,
+                        scope_id: None,
+                    })
+                    .await
+                    .unwrap();
+            }
+
+            let sweeper = Arc::clone(&registry);
+            let sweep = tokio::spawn(async move { sweeper.sweep_expired().await });
+            registry.clear_nodes().await;
+            sweep.await.unwrap();
+
+            assert!(registry.list_nodes().await.is_empty(), "Round {}", round);
+            assert_eq!(registry.node_count(), 0, "Round {}", round);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockClock, MockTransport};
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::Duration;
+
+    const INSTANCE: &str = "Printer._ipp._tcp.local";
+
+    fn service_with_clock() -> (Arc<MockClock>, Arc<MdnsService>) {
+        let clock = Arc::new(MockClock::new());
+        let service = MdnsService::with_transport_and_clock(
+            Arc::new(MockTransport::new()),
+            MdnsConfig::default(),
+            clock.clone(),
+        );
+        (clock, service)
+    }
+
+    fn browse_answer() -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::PTR {
+            name: DnsName::new("_ipp._tcp.local").unwrap(),
+            ttl: 4500,
+            ptr_name: DnsName::new(INSTANCE).unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new(INSTANCE).unwrap(),
+            ttl: 120,
+            priority: 1,
+            weight: 5,
+            port: 631,
+            target: DnsName::new("printer.local").unwrap(),
+            cache_flush: true,
+        });
+        packet.answers.push(DnsRecord::TXT {
+            name: DnsName::new(INSTANCE).unwrap(),
+            ttl: 120,
+            txt_data: b"\x0crp=ipp/print".to_vec(),
+            cache_flush: true,
+        });
+        packet
+    }
+
+    fn address(ttl: u32) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("printer.local").unwrap(),
+            ttl,
+            ip: [192, 168, 1, 40],
+            cache_flush: true,
+        });
+        packet
+    }
+
+    fn src() -> SocketAddr {
+        "192.168.1.40:5353".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_instance_without_an_address_is_left_out() {
+        let (_clock, service) = service_with_clock();
+
+        service.process_response(&browse_answer(), &src()).await;
+
+        assert_eq!(service.registry.list_discovered_services().await.len(), 1);
+        assert!(service.registry.list_resolvable_services().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fully_resolved_instance_is_assembled() {
+        let (_clock, service) = service_with_clock();
+
+        service.process_response(&browse_answer(), &src()).await;
+        service.process_response(&address(120), &src()).await;
+
+        let resolvable = service.registry.list_resolvable_services().await;
+        assert_eq!(resolvable.len(), 1);
+        let instance = &resolvable[0];
+        assert_eq!(instance.instance, INSTANCE);
+        assert_eq!(instance.host, "printer.local");
+        assert_eq!(instance.port, 631);
+        assert_eq!(instance.priority, 1);
+        assert_eq!(instance.weight, 5);
+        assert_eq!(instance.addresses, vec!["192.168.1.40".to_string()]);
+        assert_eq!(
+            instance.txt,
+            HashMap::from([("rp".to_string(), "ipp/print".to_string())])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_instance_drops_out_once_its_address_expires() {
+        let (clock, service) = service_with_clock();
+
+        service.process_response(&browse_answer(), &src()).await;
+        service.process_response(&address(10), &src()).await;
+        assert_eq!(service.registry.list_resolvable_services().await.len(), 1);
+
+        clock.advance(Duration::from_secs(10));
+        assert!(service.registry.list_resolvable_services().await.is_empty());
+        // The SRV data itself is still cached, only the address has lapsed.
+        assert_eq!(service.registry.list_discovered_services().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_srv_target_matches_address_owner_case_insensitively() {
+        let (_clock, service) = service_with_clock();
+        let mut address = address(120);
+        if let DnsRecord::A { name, .. } = &mut address.answers[0] {
+            *name = DnsName::new("Printer.LOCAL").unwrap();
+        }
+
+        service.process_response(&browse_answer(), &src()).await;
+        service.process_response(&address, &src()).await;
+
+        let resolvable = service.registry.list_resolvable_services().await;
+        assert_eq!(resolvable.len(), 1);
+        assert_eq!(resolvable[0].addresses, vec!["192.168.1.40".to_string()]);
+        let instances = service.registry.instances().await;
+        assert_eq!(instances[INSTANCE].addresses, vec!["192.168.1.40".to_string()]);
+        assert!(service.registry.flag_unresolved().await.is_empty());
+        let states = service.registry.list_services_with_state().await;
+        assert!(states.iter().all(|(_, resolves)| *resolves));
+    }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsError, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    const INSTANCE: &str = "Printer._ipp._tcp.local";
+
+    fn srv_and_txt() -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new(INSTANCE).unwrap(),
+            ttl: 120,
+            priority: 1,
+            weight: 5,
+            port: 631,
+            target: DnsName::new("printer.local").unwrap(),
+            cache_flush: true,
+        });
+        packet.answers.push(DnsRecord::TXT {
+            name: DnsName::new(INSTANCE).unwrap(),
+            ttl: 120,
+            txt_data: b"\x0crp=ipp/print".to_vec(),
+            cache_flush: true,
+        });
+        packet
+    }
+
+    fn address() -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new("printer.local").unwrap(),
+            ttl: 120,
+            ip: [192, 168, 1, 40],
+            cache_flush: true,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_resolve_instance_follows_the_srv_target() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        let responder = {
+            let service = service.clone();
+            let transport = transport.clone();
+            tokio::spawn(async move {
+                let src: SocketAddr = "192.168.1.40:5353".parse().unwrap();
+                sleep(Duration::from_millis(50)).await;
+                service.process_response(&srv_and_txt(), &src).await;
+                sleep(Duration::from_millis(50)).await;
+                // The address only arrives after the resolver asked for it.
+                let asked = transport.sent_packets().iter().any(|packet| {
+                    packet.questions.iter().any(|q| q.qtype == 1 && q.qname.to_string() == "printer.local")
+                });
+                assert!(asked, "the SRV target should have been queried");
+                service.process_response(&address(), &src).await;
+            })
+        };
+
+        let resolved = service
+            .resolve_instance(INSTANCE, Duration::from_secs(5))
+            .await
+            .unwrap();
+        responder.await.unwrap();
+
+        assert_eq!(resolved.instance, INSTANCE);
+        assert_eq!(resolved.host, "printer.local");
+        assert_eq!((resolved.port, resolved.priority, resolved.weight), (631, 1, 5));
+        assert_eq!(resolved.addresses, vec!["192.168.1.40".to_string()]);
+        assert_eq!(resolved.txt.get("rp").map(String::as_str), Some("ipp/print"));
+
+        let query = &transport.sent_packets()[0];
+        let qtypes: Vec<_> = query.questions.iter().map(|q| q.qtype).collect();
+        assert_eq!(qtypes, vec![33, 16]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_instance_times_out_without_an_address() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+        let src: SocketAddr = "192.168.1.40:5353".parse().unwrap();
+        service.process_response(&srv_and_txt(), &src).await;
+
+        let result = service.resolve_instance(INSTANCE, Duration::from_millis(100)).await;
+        assert!(matches!(result, Err(MdnsError::Timeout(message)) if message.contains("printer.local")));
+    }
+}
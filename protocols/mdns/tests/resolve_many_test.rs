@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    #[tokio::test]
+    async fn test_resolve_many_respects_concurrency_cap() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            max_concurrent_resolves: 2,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        // Simulated peer: answers every SRV query 50ms after it appears on the wire.
+        let responder = {
+            let transport = transport.clone();
+            let service = service.clone();
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            tokio::spawn(async move {
+                let mut handled = 0;
+                loop {
+                    let sent = transport.sent_packets();
+                    for query in sent.into_iter().skip(handled) {
+                        handled += 1;
+                        let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_in_flight.fetch_max(current, Ordering::SeqCst);
+
+                        let service = service.clone();
+                        let in_flight = in_flight.clone();
+                        tokio::spawn(async move {
+                            sleep(Duration::from_millis(50)).await;
+                            let instance = query.questions[0].qname.clone();
+                            let mut response = DnsPacket::new();
+                            response.answers.push(DnsRecord::SRV {
+                                name: instance,
+                                ttl: 120,
+                                priority: 0,
+                                weight: 0,
+                                port: 8080,
+                                target: DnsName::new("host.local").unwrap(),
+                                cache_flush: false,
+                            });
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                            let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+                            service.process_response(&response, &src).await;
+                        });
+                    }
+                    sleep(Duration::from_millis(5)).await;
+                }
+            })
+        };
+
+        let instances: Vec<String> = (0..5)
+            .map(|i| format!("Instance{}._http._tcp.local", i))
+            .collect();
+        let results = service.resolve_many(&instances, Duration::from_secs(2)).await;
+        responder.abort();
+
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(transport.sent().len(), 5, "One query per instance");
+        assert!(
+            max_in_flight.load(Ordering::SeqCst) <= 2,
+            "At most two resolves may be in flight, saw {}",
+            max_in_flight.load(Ordering::SeqCst)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService, TimingPolicy};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration, Instant};
+
+    fn answer(instance: &str) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::PTR {
+            name: DnsName::new("_http._tcp.local").unwrap(),
+            ttl: 120,
+            ptr_name: DnsName::new(instance).unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new(instance).unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 8080,
+            target: DnsName::new("peer.local").unwrap(),
+            cache_flush: false,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_resolve_collects_deduplicated_answers_and_stops_when_quiet() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                resolve_quiet_period: Duration::from_millis(200),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+
+        // Simulated peers: two instances answer, one of them twice.
+        let responder = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+                sleep(Duration::from_millis(50)).await;
+                service.process_response(&answer("One._http._tcp.local"), &src).await;
+                sleep(Duration::from_millis(50)).await;
+                service.process_response(&answer("Two._http._tcp.local"), &src).await;
+                service.process_response(&answer("One._http._tcp.local"), &src).await;
+            })
+        };
+
+        let started = Instant::now();
+        let services = service
+            .resolve("_http._tcp.local", Duration::from_secs(5))
+            .await
+            .unwrap();
+        responder.await.unwrap();
+
+        let ids: Vec<_> = services.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["One._http._tcp.local", "Two._http._tcp.local"]);
+        assert!(started.elapsed() < Duration::from_secs(1), "Resolve should stop once quiet");
+
+        let query = &transport.sent_packets()[0];
+        assert_eq!(query.questions[0].qtype, 12);
+        assert_eq!(query.questions[0].qname.to_string(), "_http._tcp.local");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_without_answers_returns_empty_after_timeout() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+
+        let services = service
+            .resolve("_http._tcp.local", Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert!(services.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService, TimingPolicy};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn answer(service_type: &str, instance: &str) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::PTR {
+            name: DnsName::new(service_type).unwrap(),
+            ttl: 120,
+            ptr_name: DnsName::new(instance).unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new(instance).unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 8080,
+            target: DnsName::new("peer.local").unwrap(),
+            cache_flush: false,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_resolve_types_sends_one_packet_and_splits_answers_by_type() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                resolve_quiet_period: Duration::from_millis(100),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+
+        let responder = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+                sleep(Duration::from_millis(30)).await;
+                service.process_response(&answer("_http._tcp.local", "Web._http._tcp.local"), &src).await;
+                service.process_response(&answer("_ipp._tcp.local", "Printer._ipp._tcp.local"), &src).await;
+                service.process_response(&answer("_ssh._tcp.local", "Shell._ssh._tcp.local"), &src).await;
+            })
+        };
+
+        let resolved = service
+            .resolve_types(&["_http._tcp.local", "_ipp._tcp.local", "_smb._tcp.local"], Duration::from_secs(2))
+            .await
+            .unwrap();
+        responder.await.unwrap();
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1, "All types should share one query packet");
+        let qnames: Vec<String> = sent[0].questions.iter().map(|q| q.qname.to_string()).collect();
+        assert_eq!(qnames, vec!["_http._tcp.local", "_ipp._tcp.local", "_smb._tcp.local"]);
+
+        assert_eq!(resolved.len(), 3);
+        let ids = |service_type: &str| -> Vec<String> {
+            resolved[service_type].iter().map(|s| s.id.clone()).collect()
+        };
+        assert_eq!(ids("_http._tcp.local"), vec!["Web._http._tcp.local"]);
+        assert_eq!(ids("_ipp._tcp.local"), vec!["Printer._ipp._tcp.local"]);
+        assert!(ids("_smb._tcp.local").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_service_types_match_case_insensitively() {
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                resolve_quiet_period: Duration::from_millis(100),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+
+        let responder = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+                sleep(Duration::from_millis(30)).await;
+                service.process_response(&answer("_http._tcp.local", "Web._http._tcp.local"), &src).await;
+                service.process_response(&answer("_IPP._TCP.local", "Printer._IPP._TCP.local"), &src).await;
+            })
+        };
+
+        let resolved = service
+            .resolve_types(&["_HTTP._tcp.local", "_ipp._tcp.local"], Duration::from_secs(2))
+            .await
+            .unwrap();
+        responder.await.unwrap();
+
+        assert_eq!(resolved["_HTTP._tcp.local"].len(), 1);
+        assert_eq!(resolved["_ipp._tcp.local"].len(), 1);
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    async fn service(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        service
+    }
+
+    fn reverse_query(name: &str) -> DnsPacket {
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new(name).unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        query
+    }
+
+    #[tokio::test]
+    async fn test_reverse_query_for_our_address_names_our_host() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone()).await;
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        service.process_query(&reverse_query("10.1.168.192.in-addr.arpa"), &peer).await;
+
+        let response = &transport.sent_packets()[0];
+        assert_eq!(response.answers.len(), 1);
+        match &response.answers[0] {
+            DnsRecord::PTR { name, ptr_name, cache_flush, .. } => {
+                assert_eq!(name.to_string(), "10.1.168.192.in-addr.arpa");
+                assert_eq!(ptr_name.to_string(), "MyHost.local");
+                assert!(cache_flush, "A reverse PTR is a unique record");
+            }
+            other => panic!("unexpected record {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reverse_query_for_other_addresses_is_ignored() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone()).await;
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        for name in ["20.1.168.192.in-addr.arpa", "1.168.192.in-addr.arpa", "x.1.168.192.in-addr.arpa"] {
+            service.process_query(&reverse_query(name), &peer).await;
+        }
+        assert!(transport.sent().is_empty());
+    }
+}
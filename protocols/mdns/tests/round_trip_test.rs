@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord};
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    const LABEL_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-_ ";
+
+    /// Returns a random name, often ending in a suffix shared with other names so that
+    /// name compression is exercised.
+    fn random_name(rng: &mut StdRng) -> DnsName {
+        let mut labels: Vec<String> = (0..rng.gen_range(1..4))
+            .map(|_| {
+                (0..rng.gen_range(1..20))
+                    .map(|_| *LABEL_CHARS.choose(rng).unwrap() as char)
+                    .collect()
+            })
+            .collect();
+        let suffixes: [&[&str]; 3] = [&["local"], &["_http", "_tcp", "local"], &[]];
+        labels.extend(suffixes.choose(rng).unwrap().iter().map(|label| label.to_string()));
+        DnsName::new(&labels.join(".")).unwrap()
+    }
+
+    fn random_txt_data(rng: &mut StdRng) -> Vec<u8> {
+        let mut data = Vec::new();
+        for _ in 0..rng.gen_range(0..5) {
+            let len = rng.gen_range(1..40);
+            data.push(len as u8);
+            data.extend((0..len).map(|_| rng.gen::<u8>()));
+        }
+        data
+    }
+
+    fn random_record(rng: &mut StdRng) -> DnsRecord {
+        let name = random_name(rng);
+        let ttl = rng.gen();
+        let cache_flush = rng.gen();
+        match rng.gen_range(0..6) {
+            0 => DnsRecord::A { name, ttl, ip: rng.gen(), cache_flush },
+            1 => DnsRecord::AAAA { name, ttl, ip: rng.gen(), cache_flush },
+            2 => DnsRecord::PTR { name, ttl, ptr_name: random_name(rng), cache_flush },
+            3 => DnsRecord::SRV {
+                name,
+                ttl,
+                priority: rng.gen(),
+                weight: rng.gen(),
+                port: rng.gen(),
+                target: random_name(rng),
+                cache_flush,
+            },
+            4 => DnsRecord::TXT { name, ttl, txt_data: random_txt_data(rng), cache_flush },
+            _ => {
+                // Parsed type bitmaps come back sorted and free of duplicates.
+                let mut types: Vec<u16> = (0..rng.gen_range(1..6)).map(|_| rng.gen()).collect();
+                types.sort_unstable();
+                types.dedup();
+                DnsRecord::NSEC { next_domain: name.clone(), name, ttl, types, cache_flush }
+            }
+        }
+    }
+
+    fn random_packet(rng: &mut StdRng) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.id = rng.gen();
+        packet.flags = rng.gen();
+        for _ in 0..rng.gen_range(0..4) {
+            packet.questions.push(DnsQuestion {
+                qname: random_name(rng),
+                qtype: rng.gen(),
+                // The top bit of the class on the wire is `unicast_response`.
+                qclass: rng.gen_range(0..0x8000),
+                unicast_response: rng.gen(),
+            });
+        }
+        for section in [&mut packet.answers, &mut packet.authorities, &mut packet.additionals] {
+            for _ in 0..rng.gen_range(0..4) {
+                section.push(random_record(rng));
+            }
+        }
+        if rng.gen_bool(0.2) {
+            packet.additionals.push(DnsRecord::OPT {
+                udp_payload_size: rng.gen(),
+                extended_rcode: rng.gen(),
+                version: rng.gen(),
+                dnssec_ok: rng.gen(),
+                options: vec![0, 10, 0, 2, rng.gen(), rng.gen()],
+            });
+        }
+        packet
+    }
+
+    #[test]
+    fn test_serialized_packets_parse_back_unchanged() {
+        let mut rng = StdRng::seed_from_u64(0x726f756e64);
+        for _ in 0..5000 {
+            let packet = random_packet(&mut rng);
+            let parsed = DnsPacket::parse(&packet.serialize())
+                .unwrap_or_else(|err| panic!("{:?} failed to parse: {}", packet, err));
+            assert_eq!(parsed, packet);
+        }
+    }
+}
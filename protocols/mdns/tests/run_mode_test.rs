@@ -0,0 +1,69 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{CancellationToken, DnsPacket, MdnsConfig, MdnsService, RunMode};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    /// Runs a service with one registered local service for a short while and returns
+    /// the packets it sent.
+    async fn run_briefly(run_mode: RunMode) -> Vec<DnsPacket> {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            run_mode,
+            registry_print_interval: None,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let shutdown = CancellationToken::new();
+        Arc::clone(&service)
+            .run("_http._tcp.local".to_string(), 60, 60, shutdown.clone())
+            .await;
+        sleep(Duration::from_millis(200)).await;
+        let sent = transport.sent_packets();
+        shutdown.cancel();
+        sent
+    }
+
+    fn is_query(packet: &DnsPacket) -> bool {
+        packet.flags & 0x8000 == 0
+    }
+
+    #[tokio::test]
+    async fn test_responder_only_announces_without_querying() {
+        let sent = run_briefly(RunMode::ResponderOnly).await;
+        assert!(sent.iter().any(|packet| !is_query(packet)), "The service should be announced");
+        assert!(!sent.iter().any(is_query), "No query should be sent");
+    }
+
+    #[tokio::test]
+    async fn test_querier_only_queries_without_announcing() {
+        let sent = run_briefly(RunMode::QuerierOnly).await;
+        assert!(sent.iter().any(is_query), "The startup query should be sent");
+        assert!(!sent.iter().any(|packet| !is_query(packet)), "Nothing should be announced");
+    }
+
+    #[tokio::test]
+    async fn test_both_announces_and_queries() {
+        let sent = run_briefly(RunMode::Both).await;
+        assert!(sent.iter().any(is_query));
+        assert!(sent.iter().any(|packet| !is_query(packet)));
+    }
+}
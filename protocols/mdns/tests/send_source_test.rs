@@ -0,0 +1,46 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_each_interface_send_records_its_source() {
+        let wired = Ipv4Addr::new(192, 168, 1, 10);
+        let wireless = Ipv4Addr::new(10, 0, 0, 5);
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(wired),
+            interfaces: vec![wired, wireless],
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(service.last_send_source(), None);
+
+        service.advertise_services().await.unwrap();
+
+        let multicast: SocketAddr = "224.0.0.251:5353".parse().unwrap();
+        assert_eq!(
+            transport.interface_sends(),
+            vec![(wired, multicast), (wireless, multicast)]
+        );
+        assert_eq!(
+            service.last_send_source(),
+            Some(SocketAddr::new(wireless.into(), 5353))
+        );
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService, TimingPolicy};
+    use std::collections::HashMap;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn encoded(records: &[DnsRecord]) -> Vec<Vec<u8>> {
+        records
+            .iter()
+            .map(|record| {
+                let mut bytes = Vec::new();
+                record.write(&mut bytes);
+                bytes
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_advertisement_announcement_and_query_answers_agree() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            timing: TimingPolicy {
+                announce_count: 1,
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                Some(HashMap::from([("path".to_string(), "/".to_string())])),
+            )
+            .await
+            .unwrap();
+
+        let advertised = service.create_advertise_packet().await.unwrap().answers;
+
+        service.announce_service("Web._http._tcp.local").await.unwrap();
+        let announced = transport.sent_packets().remove(0).answers;
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: true,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &peer).await;
+        // The response carries the same records, with all but the PTR as additionals. Being
+        // an answer, it also marks the records unique to this host for cache flushing.
+        let reply = transport.sent_packets().remove(1);
+        let mut answered: Vec<DnsRecord> = reply.answers.into_iter().chain(reply.additionals).collect();
+        assert!(answered
+            .iter()
+            .all(|record| record.cache_flush() != matches!(record, DnsRecord::PTR { .. })));
+        answered.iter_mut().for_each(|record| record.set_cache_flush(false));
+
+        assert_eq!(advertised.len(), 4, "PTR, SRV, TXT, and A records expected");
+        assert_eq!(encoded(&advertised), encoded(&announced));
+        assert_eq!(encoded(&advertised), encoded(&answered));
+    }
+}
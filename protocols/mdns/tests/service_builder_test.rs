@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{MdnsConfig, MdnsService, ServiceRecord};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_builder_defaults() {
+        let record = ServiceRecord::builder("Web._http._tcp.local", "_http._tcp.local", 8080)
+            .origin("web-host.local")
+            .build();
+        assert_eq!(record.id, "Web._http._tcp.local");
+        assert_eq!(record.service_type, "_http._tcp.local");
+        assert_eq!(record.origin, "web-host.local");
+        assert_eq!(record.port, 8080);
+        assert_eq!(record.ttl, None);
+        assert_eq!(record.priority, Some(0));
+        assert_eq!(record.weight, Some(0));
+        assert!(record.txt.is_empty());
+    }
+
+    #[test]
+    fn test_builder_sets_optional_fields() {
+        let record = ServiceRecord::builder("Web._http._tcp.local", "_http._tcp.local", 8080)
+            .origin("web-host.local")
+            .ttl(60)
+            .priority(10)
+            .weight(5)
+            .txt("version", "1")
+            .txt("path", "/old")
+            .txt("path", "/")
+            .build();
+        assert_eq!(record.ttl, Some(60));
+        assert_eq!(record.priority, Some(10));
+        assert_eq!(record.weight, Some(5));
+        assert_eq!(
+            record.txt,
+            vec![
+                ("path".to_string(), "/".to_string()),
+                ("version".to_string(), "1".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_service_accepts_built_record() {
+        let config = MdnsConfig {
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        let record = ServiceRecord::builder("Web._http._tcp.local", "_http._tcp.local", 8080)
+            .origin("web-host.local")
+            .weight(5)
+            .build();
+        service.register_service(record).await.unwrap();
+
+        let registered = service.registry.get_service("Web._http._tcp.local").await.unwrap();
+        assert_eq!(registered.origin, "web-host.local");
+        assert_eq!(registered.weight, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_register_service_without_origin_uses_hostname() {
+        let config = MdnsConfig {
+            hostname: Some("box.local".to_string()),
+            keep_service_origins: true,
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+        let record = ServiceRecord::builder("Web._http._tcp.local", "_http._tcp.local", 8080).build();
+        service.register_service(record).await.unwrap();
+
+        let registered = service.registry.get_service("Web._http._tcp.local").await.unwrap();
+        assert_eq!(registered.origin, "box.local");
+    }
+}
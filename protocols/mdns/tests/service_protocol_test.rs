@@ -0,0 +1,99 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService, Protocol, ServiceRecord};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn service(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        MdnsService::with_transport(transport, config)
+    }
+
+    async fn registered_type(service: &MdnsService, id: &str) -> String {
+        service.registry.get_service(id).await.unwrap().service_type
+    }
+
+    #[test]
+    fn test_protocol_is_read_from_the_service_type() {
+        assert_eq!(Protocol::of_service_type("_ipp._tcp.local"), Some(Protocol::Tcp));
+        assert_eq!(Protocol::of_service_type("_dns._UDP.local"), Some(Protocol::Udp));
+        assert_eq!(Protocol::of_service_type("_http"), None);
+
+        let record = ServiceRecord::builder("Resolver._dns._udp.local", "_dns._udp.local", 53).build();
+        assert_eq!(record.protocol, Some(Protocol::Udp));
+    }
+
+    #[tokio::test]
+    async fn test_omitted_protocol_and_domain_are_filled_in() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service(transport.clone());
+        let record = ServiceRecord::builder("Web._http._tcp.local", "_http", 80)
+            .origin("MyHost.local")
+            .build();
+        service.register_service(record).await.unwrap();
+        let record = ServiceRecord::builder("Resolver._dns._udp.local", "_dns._udp", 53)
+            .protocol(Protocol::Udp)
+            .origin("MyHost.local")
+            .build();
+        service.register_service(record).await.unwrap();
+
+        assert_eq!(registered_type(&service, "Web._http._tcp.local").await, "_http._tcp.local");
+        assert_eq!(registered_type(&service, "Resolver._dns._udp.local").await, "_dns._udp.local");
+
+        // A browse for the full type now finds the service registered as `_http`.
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &peer).await;
+        assert!(!transport.sent_packets()[0].answers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_protocol_is_rejected() {
+        let service = service(Arc::new(MockTransport::new()));
+        let record = ServiceRecord::builder("Web._http._tcp.local", "_http._tcp.local", 80)
+            .protocol(Protocol::Udp)
+            .origin("MyHost.local")
+            .build();
+
+        assert!(service.register_service(record).await.is_err());
+        assert!(service.registry.list_services().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_struct_literal_udp_service_takes_the_protocol_of_its_type() {
+        let service = service(Arc::new(MockTransport::new()));
+        let record = ServiceRecord {
+            id: "Resolver._dns._udp.local".to_string(),
+            service_type: "_dns._udp.local".to_string(),
+            port: 53,
+            ttl: Some(120),
+            origin: "MyHost.local".to_string(),
+            priority: None,
+            weight: None,
+            cache_flush: Default::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: None,
+            bind_addr: None,
+        };
+
+        service.register_service(record).await.unwrap();
+
+        let registered = service.registry.get_service("Resolver._dns._udp.local").await.unwrap();
+        assert_eq!(registered.service_type, "_dns._udp.local");
+        assert_eq!(registered.protocol, Some(Protocol::Udp));
+    }
+}
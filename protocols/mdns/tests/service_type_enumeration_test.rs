@@ -0,0 +1,107 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService, TimingPolicy};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    const META: &str = "_services._dns-sd._udp.local";
+
+    #[tokio::test]
+    async fn test_meta_query_lists_each_local_service_type_once() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        for (id, service_type) in [
+            ("One._http._tcp.local", "_http._tcp.local"),
+            ("Two._http._tcp.local", "_http._tcp.local"),
+            ("Printer._ipp._tcp.local", "_ipp._tcp.local"),
+        ] {
+            service
+                .register_local_service(
+                    id.to_string(),
+                    service_type.to_string(),
+                    8080,
+                    Some(120),
+                    "MyHost.local".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new(META).unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let peer: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &peer).await;
+
+        let response = &transport.sent_packets()[0];
+        let types: Vec<String> = response
+            .answers
+            .iter()
+            .map(|record| match record {
+                DnsRecord::PTR { name, ptr_name, .. } => {
+                    assert_eq!(name.to_string(), META);
+                    ptr_name.to_string()
+                }
+                other => panic!("unexpected record {:?}", other),
+            })
+            .collect();
+        assert_eq!(types, vec!["_http._tcp.local", "_ipp._tcp.local"]);
+    }
+
+    #[tokio::test]
+    async fn test_enumerate_service_types_collects_peer_answers() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                resolve_quiet_period: Duration::from_millis(100),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+
+        let responder = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+                sleep(Duration::from_millis(50)).await;
+                let mut answer = DnsPacket::new();
+                for service_type in ["_ipp._tcp.local", "_http._tcp.local"] {
+                    answer.answers.push(DnsRecord::PTR {
+                        name: DnsName::new(META).unwrap(),
+                        ttl: 120,
+                        ptr_name: DnsName::new(service_type).unwrap(),
+                        cache_flush: false,
+                    });
+                }
+                service.process_response(&answer, &src).await;
+            })
+        };
+
+        let types = service
+            .enumerate_service_types(Duration::from_secs(5))
+            .await
+            .unwrap();
+        responder.await.unwrap();
+
+        assert_eq!(types, vec!["_http._tcp.local", "_ipp._tcp.local"]);
+        let query = &transport.sent_packets()[0];
+        assert_eq!(query.questions[0].qname.to_string(), META);
+        assert_eq!(query.questions[0].qtype, 12);
+        assert!(service.registry.list_discovered_services().await.is_empty());
+    }
+}
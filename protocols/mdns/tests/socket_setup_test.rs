@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{MdnsConfig, MdnsError, MdnsService};
+    use std::net::Ipv4Addr;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_unavailable_interface_is_retried_then_reported() {
+        // 192.0.2.1 (TEST-NET-1) is never assigned to a local interface.
+        let config = MdnsConfig {
+            interface: Some(Ipv4Addr::new(192, 0, 2, 1)),
+            port: 0,
+            socket_setup_attempts: 3,
+            socket_setup_backoff: Duration::from_millis(20),
+            ..MdnsConfig::default()
+        };
+
+        let started = Instant::now();
+        let err = match MdnsService::with_config(config).await {
+            Ok(_) => panic!("setup should fail on an unassigned interface"),
+            Err(err) => err,
+        };
+
+        match &err {
+            MdnsError::SocketSetup { step, error } => {
+                assert_eq!(step, "selecting multicast interface 192.0.2.1");
+                assert_eq!(error.kind(), std::io::ErrorKind::AddrNotAvailable);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.to_string().starts_with("Socket setup failed while selecting"));
+        // Two retries wait 20ms and 40ms.
+        assert!(started.elapsed() >= Duration::from_millis(60));
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_multicast_ttl_is_reported() {
+        let config = MdnsConfig {
+            port: 0,
+            multicast_ttl: 256,
+            socket_setup_attempts: 1,
+            ..MdnsConfig::default()
+        };
+
+        match MdnsService::with_config(config).await {
+            Err(MdnsError::SocketSetup { step, .. }) => assert_eq!(step, "setting multicast TTL 256"),
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("setup should reject a TTL above 255"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_attempt_fails_without_waiting() {
+        let config = MdnsConfig {
+            interface: Some(Ipv4Addr::new(192, 0, 2, 1)),
+            port: 0,
+            socket_setup_attempts: 1,
+            socket_setup_backoff: Duration::from_secs(5),
+            ..MdnsConfig::default()
+        };
+
+        let started = Instant::now();
+        assert!(MdnsService::with_config(config).await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,80 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService, ResolvedInstance, TimingPolicy};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn instance(name: &str, priority: u16, weight: u16) -> ResolvedInstance {
+        ResolvedInstance {
+            instance: name.to_string(),
+            host: "host.local".to_string(),
+            port: 80,
+            priority,
+            weight,
+            addresses: Vec::new(),
+            txt: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_pick_prefers_low_priority_and_high_weight() {
+        let instances = vec![
+            instance("backup", 10, 1000),
+            instance("light", 0, 1),
+            instance("heavy", 0, 99),
+        ];
+        let mut picks: HashMap<String, usize> = HashMap::new();
+        for _ in 0..2000 {
+            let picked = ResolvedInstance::pick(&instances).unwrap();
+            *picks.entry(picked.instance.clone()).or_default() += 1;
+        }
+
+        assert!(!picks.contains_key("backup"), "A higher priority value is only a fallback");
+        assert!(picks["heavy"] > picks.get("light").copied().unwrap_or(0) * 10);
+        assert!(ResolvedInstance::pick(&[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_orders_by_priority() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                resolve_quiet_period: Duration::from_millis(10),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+
+        let mut response = DnsPacket::new();
+        for (name, priority) in [("C", 0), ("A", 20), ("B", 10)] {
+            let instance = DnsName::new(&format!("{}._http._tcp.local", name)).unwrap();
+            response.answers.push(DnsRecord::PTR {
+                name: DnsName::new("_http._tcp.local").unwrap(),
+                ttl: 120,
+                ptr_name: instance.clone(),
+                cache_flush: false,
+            });
+            response.answers.push(DnsRecord::SRV {
+                name: instance,
+                ttl: 120,
+                priority,
+                weight: 5,
+                port: 80,
+                target: DnsName::new("host.local").unwrap(),
+                cache_flush: false,
+            });
+        }
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+        service.process_response(&response, &src).await;
+
+        let found = service.resolve("_http._tcp.local", Duration::from_secs(1)).await.unwrap();
+        let ids: Vec<&str> = found.iter().map(|service| service.id.as_str()).collect();
+        assert_eq!(ids, vec!["C._http._tcp.local", "B._http._tcp.local", "A._http._tcp.local"]);
+    }
+}
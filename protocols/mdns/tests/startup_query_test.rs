@@ -0,0 +1,30 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{CancellationToken, MdnsConfig, MdnsService};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    #[tokio::test]
+    async fn test_query_sent_promptly_on_run() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        // Use an interval far longer than the test so only the initial query can be observed.
+        Arc::clone(&service)
+            .run("_http._tcp.local".to_string(), 60, 60, CancellationToken::new())
+            .await;
+        sleep(Duration::from_millis(200)).await;
+
+        let queries: Vec<_> = transport
+            .sent_packets()
+            .into_iter()
+            .filter(|packet| packet.flags & 0x8000 == 0)
+            .collect();
+        assert_eq!(queries.len(), 1, "Exactly one query should go out right after start");
+        assert_eq!(queries[0].questions[0].qname.to_string(), "_http._tcp.local");
+        assert_eq!(queries[0].questions[0].qtype, 12);
+    }
+}
@@ -0,0 +1,163 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{
+        CacheFlush, DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService,
+        Protocol, ServiceRecord, TimingPolicy,
+    };
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    async fn service_with_subtype(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .registry
+            .add_service(ServiceRecord {
+                id: "Printer._http._tcp.local".to_string(),
+                service_type: "_http._tcp.local".to_string(),
+                port: 8080,
+                ttl: Some(120),
+                origin: "printer-host.local".to_string(),
+                priority: Some(0),
+                weight: Some(0),
+                cache_flush: CacheFlush::default(),
+                txt: Vec::new(),
+                subtypes: vec!["_printer".to_string()],
+                protocol: Some(Protocol::Tcp),
+                bind_addr: None,
+            })
+            .await
+            .unwrap();
+        service
+    }
+
+    fn ptr_owners(packet: &DnsPacket) -> Vec<String> {
+        packet
+            .answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::PTR { name, ptr_name, .. } => {
+                    assert_eq!(ptr_name.to_string(), "Printer._http._tcp.local");
+                    Some(name.to_string())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_advertise_includes_base_and_subtype_ptr() {
+        let transport = Arc::new(MockTransport::new());
+        let service = service_with_subtype(transport).await;
+
+        let packet = service.create_advertise_packet().await.unwrap();
+        assert_eq!(
+            ptr_owners(&packet),
+            vec![
+                "_http._tcp.local".to_string(),
+                "_printer._sub._http._tcp.local".to_string()
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_browse_by_base_type_or_subtype_finds_instance() {
+        for browse_name in ["_http._tcp.local", "_printer._sub._http._tcp.local"] {
+            let transport = Arc::new(MockTransport::new());
+            let service = service_with_subtype(transport.clone()).await;
+
+            let mut query = DnsPacket::new();
+            query.questions.push(DnsQuestion {
+                qname: DnsName::new(browse_name).unwrap(),
+                qtype: 12,
+                qclass: 1,
+                unicast_response: false,
+            });
+            let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+            service.process_query(&query, &src).await;
+
+            let sent = transport.sent_packets();
+            assert_eq!(sent.len(), 1, "Browsing '{}' should find the instance", browse_name);
+            assert_eq!(ptr_owners(&sent[0]), vec![browse_name.to_string()]);
+        }
+    }
+
+    /// A peer's answer for `instance`, with a PTR for each browse name it is listed under.
+    fn peer_answer(instance: &str, browse_names: &[&str]) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        for browse_name in browse_names {
+            packet.answers.push(DnsRecord::PTR {
+                name: DnsName::new(browse_name).unwrap(),
+                ttl: 120,
+                ptr_name: DnsName::new(instance).unwrap(),
+                cache_flush: false,
+            });
+        }
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new(instance).unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 631,
+            target: DnsName::new("peer.local").unwrap(),
+            cache_flush: false,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_resolve_by_subtype_finds_only_matching_instances() {
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                resolve_quiet_period: Duration::from_millis(100),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), config);
+
+        let responder = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+                sleep(Duration::from_millis(20)).await;
+                service
+                    .process_response(
+                        &peer_answer(
+                            "Color._ipp._tcp.local",
+                            &["_ipp._tcp.local", "_color._sub._ipp._tcp.local"],
+                        ),
+                        &src,
+                    )
+                    .await;
+                service
+                    .process_response(&peer_answer("Mono._ipp._tcp.local", &["_ipp._tcp.local"]), &src)
+                    .await;
+            })
+        };
+
+        let services = service
+            .resolve("_color._sub._ipp._tcp.local", Duration::from_secs(2))
+            .await
+            .unwrap();
+        responder.await.unwrap();
+
+        let ids: Vec<&str> = services.iter().map(|service| service.id.as_str()).collect();
+        assert_eq!(ids, vec!["Color._ipp._tcp.local"]);
+        assert_eq!(services[0].subtypes, vec!["_color".to_string()]);
+
+        // The base type still finds every instance.
+        let all = service
+            .resolve("_ipp._tcp.local", Duration::from_millis(200))
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+    }
+}
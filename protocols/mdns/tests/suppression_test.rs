@@ -0,0 +1,56 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_clearing_suppression_lets_repeated_query_be_answered() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut query = DnsPacket::new();
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        // The identical query within a second is suppressed by the rate limit.
+        service.process_query(&query, &src).await;
+        service.process_query(&query, &src).await;
+        assert_eq!(transport.sent().len(), 1);
+
+        let state = service.suppression_state();
+        let ptr = state
+            .get("PTR _http._tcp.local -> Web._http._tcp.local")
+            .expect("PTR record should be tracked");
+        assert_eq!(ptr.suppressed, 1);
+
+        service.clear_suppression();
+        assert!(service.suppression_state().is_empty());
+
+        service.process_query(&query, &src).await;
+        assert_eq!(transport.sent().len(), 2, "The query should be answered after clearing");
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use mdns::{MdnsConfig, TimingPolicy};
+    use std::time::Duration;
+
+    #[test]
+    fn test_default_timing_matches_rfc_6762() {
+        let timing = MdnsConfig::default().timing;
+        assert_eq!(
+            timing.probe_schedule(),
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(250),
+                Duration::from_millis(500)
+            ]
+        );
+        assert_eq!(
+            timing.announce_schedule(),
+            vec![Duration::ZERO, Duration::from_secs(1), Duration::from_secs(2)]
+        );
+        assert_eq!(timing.multicast_rate_limit, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_custom_timing_policy_is_reflected() {
+        let timing = TimingPolicy {
+            probe_count: 2,
+            probe_interval: Duration::from_millis(100),
+            announce_count: 3,
+            announce_interval: Duration::from_millis(500),
+            response_delay_min: Duration::from_millis(5),
+            response_delay_max: Duration::from_millis(10),
+            ..TimingPolicy::default()
+        };
+        let config = MdnsConfig {
+            timing: timing.clone(),
+            ..MdnsConfig::default()
+        };
+
+        assert_eq!(
+            config.timing.probe_schedule(),
+            vec![Duration::ZERO, Duration::from_millis(100)]
+        );
+        assert_eq!(
+            config.timing.announce_schedule(),
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_millis(1000)
+            ]
+        );
+        for _ in 0..100 {
+            let delay = config.timing.response_delay();
+            assert!(delay >= Duration::from_millis(5) && delay <= Duration::from_millis(10));
+        }
+
+        let fixed = TimingPolicy {
+            response_delay_min: Duration::from_millis(30),
+            response_delay_max: Duration::from_millis(30),
+            ..timing
+        };
+        assert_eq!(fixed.response_delay(), Duration::from_millis(30));
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsHeader, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    const TRUNCATED: u16 = 0x0200;
+
+    fn ptr(instance: &str) -> DnsRecord {
+        DnsRecord::PTR {
+            name: DnsName::new("_http._tcp.local").unwrap(),
+            ttl: 120,
+            ptr_name: DnsName::new(instance).unwrap(),
+            cache_flush: false,
+        }
+    }
+
+    /// A response from a responder that could only fit its first two instances.
+    fn partial_response(truncated: bool) -> DnsPacket {
+        let flags = DnsHeader::response().flags | if truncated { TRUNCATED } else { 0 };
+        let mut packet = DnsPacket::with_header(DnsHeader { id: 0, flags });
+        packet.answers.push(ptr("One._http._tcp.local"));
+        packet.answers.push(ptr("Two._http._tcp.local"));
+        packet
+    }
+
+    fn responder_addr() -> SocketAddr {
+        "192.168.1.10:5353".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_truncated_response_triggers_known_answer_query() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        service.process_response(&partial_response(true), &responder_addr()).await;
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1, "One follow-up query expected");
+        let query = &sent[0];
+        assert!(!query.is_response());
+        assert_eq!(query.questions.len(), 1);
+        assert_eq!(query.questions[0].qname.to_string(), "_http._tcp.local");
+        assert_eq!(query.questions[0].qtype, 12);
+        assert_eq!(query.answers, partial_response(true).answers);
+    }
+
+    #[tokio::test]
+    async fn test_complete_response_sends_nothing() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+
+        service.process_response(&partial_response(false), &responder_addr()).await;
+
+        assert!(transport.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_follow_up_pulls_only_the_missing_instances() {
+        let querier_transport = Arc::new(MockTransport::new());
+        let querier = MdnsService::with_transport(querier_transport.clone(), MdnsConfig::default());
+        querier.process_response(&partial_response(true), &responder_addr()).await;
+        let follow_up = querier_transport.sent_packets().remove(0);
+
+        let responder_transport = Arc::new(MockTransport::new());
+        let responder = MdnsService::with_transport(
+            responder_transport.clone(),
+            MdnsConfig {
+                advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+                ..MdnsConfig::default()
+            },
+        );
+        for instance in ["One", "Two", "Three"] {
+            responder
+                .register_local_service(
+                    format!("{}._http._tcp.local", instance),
+                    "_http._tcp.local".to_string(),
+                    8080,
+                    Some(120),
+                    "web-host.local".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        let querier_addr: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        responder.process_query(&follow_up, &querier_addr).await;
+
+        let response = responder_transport.sent_packets().remove(0);
+        let instances: Vec<String> = response
+            .answers
+            .iter()
+            .filter_map(|record| match record {
+                DnsRecord::PTR { ptr_name, .. } => Some(ptr_name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(instances, vec!["Three._http._tcp.local".to_string()]);
+    }
+}
@@ -0,0 +1,111 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, DnsRecord, MdnsConfig, MdnsService};
+    use std::collections::HashMap;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn metadata() -> HashMap<String, String> {
+        HashMap::from([
+            ("version".to_string(), "1.2".to_string()),
+            ("path".to_string(), "/api".to_string()),
+        ])
+    }
+
+    /// Decodes TXT RDATA into its `key=value` strings.
+    fn txt_strings(data: &[u8]) -> Vec<String> {
+        let mut strings = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let len = data[offset] as usize;
+            strings.push(String::from_utf8_lossy(&data[offset + 1..offset + 1 + len]).into_owned());
+            offset += 1 + len;
+        }
+        strings
+    }
+
+    #[tokio::test]
+    async fn test_query_answer_includes_txt_metadata() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Api._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "api-host.local".to_string(),
+                Some(metadata()),
+            )
+            .await
+            .unwrap();
+
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response: false,
+        });
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+        service.process_query(&query, &src).await;
+
+        let reply = &transport.sent_packets()[0];
+        let txt = reply
+            .additionals
+            .iter()
+            .find_map(|record| match record {
+                DnsRecord::TXT { name, txt_data, .. } => Some((name.to_string(), txt_data.clone())),
+                _ => None,
+            })
+            .expect("Reply should carry a TXT record");
+        assert_eq!(txt.0, "Api._http._tcp.local");
+        assert_eq!(txt_strings(&txt.1), vec!["path=/api", "version=1.2"]);
+    }
+
+    #[tokio::test]
+    async fn test_discovered_service_keeps_decoded_txt() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, MdnsConfig::default());
+
+        let mut response = DnsPacket::new();
+        response.answers.push(DnsRecord::TXT {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            txt_data: b"\x0bversion=2.0\x07secure".to_vec(),
+            cache_flush: false,
+        });
+        response.answers.push(DnsRecord::SRV {
+            name: DnsName::new("Peer._http._tcp.local").unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 9000,
+            target: DnsName::new("peer.local").unwrap(),
+            cache_flush: false,
+        });
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+        service.process_response(&response, &src).await;
+
+        let discovered = service
+            .registry
+            .get_discovered_service("Peer._http._tcp.local")
+            .await
+            .unwrap();
+        assert_eq!(
+            discovered.txt,
+            vec![
+                ("version".to_string(), "2.0".to_string()),
+                ("secure".to_string(), String::new()),
+            ]
+        );
+    }
+}
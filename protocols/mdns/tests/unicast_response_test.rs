@@ -0,0 +1,78 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsQuestion, MdnsConfig, MdnsService};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+
+    fn ptr_query(unicast_response: bool) -> DnsPacket {
+        let mut query = DnsPacket::new();
+        query.flags = 0x0000;
+        query.questions.push(DnsQuestion {
+            qname: DnsName::new("_http._tcp.local").unwrap(),
+            qtype: 12,
+            qclass: 1,
+            unicast_response,
+        });
+        query
+    }
+
+    async fn responder(transport: Arc<MockTransport>) -> Arc<MdnsService> {
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport, config);
+        service
+            .register_local_service(
+                "MyService._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "MyHost.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        service
+    }
+
+    #[test]
+    fn test_qu_bit_roundtrips_separately_from_class() {
+        let bytes = ptr_query(true).serialize();
+        let parsed = DnsPacket::parse(&bytes).unwrap();
+        assert!(parsed.questions[0].unicast_response);
+        assert_eq!(parsed.questions[0].qclass, 1);
+    }
+
+    #[tokio::test]
+    async fn test_qu_query_is_answered_by_unicast() {
+        let transport = Arc::new(MockTransport::new());
+        let service = responder(transport.clone()).await;
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        let query = DnsPacket::parse(&ptr_query(true).serialize()).unwrap();
+        service.process_query(&query, &src).await;
+
+        let sent = transport.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1, src, "QU response must go straight to the querier");
+        // The answer was not multicast, so it must not count against the rate limit.
+        assert!(service.suppression_state().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_qm_query_is_answered_by_multicast() {
+        let transport = Arc::new(MockTransport::new());
+        let service = responder(transport.clone()).await;
+        let src: SocketAddr = "192.168.1.20:5353".parse().unwrap();
+
+        service.process_query(&ptr_query(false), &src).await;
+
+        let sent = transport.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1, "224.0.0.251:5353".parse::<SocketAddr>().unwrap());
+    }
+}
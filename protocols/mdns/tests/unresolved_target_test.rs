@@ -0,0 +1,116 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{
+        CancellationToken, DiscoveryEvent, DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsService,
+        TimingPolicy,
+    };
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{timeout, Duration};
+
+    fn srv_response(instance: &str, target: &str) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new(instance).unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 8080,
+            target: DnsName::new(target).unwrap(),
+            cache_flush: false,
+        });
+        packet
+    }
+
+    fn a_response(host: &str, ip: [u8; 4]) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::A {
+            name: DnsName::new(host).unwrap(),
+            ttl: 120,
+            ip,
+            cache_flush: false,
+        });
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_services_without_target_address_are_flagged_once() {
+        let service = MdnsService::with_transport(Arc::new(MockTransport::new()), MdnsConfig::default());
+        let src: SocketAddr = "192.168.1.30:5353".parse().unwrap();
+        service
+            .process_response(&srv_response("Web._http._tcp.local", "web-host.local"), &src)
+            .await;
+        service
+            .process_response(&srv_response("Print._ipp._tcp.local", "printer.local"), &src)
+            .await;
+        service
+            .process_response(&a_response("printer.local", [192, 168, 1, 40]), &src)
+            .await;
+
+        let states: Vec<(String, bool)> = service
+            .registry
+            .list_services_with_state()
+            .await
+            .into_iter()
+            .map(|(record, resolves)| (record.id, resolves))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("Print._ipp._tcp.local".to_string(), true),
+                ("Web._http._tcp.local".to_string(), false),
+            ]
+        );
+
+        assert_eq!(service.registry.flag_unresolved().await, vec!["Web._http._tcp.local"]);
+        assert!(service.registry.flag_unresolved().await.is_empty(), "Flagged only once");
+
+        // Once the target resolves the flag clears, so a later loss is reported again.
+        service
+            .process_response(&a_response("web-host.local", [192, 168, 1, 30]), &src)
+            .await;
+        assert!(service.registry.flag_unresolved().await.is_empty());
+        service.registry.remove_node("web-host.local").await.unwrap();
+        assert_eq!(service.registry.flag_unresolved().await, vec!["Web._http._tcp.local"]);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_emits_service_unresolved() {
+        let transport = Arc::new(MockTransport::new());
+        transport.hold_open();
+        let config = MdnsConfig {
+            timing: TimingPolicy {
+                record_sweep_interval: Duration::from_millis(20),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        let mut events = service.subscribe();
+        transport.push_inbound(
+            srv_response("Web._http._tcp.local", "web-host.local").serialize(),
+            "192.168.1.30:5353".parse().unwrap(),
+        );
+
+        let shutdown = CancellationToken::new();
+        Arc::clone(&service)
+            .run("_http._tcp.local".to_string(), 60, 60, shutdown.clone())
+            .await;
+
+        let event = timeout(Duration::from_secs(2), async {
+            loop {
+                match events.recv().await.unwrap() {
+                    DiscoveryEvent::ServiceUnresolved(id) => return id,
+                    _ => continue,
+                }
+            }
+        })
+        .await
+        .expect("the sweep should flag the unresolved service");
+        shutdown.cancel();
+        assert_eq!(event, "Web._http._tcp.local");
+    }
+}
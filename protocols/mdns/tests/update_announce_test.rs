@@ -0,0 +1,83 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsRecord, MdnsConfig, MdnsService};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_update_announces_new_port_with_cache_flush() {
+        let transport = Arc::new(MockTransport::new());
+        let config = MdnsConfig {
+            advertised_address: Some(Ipv4Addr::new(192, 168, 1, 10)),
+            ..MdnsConfig::default()
+        };
+        let service = MdnsService::with_transport(transport.clone(), config);
+        service
+            .register_local_service(
+                "Web._http._tcp.local".to_string(),
+                "_http._tcp.local".to_string(),
+                8080,
+                Some(120),
+                "web-host.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut updated = service.registry.get_service("Web._http._tcp.local").await.unwrap();
+        updated.port = 9090;
+        updated.txt = vec![("version".to_string(), "2".to_string())];
+        service
+            .update_and_announce("Web._http._tcp.local", updated)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            service.registry.get_service("Web._http._tcp.local").await.unwrap().port,
+            9090
+        );
+
+        let sent = transport.sent_packets();
+        assert_eq!(sent.len(), 1, "The update should be announced in a single packet");
+        let answers = &sent[0].answers;
+        assert!(answers.iter().any(|record| matches!(
+            record,
+            DnsRecord::SRV { port: 9090, cache_flush: true, .. }
+        )));
+        assert!(answers
+            .iter()
+            .any(|record| matches!(record, DnsRecord::TXT { cache_flush: true, .. })));
+        assert!(answers
+            .iter()
+            .any(|record| matches!(record, DnsRecord::PTR { cache_flush: false, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_update_of_unknown_service_fails() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), MdnsConfig::default());
+        let record = mdns::ServiceRecord {
+            id: "Missing._http._tcp.local".to_string(),
+            service_type: "_http._tcp.local".to_string(),
+            port: 80,
+            ttl: Some(120),
+            origin: "host.local".to_string(),
+            priority: Some(0),
+            weight: Some(0),
+            cache_flush: mdns::CacheFlush::default(),
+            txt: Vec::new(),
+            subtypes: Vec::new(),
+            protocol: Some(mdns::Protocol::Tcp),
+            bind_addr: None,
+        };
+
+        assert!(service
+            .update_and_announce("Missing._http._tcp.local", record)
+            .await
+            .is_err());
+        assert!(transport.sent().is_empty());
+    }
+}
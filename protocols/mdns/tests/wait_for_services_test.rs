@@ -0,0 +1,90 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockTransport;
+    use mdns::{DnsName, DnsPacket, DnsRecord, MdnsConfig, MdnsError, MdnsService, TimingPolicy};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::{sleep, Duration};
+
+    fn answer(instance: &str) -> DnsPacket {
+        let mut packet = DnsPacket::new();
+        packet.answers.push(DnsRecord::PTR {
+            name: DnsName::new("_node._tcp.local").unwrap(),
+            ttl: 120,
+            ptr_name: DnsName::new(instance).unwrap(),
+            cache_flush: false,
+        });
+        packet.answers.push(DnsRecord::SRV {
+            name: DnsName::new(instance).unwrap(),
+            ttl: 120,
+            priority: 0,
+            weight: 0,
+            port: 7000,
+            target: DnsName::new("peer.local").unwrap(),
+            cache_flush: false,
+        });
+        packet
+    }
+
+    fn config() -> MdnsConfig {
+        MdnsConfig {
+            timing: TimingPolicy {
+                requery_interval: Duration::from_millis(40),
+                requery_interval_max: Duration::from_millis(80),
+                ..TimingPolicy::default()
+            },
+            ..MdnsConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_services_returns_once_quorum_is_known() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport.clone(), config());
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+        // One peer is known before waiting starts.
+        service.process_response(&answer("A._node._tcp.local"), &src).await;
+
+        let responder = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                sleep(Duration::from_millis(150)).await;
+                service.process_response(&answer("B._node._tcp.local"), &src).await;
+                service.process_response(&answer("A._node._tcp.local"), &src).await;
+                sleep(Duration::from_millis(100)).await;
+                service.process_response(&answer("C._node._tcp.local"), &src).await;
+            })
+        };
+
+        let services = service
+            .wait_for_services("_node._tcp.local", 3, Duration::from_secs(5))
+            .await
+            .unwrap();
+        responder.await.unwrap();
+
+        let ids: Vec<_> = services.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["A._node._tcp.local", "B._node._tcp.local", "C._node._tcp.local"]);
+        let queries = transport.sent_packets();
+        assert!(queries.len() >= 3, "The query should be repeated while waiting, sent {}", queries.len());
+        assert!(queries.iter().all(|query| query.questions[0].qtype == 12));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_services_times_out_short_of_quorum() {
+        let transport = Arc::new(MockTransport::new());
+        let service = MdnsService::with_transport(transport, config());
+        let src: SocketAddr = "192.168.1.50:5353".parse().unwrap();
+        service.process_response(&answer("A._node._tcp.local"), &src).await;
+
+        let result = service
+            .wait_for_services("_node._tcp.local", 2, Duration::from_millis(150))
+            .await;
+
+        match result {
+            Err(MdnsError::Timeout(message)) => assert!(message.contains("1 of 2"), "{}", message),
+            other => panic!("Expected a timeout, got {:?}", other),
+        }
+    }
+}
@@ -15,15 +15,19 @@ use std::time::Duration;
 use tokio::task;
 
 /// Represents an entry in the expiration heap, containing an expiration time and record identifier.
+/// The heap holds one entry per stored record.
 #[derive(Debug, Clone)]
 struct ExpirationEntry {
     expires_at: Option<SystemTime>,
     identifier: String,
+    sequence: u64, // Order of insertion, so equal expirations evict the oldest record first.
 }
 
 impl PartialEq for ExpirationEntry {
     fn eq(&self, other: &Self) -> bool {
-        self.expires_at == other.expires_at && self.identifier == other.identifier
+        self.expires_at == other.expires_at
+            && self.identifier == other.identifier
+            && self.sequence == other.sequence
     }
 }
 
@@ -43,13 +47,15 @@ impl Ord for ExpirationEntry {
 
 impl ExpirationEntry {
     /// Compares two expiration entries, treating the earliest expiration as "less".
+    /// Entries expiring at the same time order by insertion, the oldest popping first.
     fn compare_expirations(a: &Self, b: &Self) -> Ordering {
-        match (a.expires_at, b.expires_at) {
+        let by_expiration = match (a.expires_at, b.expires_at) {
             (Some(a_time), Some(b_time)) => b_time.cmp(&a_time),
             (None, Some(_)) => Ordering::Less,   // None = no expiration => treat as "infinite"
             (Some(_), None) => Ordering::Greater,
             (None, None) => Ordering::Equal,
-        }
+        };
+        by_expiration.then_with(|| b.sequence.cmp(&a.sequence))
     }
 }
 
@@ -58,7 +64,28 @@ impl ExpirationEntry {
 struct Inner<R: Record> {
     records: HashMap<String, R>, // Keyed by identifier
     heap: BinaryHeap<ExpirationEntry>,
+    expirations: HashMap<String, Option<SystemTime>>, // Current expiration of each record
     capacity: usize,
+    next_sequence: u64, // Sequence number of the next heap entry
+}
+
+impl<R: Record> Inner<R> {
+    /// Returns whether a heap entry still reflects the latest version of its record.
+    /// Re-adding a record pushes a new entry and leaves the old one behind as stale.
+    fn is_current(&self, entry: &ExpirationEntry) -> bool {
+        self.expirations.get(&entry.identifier) == Some(&entry.expires_at)
+    }
+
+    /// Removes a record together with its expiration bookkeeping.
+    fn evict(&mut self, identifier: &str) -> bool {
+        self.expirations.remove(identifier);
+        self.records.remove(identifier).is_some()
+    }
+
+    /// Drops the heap entry of a record, so re-adding or removing it leaves nothing behind.
+    fn forget_expiration(&mut self, identifier: &str) {
+        self.heap.retain(|entry| entry.identifier != identifier);
+    }
 }
 
 /// A thread-safe, in-memory registry implementation.
@@ -72,7 +99,9 @@ impl<R: Record + 'static> InMemoryRegistry<R> {
         let inner = Inner {
             records: HashMap::new(),
             heap: BinaryHeap::new(),
+            expirations: HashMap::new(),
             capacity,
+            next_sequence: 0,
         };
         let registry = Self {
             inner: Arc::new(RwLock::new(inner)),
@@ -103,7 +132,9 @@ impl<R: Record + 'static> InMemoryRegistry<R> {
 
             if is_expired {
                 let expired = guard.heap.pop().unwrap();
-                guard.records.remove(&expired.identifier);
+                if guard.is_current(&expired) {
+                    guard.evict(&expired.identifier);
+                }
             } else {
                 break; // Stop if the earliest expiration is in the future
             }
@@ -121,7 +152,10 @@ impl<R: Record + 'static> InMemoryRegistry<R> {
         // Evict oldest records if still exceeding capacity
         while guard.records.len() > guard.capacity {
             if let Some(top) = guard.heap.pop() {
-                if guard.records.remove(&top.identifier).is_some() {
+                if !guard.is_current(&top) {
+                    continue;
+                }
+                if guard.evict(&top.identifier) {
                     println!("Evicting record due to capacity: {}", top.identifier);
                 } else {
                     println!(
@@ -203,8 +237,12 @@ impl<R: Record + Send + Sync + 'static> Registry<R> for InMemoryRegistry<R>{
             // Insert or update the record
             guard.records.insert(identifier.clone(), record.clone());
 
-            // Insert into the heap
-            guard.heap.push(ExpirationEntry { expires_at, identifier });
+            // Replace the record's heap entry rather than stacking another one
+            guard.forget_expiration(&identifier);
+            guard.expirations.insert(identifier.clone(), expires_at);
+            let sequence = guard.next_sequence;
+            guard.next_sequence += 1;
+            guard.heap.push(ExpirationEntry { expires_at, identifier, sequence });
         }
 
         // Enforce capacity after adding the new record
@@ -241,9 +279,8 @@ impl<R: Record + Send + Sync + 'static> Registry<R> for InMemoryRegistry<R>{
     /// * `Err(RegistryError)` - If an error occurs.
     async fn remove(&self, identifier: &str) -> Result<(), RegistryError> {
         let mut guard = self.inner.write().unwrap();
-        guard.records.remove(identifier);
-        // Note: Removing from the heap is not straightforward. For simplicity, we can leave it as is.
-        // Alternatively, implement a more sophisticated heap structure that allows removal.
+        guard.evict(identifier);
+        guard.forget_expiration(identifier);
         Ok(())
     }
 
@@ -254,7 +291,9 @@ impl<R: Record + Send + Sync + 'static> Registry<R> for InMemoryRegistry<R>{
         // Enforce new capacity
         while guard.records.len() > guard.capacity {
             if let Some(top) = guard.heap.pop() {
-                guard.records.remove(&top.identifier);
+                if guard.is_current(&top) {
+                    guard.evict(&top.identifier);
+                }
             } else {
                 break;
             }
@@ -395,6 +434,36 @@ mod tests {
         assert_eq!(records[0].identifier(), "service2");
     }
 
+    #[tokio::test]
+    async fn test_readding_record_resets_expiration() {
+        let registry = InMemoryRegistry::new(10);
+
+        registry.add(ServiceRecord::new("service1", "http", 8080, Some(1))).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        registry.add(ServiceRecord::new("service1", "http", 8080, Some(1))).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(600)).await;
+
+        assert!(
+            registry.get("service1").await.is_some(),
+            "Refreshed record must not expire on its original deadline."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_readding_record_keeps_one_heap_entry() {
+        let registry = InMemoryRegistry::new(10);
+
+        for _ in 0..1000 {
+            registry.add(ServiceRecord::new("service1", "http", 8080, None)).await.unwrap();
+        }
+        registry.add(ServiceRecord::new("service2", "http", 8081, Some(60))).await.unwrap();
+        registry.add(ServiceRecord::new("service2", "http", 8081, Some(60))).await.unwrap();
+        assert_eq!(registry.inner.read().unwrap().heap.len(), 2);
+
+        registry.remove("service1").await.unwrap();
+        assert_eq!(registry.inner.read().unwrap().heap.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_edge_case_expiration_and_capacity() {
         let registry = InMemoryRegistry::new(2);